@@ -11,7 +11,6 @@ pub mod util;
 use crate::term::Term;
 use crate::task::{Task, Punctuation};
 use crate::table::TaskTable;
-use std::collections::HashMap;
 use std::fmt;
 
 pub use util::{Emotion, ConceptBuilder};
@@ -280,17 +279,19 @@ impl TaskConcept {
         }
     }
     
-    /// Add a task to the concept
-    pub fn add_task(&mut self, task: Task) {
+    /// Add a task to the concept; returns the revision an eternal belief
+    /// or goal was merged into, if any
+    pub fn add_task(&mut self, task: Task, config: &crate::truth::TruthConfig) -> Option<Task> {
         match task.punctuation() {
-            Punctuation::Belief => self.beliefs.add(task),
-            Punctuation::Goal => self.goals.add(task),
+            Punctuation::Belief => return self.beliefs.add(task, config),
+            Punctuation::Goal => return self.goals.add(task, config),
             Punctuation::Question => self.questions.add(task),
             Punctuation::Quest => self.quests.add(task),
             Punctuation::Command => {
                 // Commands might be handled differently, for now we ignore them
             }
         }
+        None
     }
     
     /// Get the activation level
@@ -339,6 +340,12 @@ impl TaskConcept {
         &self.tasklinks
     }
     
+    /// Keep only the `max` most recent tasklinks
+    pub fn limit_tasklinks(&mut self, max: usize) {
+        let excess = self.tasklinks.len().saturating_sub(max);
+        self.tasklinks.drain(..excess);
+    }
+    
     /// Get all tasks in this concept
     pub fn tasks(&self, beliefs: bool, questions: bool, goals: bool, quests: bool) -> Vec<&Task> {
         let mut tasks = Vec::new();
@@ -2,8 +2,8 @@
 //!
 //! This module provides utilities for concept management in NARS.
 
-use std::sync::{Arc, Mutex};
-use crate::time::Time;
+use std::sync::Arc;
+use crate::time::Clock;
 use crate::concept::TaskConcept;
 use crate::term::Term;
 
@@ -50,17 +50,17 @@ impl Emotion {
     
     /// Set happiness value
     pub fn set_happiness(&mut self, value: f32) {
-        self.happiness = value.max(-1.0).min(1.0); // Clamp to [-1, 1]
+        self.happiness = value.clamp(-1.0, 1.0); // Clamp to [-1, 1]
     }
     
     /// Set sadness value
     pub fn set_sadness(&mut self, value: f32) {
-        self.sadness = value.max(0.0).min(1.0); // Clamp to [0, 1]
+        self.sadness = value.clamp(0.0, 1.0); // Clamp to [0, 1]
     }
     
     /// Set arousal value
     pub fn set_arousal(&mut self, value: f32) {
-        self.arousal = value.max(0.0).min(1.0); // Clamp to [0, 1]
+        self.arousal = value.clamp(0.0, 1.0); // Clamp to [0, 1]
     }
 }
 
@@ -76,7 +76,7 @@ pub struct ConceptBuilder {
     emotion: Option<Emotion>,
     
     /// Time reference
-    time: Option<Arc<Time>>,
+    time: Option<Arc<dyn Clock>>,
     
     /// Whether concepts should be built with task support
     task_concept_only: bool,
@@ -93,7 +93,7 @@ impl ConceptBuilder {
     }
     
     /// Initialize the concept builder with emotion and time
-    pub fn init(&mut self, emotion: Emotion, time: Arc<Time>) {
+    pub fn init(&mut self, emotion: Emotion, time: Arc<dyn Clock>) {
         self.emotion = Some(emotion);
         self.time = Some(time);
     }
@@ -112,9 +112,14 @@ impl ConceptBuilder {
     }
     
     /// Get the time reference
-    pub fn time(&self) -> Option<&Arc<Time>> {
+    pub fn time(&self) -> Option<&Arc<dyn Clock>> {
         self.time.as_ref()
     }
+    
+    /// Whether only task-capable concepts are built
+    pub fn task_concept_only(&self) -> bool {
+        self.task_concept_only
+    }
 }
 
 impl Default for ConceptBuilder {
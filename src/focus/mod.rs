@@ -1,9 +1,49 @@
 //! Focus mechanisms for NARS
 //!
-//! This module handles focus of attention in the NARS system.
+//! This module handles focus of attention in the NARS system: which tasks
+//! a reasoning cycle works on, and which beliefs each is combined with.
 
 pub mod util;
 pub mod pri_tree;
 
 pub use util::FocusBag;
-pub use pri_tree::PriTree;
\ No newline at end of file
+pub use pri_tree::PriTree;
+
+use crate::memory::ConceptMemory;
+use crate::task::Task;
+use rand::RngCore;
+
+/// Chooses what a reasoner thinks about
+pub trait Focus: Send {
+    /// Take in a task that was just input or derived
+    fn accept(&mut self, _task: &Task) {}
+    
+    /// Choose the tasks to reason about in a cycle
+    fn select(&mut self, memory: &dyn ConceptMemory, rng: &mut dyn RngCore) -> Vec<Task>;
+    
+    /// Choose the beliefs to combine a task with
+    fn partners(&mut self, task: &Task, memory: &dyn ConceptMemory, rng: &mut dyn RngCore) -> Vec<Task>;
+    
+    /// Let attention fade at the end of a cycle
+    fn commit(&mut self, _memory: &mut dyn ConceptMemory) {}
+    
+    /// Forget everything in focus
+    fn clear(&mut self) {}
+}
+
+/// Get the concepts above an activation threshold, most active first
+pub fn most_active(memory: &dyn ConceptMemory, threshold: f32, count: usize) -> Vec<crate::concept::TaskConcept> {
+    let mut concepts = memory.active_concepts(threshold);
+    concepts.sort_by(|a, b| b.activation().total_cmp(&a.activation()));
+    concepts.truncate(count);
+    concepts
+}
+
+/// Get the best beliefs of the most active concepts other than a task's own
+pub fn active_beliefs(task: &Task, memory: &dyn ConceptMemory, threshold: f32, count: usize) -> Vec<Task> {
+    most_active(memory, threshold, count)
+        .iter()
+        .filter(|concept| concept.term() != task.term())
+        .filter_map(|concept| concept.best_belief(None).cloned())
+        .collect()
+}
@@ -32,12 +32,6 @@ impl PriTree {
         *self.priorities.get(key).unwrap_or(&self.default_priority)
     }
     
-    /// Commit priority changes
-    pub fn commit(&mut self) {
-        // Apply any priority changes or updates
-        // In a real implementation, this would update the tree structure
-    }
-    
     /// Get all priorities
     pub fn priorities(&self) -> &HashMap<String, f32> {
         &self.priorities
@@ -77,17 +71,6 @@ mod tests {
         assert_eq!(pri_tree.get_priority("nonexistent"), 0.5);
     }
 
-    #[test]
-    fn test_commit() {
-        let mut pri_tree = PriTree::new();
-        pri_tree.set_priority("test", 0.8);
-        
-        // Commit should not panic
-        pri_tree.commit();
-        
-        assert_eq!(pri_tree.get_priority("test"), 0.8);
-    }
-
     #[test]
     fn test_clear() {
         let mut pri_tree = PriTree::new();
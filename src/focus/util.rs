@@ -3,7 +3,10 @@
 //! This module implements the focus bag which manages the focus of attention
 //! in the NARS system.
 
+use super::Focus;
+use crate::memory::ConceptMemory;
 use crate::task::Task;
+use rand::RngCore;
 use std::collections::VecDeque;
 
 /// Focus bag for managing attention focus
@@ -52,17 +55,16 @@ impl FocusBag {
         }
     }
     
-    /// Commit focus changes
-    pub fn commit(&mut self) {
-        // Apply decay to tasks in focus - but task doesn't have decay_activation method
-        // For now, just keep the tasks as they are
-    }
-    
     /// Clear the focus bag
     pub fn clear(&mut self) {
         self.tasks.clear();
     }
     
+    /// Get the priority threshold
+    pub fn priority_threshold(&self) -> f32 {
+        self.priority_threshold
+    }
+    
     /// Get tasks in the focus bag
     pub fn tasks(&self) -> &VecDeque<Task> {
         &self.tasks
@@ -74,10 +76,33 @@ impl FocusBag {
     }
 }
 
+/// Reasons about each task taken in once, highest priority first, with the
+/// beliefs of the most active concepts; since every cycle takes all the
+/// tasks in the bag, nothing is left in it to fade on commit
+impl Focus for FocusBag {
+    fn accept(&mut self, task: &Task) {
+        FocusBag::accept(self, task.clone());
+    }
+    
+    fn select(&mut self, _memory: &dyn ConceptMemory, _rng: &mut dyn RngCore) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self.tasks.drain(..).collect();
+        tasks.sort_by(|a, b| b.budget().priority().total_cmp(&a.budget().priority()));
+        tasks
+    }
+    
+    fn partners(&mut self, task: &Task, memory: &dyn ConceptMemory, _rng: &mut dyn RngCore) -> Vec<Task> {
+        super::active_beliefs(task, memory, self.priority_threshold, self.capacity)
+    }
+    
+    fn clear(&mut self) {
+        FocusBag::clear(self);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::{Task, TaskBuilder, Budget, Punctuation};
+    use crate::task::{TaskBuilder, Budget, Punctuation};
     use crate::term::Term;
     use crate::truth::Truth;
 
@@ -163,25 +188,26 @@ mod tests {
     }
     
     #[test]
-    fn test_focus_bag_commit() {
+    fn test_focus_bag_select() {
         let mut focus_bag = FocusBag::new(10);
+        for (id, priority) in [(1, 0.2), (2, 0.8)] {
+            let task = TaskBuilder::new()
+                .id(id)
+                .term(Term::Atomic(crate::term::atom::Atomic::new_atom("test")))
+                .truth(Truth::new(0.8, 0.9))
+                .punctuation(Punctuation::Belief)
+                .budget(Budget::new(priority, 0.5, 0.5))
+                .build()
+                .unwrap();
+            Focus::accept(&mut focus_bag, &task);
+        }
         
-        // Add a task
-        let term = Term::Atomic(crate::term::atom::Atomic::new_atom("test"));
-        let task = TaskBuilder::new()
-            .id(1)
-            .term(term)
-            .truth(Truth::new(0.8, 0.9))
-            .punctuation(Punctuation::Belief)
-            .time(crate::task::Time::Eternal)
-            .budget(Budget::new(0.5, 0.5, 0.5))
-            .build()
-            .unwrap();
-            
-        focus_bag.accept(task);
-        
-        // Check that commit doesn't panic
-        focus_bag.commit();
+        // Each task is selected once, highest priority first
+        let memory = crate::memory::Memory::new();
+        let mut rng = rand::rngs::mock::StepRng::new(0, 1);
+        let selected: Vec<u64> = focus_bag.select(&memory, &mut rng).iter().map(Task::id).collect();
+        assert_eq!(selected, vec![2, 1]);
+        assert!(focus_bag.select(&memory, &mut rng).is_empty());
     }
     
     #[test]
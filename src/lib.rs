@@ -13,8 +13,10 @@ pub mod memory;
 pub mod table;
 pub mod nal;
 pub mod parser;
+pub mod narsese;
 pub mod focus;
 pub mod time;
+pub mod testing;
 
 // Re-export the main components for easier access
 pub use term::Term;
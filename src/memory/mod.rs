@@ -11,10 +11,79 @@
 pub mod radix_tree;
 
 use crate::concept::TaskConcept;
-use crate::term::{Term, TermTrait};
+use crate::term::Term;
+use crate::term::intern::{TermInterner, TermRef};
 use crate::memory::radix_tree::RadixTree;
 use std::fmt;
 
+/// Smallest number of interned terms at which the interner is rebuilt
+const MIN_TERMS_LIMIT: usize = 4096;
+
+/// Where a reasoner keeps its concepts
+///
+/// Concepts are handed out by value; a changed concept is stored again with
+/// `add_concept`. `Memory` is the default implementation.
+pub trait ConceptMemory: Send {
+    /// Get the concept of a term
+    fn get_concept(&self, term: &Term) -> Option<TaskConcept>;
+    
+    /// Add or update a concept
+    fn add_concept(&mut self, concept: TaskConcept);
+    
+    /// Remove the concept of a term
+    fn remove_concept(&mut self, term: &Term) -> Option<TaskConcept>;
+    
+    /// Get all concepts
+    fn concepts(&self) -> Vec<TaskConcept>;
+    
+    /// Get the number of concepts
+    fn len(&self) -> usize;
+    
+    /// Check if there are no concepts
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Remove every concept
+    fn clear(&mut self);
+    
+    /// Get concepts above an activation threshold
+    fn active_concepts(&self, threshold: f32) -> Vec<TaskConcept> {
+        self.concepts()
+            .into_iter()
+            .filter(|concept| concept.activation() >= threshold)
+            .collect()
+    }
+    
+    /// Apply activation decay to all concepts
+    fn decay_activation(&mut self, rate: f32) {
+        for mut concept in self.concepts() {
+            concept.decay_activation(rate);
+            self.add_concept(concept);
+        }
+    }
+    
+    /// Link the concept of a task to the task, and the concept of every
+    /// compound in its term to the compound's subterms
+    fn create_links(&mut self, task: &crate::task::Task) {
+        for (_, sub) in task.term().dfs() {
+            let Term::Compound(compound) = sub else {
+                continue;
+            };
+            if let Some(mut concept) = self.get_concept(sub) {
+                for subterm in compound.subterms() {
+                    concept.add_termlink(subterm.clone());
+                }
+                self.add_concept(concept);
+            }
+        }
+        if let Some(mut concept) = self.get_concept(task.term()) {
+            concept.add_tasklink(task.id());
+            self.add_concept(concept);
+        }
+    }
+}
+
 /// Memory struct representing the NARS memory system
 #[derive(Debug)]
 pub struct Memory {
@@ -32,6 +101,14 @@ pub struct Memory {
     
     /// Concept linking parameters
     linking: LinkingParams,
+    
+    /// Interned normal forms of the terms of concepts, which give concepts
+    /// their keys
+    terms: TermInterner,
+    
+    /// Number of interned terms past which the interner is rebuilt from
+    /// the concepts still held
+    terms_limit: usize,
 }
 
 /// Parameters for concept linking
@@ -76,6 +153,8 @@ impl Memory {
             forgetting_rate: 0.1,
             min_activation: 0.01,
             linking,
+            terms: TermInterner::new(),
+            terms_limit: MIN_TERMS_LIMIT,
         }
     }
     
@@ -91,56 +170,84 @@ impl Memory {
     
     /// Get a concept by term
     pub fn get_concept(&self, term: &Term) -> Option<TaskConcept> {
-        let key = Self::term_to_key(term);
+        let key = self.find_key(term)?;
         self.concepts.get(&key)
     }
     
     /// Get a mutable reference to a concept by term
     /// Note: This requires removing and re-inserting the concept
     pub fn get_concept_mut(&mut self, term: &Term) -> Option<TaskConcept> {
-        let key = Self::term_to_key(term);
-        self.concepts.get(&key)
+        self.get_concept(term)
     }
     
     /// Add or update a concept in memory
     pub fn add_concept(&mut self, concept: TaskConcept) {
-        let key = Self::term_to_key(concept.term());
-        self.concepts.insert(key, concept);
+        let key = self.term_to_key(concept.term());
+        self.concepts.insert(key.to_vec(), concept);
         
-        // The radix tree handles capacity management internally
+        if self.concepts.len() > self.capacity {
+            self.forget_concepts();
+        }
+        
+        // Removed concepts leave the terms of their subterms behind; those
+        // are dropped when the interner is next rebuilt
+        if self.terms.len() > self.terms_limit {
+            self.compact();
+        }
     }
     
     /// Create a concept for a term if it doesn't exist
     pub fn get_or_create_concept(&mut self, term: &Term) -> TaskConcept {
-        let key = Self::term_to_key(term);
-        
-        if let Some(concept) = self.concepts.get(&key) {
-            concept
-        } else {
-            let concept = TaskConcept::new(term.clone());
-            self.concepts.insert(key, concept.clone());
-            concept
+        if let Some(concept) = self.get_concept(term) {
+            return concept;
         }
+        let concept = TaskConcept::new(term.clone());
+        self.add_concept(concept.clone());
+        concept
     }
     
     /// Remove a concept from memory
     pub fn remove_concept(&mut self, term: &Term) -> Option<TaskConcept> {
-        let key = Self::term_to_key(term);
-        self.concepts.remove(&key)
+        let normal = self.terms.get_normal(term)?;
+        let removed = self.concepts.remove(&Self::key(&normal));
+        self.terms.remove(&normal);
+        removed
     }
     
     /// Apply activation decay to all concepts
     pub fn decay_activation(&mut self, rate: f32) {
-        // Get all concepts, decay them, and reinsert them
-        let concepts = self.concepts.values();
-        // In a real implementation, we would need to handle this differently
-        // For now, we'll skip this implementation detail
+        self.concepts.update_values(|concept| concept.decay_activation(rate));
     }
     
-    /// Forget concepts with low activation
+    /// Forget the concepts below the minimum activation and then, while
+    /// memory is over capacity, the least active ones; memory does this
+    /// itself whenever a new concept takes it over capacity
     pub fn forget_concepts(&mut self) {
-        // The radix tree handles forgetting internally based on capacity
-        // In a more sophisticated implementation, we would implement priority-based forgetting
+        let mut activations: Vec<f32> = self.concepts.values().iter().map(TaskConcept::activation).collect();
+        let excess = activations.len().saturating_sub(self.capacity);
+        
+        // Concepts below the cutoff go, and enough of those at it
+        let (cutoff, mut at_cutoff) = if excess > 0 {
+            activations.sort_unstable_by(f32::total_cmp);
+            let cutoff = activations[excess - 1];
+            (cutoff, excess - activations.partition_point(|&a| a < cutoff))
+        } else {
+            (f32::NEG_INFINITY, 0)
+        };
+        let min_activation = self.min_activation;
+        let forgotten = self.concepts.retain(|concept| {
+            let activation = concept.activation();
+            if activation == cutoff && at_cutoff > 0 {
+                at_cutoff -= 1;
+                return false;
+            }
+            activation >= cutoff && activation >= min_activation
+        });
+        for concept in forgotten {
+            if let Some(normal) = self.terms.get_normal(concept.term()) {
+                self.terms.remove(&normal);
+            }
+        }
     }
     
     /// Get all concepts
@@ -171,6 +278,8 @@ impl Memory {
     pub fn clear(&mut self) {
         // Create a new radix tree to clear all concepts
         self.concepts = RadixTree::new(self.capacity);
+        self.terms.clear();
+        self.terms_limit = MIN_TERMS_LIMIT;
     }
     
     /// Set the forgetting rate
@@ -197,11 +306,12 @@ impl Memory {
         
         // Transfer all concepts to the new tree
         for concept in self.concepts.values() {
-            let key = Self::term_to_key(concept.term());
-            new_concepts.insert(key, concept);
+            let key = self.term_to_key(concept.term());
+            new_concepts.insert(key.to_vec(), concept);
         }
         
         self.concepts = new_concepts;
+        self.compact();
     }
     
     /// Get the linking parameters
@@ -220,66 +330,119 @@ impl Memory {
         self.create_termlinks_for_term(task.term());
         
         // Get the key for this task's concept
-        let key = Self::term_to_key(task.term());
+        let Some(key) = self.find_key(task.term()) else {
+            return;
+        };
         
         // Add a tasklink to the concept
         if let Some(mut concept) = self.concepts.get(&key) {
             concept.add_tasklink(task.id());
-            
-            // Limit the number of tasklinks
-            if concept.tasklinks().len() > self.linking.max_tasklinks {
-                // For now, just truncate
-                // A more sophisticated implementation might prioritize based on relevance
-            }
+            concept.limit_tasklinks(self.linking.max_tasklinks);
             
             // Reinsert the modified concept
-            self.concepts.insert(key, concept);
+            self.concepts.insert(key.to_vec(), concept);
         }
     }
     
-    /// Create termlinks for a term
+    /// Create termlinks for a term: the concept of every compound in it
+    /// links to the compound's subterms
     fn create_termlinks_for_term(&mut self, term: &Term) {
-        // For compound terms, collect subterms first to avoid borrowing issues
-        let subterms = if let Term::Compound(compound) = term {
-            compound.subterms().to_vec()
-        } else {
-            Vec::new()
-        };
-        
-        // Add termlinks based on the term structure
-        let key = Self::term_to_key(term);
-        if let Some(mut concept) = self.concepts.get(&key) {
-            // For compound terms, create links to subterms
-            for subterm in &subterms {
-                concept.add_termlink(subterm.clone());
+        for (_, sub) in term.dfs() {
+            let Term::Compound(compound) = sub else {
+                continue;
+            };
+            let Some(key) = self.find_key(sub) else {
+                continue;
+            };
+            if let Some(mut concept) = self.concepts.get(&key) {
+                for subterm in compound.subterms() {
+                    concept.add_termlink(subterm.clone());
+                }
+                
+                // Reinsert the modified concept
+                self.concepts.insert(key.to_vec(), concept);
             }
-            
-            // Reinsert the modified concept
-            self.concepts.insert(key, concept);
         }
-        
-        // Recursively create links for subterms
-        for subterm in &subterms {
-            self.create_termlinks_for_term(subterm);
+    }
+    
+    /// Get the interner holding the normal forms of concept terms
+    pub fn terms(&self) -> &TermInterner {
+        &self.terms
+    }
+    
+    /// Rebuild the interner from the terms of the concepts still held
+    fn compact(&mut self) {
+        let concepts = self.concepts.values();
+        self.terms.clear();
+        self.concepts = RadixTree::new(self.capacity);
+        for concept in concepts {
+            let key = self.term_to_key(concept.term());
+            self.concepts.insert(key.to_vec(), concept);
         }
+        self.terms_limit = (2 * self.terms.len()).max(MIN_TERMS_LIMIT);
     }
     
-    /// Convert a term to a byte sequence for use as a key in the radix tree
-    fn term_to_key(term: &Term) -> Vec<u8> {
-        // Start with the complexity as a 2-byte prefix for sorting by complexity
-        let complexity = term.complexity() as u16;
-        let mut key = Vec::with_capacity(2 + 32); // Estimate capacity
-        
-        // Add complexity prefix (big endian)
-        key.extend_from_slice(&complexity.to_be_bytes());
-        
-        // Add term representation as bytes
-        key.extend(format!("{}", term.concept()).as_bytes());
-        
+    /// Key of a term that has a concept, if it has one; looking a term up
+    /// interns nothing
+    fn find_key(&self, term: &Term) -> Option<[u8; 10]> {
+        self.terms.get_normal(term).map(|normal| Self::key(&normal))
+    }
+    
+    /// Key of a term in the radix tree, interning its normal form
+    fn term_to_key(&self, term: &Term) -> [u8; 10] {
+        Self::key(&self.terms.intern_normal(term))
+    }
+    
+    /// Key of a normal form: its complexity, for sorting by complexity,
+    /// followed by its interned id; terms with the same normal form share
+    /// a key
+    fn key(normal: &TermRef) -> [u8; 10] {
+        let complexity = normal.volume().min(u16::MAX as u32) as u16;
+        let mut key = [0; 10];
+        key[..2].copy_from_slice(&complexity.to_be_bytes());
+        key[2..].copy_from_slice(&normal.id().to_be_bytes());
         key
     }
 }
 
+impl ConceptMemory for Memory {
+    fn get_concept(&self, term: &Term) -> Option<TaskConcept> {
+        Memory::get_concept(self, term)
+    }
+    
+    fn add_concept(&mut self, concept: TaskConcept) {
+        Memory::add_concept(self, concept)
+    }
+    
+    fn remove_concept(&mut self, term: &Term) -> Option<TaskConcept> {
+        Memory::remove_concept(self, term)
+    }
+    
+    fn concepts(&self) -> Vec<TaskConcept> {
+        Memory::concepts(self)
+    }
+    
+    fn len(&self) -> usize {
+        Memory::len(self)
+    }
+    
+    fn clear(&mut self) {
+        Memory::clear(self)
+    }
+    
+    fn active_concepts(&self, threshold: f32) -> Vec<TaskConcept> {
+        Memory::active_concepts(self, threshold)
+    }
+    
+    fn decay_activation(&mut self, rate: f32) {
+        Memory::decay_activation(self, rate)
+    }
+    
+    fn create_links(&mut self, task: &crate::task::Task) {
+        Memory::create_links(self, task)
+    }
+}
+
 impl Default for Memory {
     fn default() -> Self {
         Self::new()
@@ -348,6 +511,42 @@ mod tests {
         assert_eq!(memory.len(), 0);
     }
 
+    #[test]
+    fn test_normalized_concept_keys() {
+        let mut memory = Memory::new();
+        let parse = |narsese| crate::parser::Parser::parse_term(narsese).unwrap();
+        
+        memory.get_or_create_concept(&parse("(a && b)"));
+        assert!(memory.get_concept(&parse("(b && a)")).is_some());
+        memory.get_or_create_concept(&parse("(&&, b, a, a)"));
+        assert_eq!(memory.len(), 1);
+        
+        memory.get_or_create_concept(&parse("<$x --> animal>"));
+        assert!(memory.get_concept(&parse("<$y --> animal>")).is_some());
+        assert!(memory.get_concept(&parse("<animal --> $y>")).is_none());
+        assert_eq!(memory.len(), 2);
+        
+        // Images share the concept of their product statement
+        memory.get_or_create_concept(&parse("<(*, a, b) --> r>"));
+        assert!(memory.get_concept(&parse("<a --> (/, r, _, b)>")).is_some());
+        assert!(memory.get_concept(&parse("<b --> (/, r, a, _)>")).is_some());
+        assert_eq!(memory.len(), 3);
+        
+        // Repeated lookups reuse the interned normal form
+        let interned = memory.terms().len();
+        assert!(memory.get_concept(&parse("<$y --> animal>")).is_some());
+        assert_eq!(memory.terms().len(), interned);
+        
+        // Misses intern nothing, and removed concepts give up their term
+        assert!(memory.get_concept(&parse("<dog --> animal>")).is_none());
+        assert_eq!(memory.terms().len(), interned);
+        memory.remove_concept(&parse("<$z --> animal>"));
+        assert_eq!(memory.terms().len(), interned - 1);
+        
+        memory.clear();
+        assert!(memory.terms().is_empty());
+    }
+
     #[test]
     fn test_activation_decay() {
         let mut memory = Memory::new();
@@ -367,8 +566,7 @@ mod tests {
         
         // Check that activation decreased
         let concept_ref = memory.get_concept(&term).unwrap();
-        // Skip this assertion since decay_activation is not implemented yet
-        // assert!(concept_ref.activation() < 1.0);
+        assert!((concept_ref.activation() - 0.9).abs() < 1e-6);
     }
 
     #[test]
@@ -393,10 +591,23 @@ mod tests {
         // Forget concepts with low activation
         memory.forget_concepts();
         
-        // The radix tree doesn't implement threshold-based forgetting yet
-        // so we'll just check that the concepts still exist
-        assert_eq!(memory.len(), 2);
+        assert_eq!(memory.len(), 1);
         assert!(memory.get_concept(&term2).is_some());
-        assert!(memory.get_concept(&term1).is_some());
+        assert!(memory.get_concept(&term1).is_none());
+    }
+    
+    #[test]
+    fn test_forget_past_capacity() {
+        let mut memory = Memory::with_capacity(2);
+        for (name, activation) in [("a", 0.5), ("b", 0.2), ("c", 0.5)] {
+            let mut concept = TaskConcept::new(Term::Atomic(Atomic::new_atom(name)));
+            concept.set_activation(activation);
+            memory.add_concept(concept);
+        }
+        
+        // The least active concept makes room, and its term is released
+        assert_eq!(memory.len(), 2);
+        assert!(memory.get_concept(&Term::Atomic(Atomic::new_atom("b"))).is_none());
+        assert_eq!(memory.terms().len(), 2);
     }
 }
\ No newline at end of file
@@ -40,11 +40,12 @@ where
         if common_prefix_len == fragment.len() {
             // Key continues beyond fragment
             let remaining_key = key[common_prefix_len..].to_vec();
-            return insert_recursive(&mut child_node, remaining_key, value, size);
+            insert_recursive(&mut child_node, remaining_key, value, size)
         } else {
-            // Need to split the node
+            // Need to split the node; release the child first since the split relocks it
+            drop(child_node);
             split_node(node, first_byte, common_prefix_len, key, value, size);
-            return None;
+            None
         }
     } else {
         // Create new node
@@ -136,7 +137,7 @@ where
         let mut child_node = child.write();
         let fragment = child_node.key_fragment().to_vec();
         
-        if key.len() >= fragment.len() && &key[..fragment.len()] == &fragment[..] {
+        if key.len() >= fragment.len() && key[..fragment.len()] == fragment[..] {
             let remaining_key = &key[fragment.len()..];
             let result = remove_recursive(&mut child_node, remaining_key, size);
             
@@ -146,7 +147,7 @@ where
                 node.remove_child(first_byte);
             }
             
-            return result;
+            result
         } else {
             None
         }
@@ -193,11 +194,6 @@ impl<V> RadixTreeNode<V> {
         self.value.as_ref()
     }
     
-    /// Get a mutable reference to the value (if any)
-    fn value_mut(&mut self) -> Option<&mut V> {
-        self.value.as_mut()
-    }
-    
     /// Set the value for this node
     fn set_value(&mut self, value: V) {
         self.value = Some(value);
@@ -238,7 +234,8 @@ pub struct RadixTree<V> {
     /// Number of values stored in the tree
     size: usize,
     
-    /// Maximum size of the tree
+    /// Maximum size of the tree; the tree only records it, since ranking
+    /// values to evict is up to the owner
     capacity: usize,
 }
 
@@ -311,11 +308,6 @@ where
             insert_recursive(&mut root, key, value, &mut self.size)
         };
         
-        // If we're exceeding capacity, perform garbage collection
-        if self.size > self.capacity {
-            self.perform_garbage_collection();
-        }
-        
         result
     }
     
@@ -332,18 +324,6 @@ where
     }
     
     
-    /// Perform garbage collection to remove excess concepts
-    fn perform_garbage_collection(&mut self) {
-        // This is a placeholder implementation
-        // In a real implementation, this would remove concepts based on priority/budget
-        // For now, we'll just remove random concepts if we exceed capacity by too much
-        let overflow = self.size.saturating_sub(self.capacity);
-        if overflow > 0 {
-            // Remove some concepts (simplified implementation)
-            // A real implementation would use a more sophisticated strategy
-        }
-    }
-    
     /// Get all values in the tree
     pub fn values(&self) -> Vec<V> {
         let root = self.root.read();
@@ -358,12 +338,48 @@ where
             result.push(value.clone());
         }
         
-        for child in node.children().values() {
+        // Visit children in key order so that iteration is deterministic
+        let mut children: Vec<_> = node.children().iter().collect();
+        children.sort_unstable_by_key(|(key, _)| **key);
+        for (_, child) in children {
             let child_node = child.read();
             self.collect_values(&child_node, result);
         }
     }
     
+    /// Change every value in place
+    pub fn update_values(&mut self, mut update: impl FnMut(&mut V)) {
+        fn visit<V>(node: &mut RadixTreeNode<V>, update: &mut impl FnMut(&mut V)) {
+            if let Some(value) = node.value.as_mut() {
+                update(value);
+            }
+            for child in node.children.values() {
+                visit(&mut child.write(), update);
+            }
+        }
+        visit(&mut self.root.write(), &mut update);
+    }
+    
+    /// Remove the values for which `keep` is false, in key order; returns
+    /// the removed values
+    pub fn retain(&mut self, mut keep: impl FnMut(&V) -> bool) -> Vec<V> {
+        fn visit<V>(node: &RadixTreeNode<V>, prefix: &mut Vec<u8>, keep: &mut impl FnMut(&V) -> bool, keys: &mut Vec<Vec<u8>>) {
+            prefix.extend_from_slice(node.key_fragment());
+            if node.value().is_some_and(|value| !keep(value)) {
+                keys.push(prefix.clone());
+            }
+            let mut children: Vec<_> = node.children().iter().collect();
+            children.sort_unstable_by_key(|(key, _)| **key);
+            for (_, child) in children {
+                visit(&child.read(), prefix, keep, keys);
+            }
+            prefix.truncate(prefix.len() - node.key_fragment().len());
+        }
+        let mut keys = Vec::new();
+        visit(&self.root.read(), &mut Vec::new(), &mut keep, &mut keys);
+        keys.iter().filter_map(|key| self.remove(key)).collect()
+    }
+    
     /// Update a value with a function
     pub fn update_with<F>(&mut self, key: Vec<u8>, f: F) -> Option<V>
     where
@@ -427,4 +443,20 @@ mod tests {
         assert_eq!(tree.get(b"team"), Some("value2".to_string()));
         assert_eq!(tree.get(b"te"), Some("value3".to_string()));
     }
+    
+    #[test]
+    fn test_radix_tree_update_and_retain() {
+        let mut tree: RadixTree<u32> = RadixTree::new(100);
+        for (i, key) in ["test", "team", "te", "x"].iter().enumerate() {
+            tree.insert(key.as_bytes().to_vec(), i as u32);
+        }
+        
+        tree.update_values(|value| *value *= 10);
+        assert_eq!(tree.get(b"team"), Some(10));
+        
+        // Removed in key order
+        assert_eq!(tree.retain(|value| *value == 10), vec![20, 0, 30]);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.values(), vec![10]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,457 @@
+//! Rule-driven derivation
+//!
+//! The deriver walks a task/belief pair through the rule trie and
+//! instantiates the conclusions of the rules that fire. It only deals with
+//! terms, truth and time; the reasoner turns derivations into tasks with ids,
+//! budgets and evidence.
+
+use super::rule::{self, ConclusionDt, Rule};
+use super::trie::RuleTrie;
+use crate::task::{Punctuation, Task, Time};
+use crate::term::builder::TermBuilder;
+use crate::term::compound::Compound;
+use crate::term::temporal::{Dt, Sequence};
+use crate::term::unify::{self, VarTypes};
+use crate::term::{Op, Term, TermTrait};
+use crate::truth::{Truth, TruthConfig};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Conclusion of one rule applied to a task and a belief
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derivation {
+    /// Name of the rule that fired
+    pub rule: String,
+
+    /// Derived term
+    pub term: Term,
+
+    /// Derived truth value
+    pub truth: Truth,
+
+    /// Occurrence time of the conclusion
+    pub time: Time,
+
+    /// Punctuation of the conclusion
+    pub punctuation: Punctuation,
+}
+
+/// Applies a set of rules to premise pairs
+#[derive(Debug, Clone)]
+pub struct Deriver {
+    rules: Vec<Rule>,
+    trie: RuleTrie,
+    counters: Counters,
+}
+
+impl Deriver {
+    /// Create a deriver over a rule set, compiling it into a trie
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Deriver {
+            trie: RuleTrie::new(&rules),
+            counters: Counters::new(rules.len()),
+            rules,
+        }
+    }
+
+    /// Get the rules
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Get the compiled rule trie
+    pub fn trie(&self) -> &RuleTrie {
+        &self.trie
+    }
+
+    /// Apply the rules reached through the trie to a task and a belief; both
+    /// premises need truth values. Conclusions hold at the task's time, so
+    /// the belief is projected there, unless the conclusion records the
+    /// interval between the two. Premises sharing evidence derive nothing,
+    /// or a task could be confirmed by its own consequences
+    pub fn derive(&self, task: &Task, belief: &Task, now: i64, config: &TruthConfig) -> Vec<Derivation> {
+        let (Some(task_truth), Some(belief_truth)) = (task.truth(), belief.truth()) else {
+            return Vec::new();
+        };
+        if task.stamp().overlaps(belief.stamp()) {
+            return Vec::new();
+        }
+        let projected = belief.truth_at(task.time(), now, config).unwrap_or(*belief_truth);
+        let found = self.trie.matches(task, belief);
+        self.counters.premise(found.rules.len(), found.checks);
+        found
+            .rules
+            .into_iter()
+            .filter_map(|index| {
+                let rule = &self.rules[index];
+                self.counters.visit(index);
+                // Commutative subterms may match in any order; the first
+                // binding of the pattern variables meeting the
+                // preconditions is used
+                let mut conclusion = None;
+                let premises = [(rule.task(), task.term()), (rule.belief(), belief.term())];
+                unify::unify_pairs(&premises, VarTypes::PATTERN, &mut |subst| {
+                    if !rule.preconditions().iter().all(|p| p.holds(task.time(), belief.time(), config.duration, subst)) {
+                        return false;
+                    }
+                    conclusion = Some(rule.conclusion().apply(subst));
+                    true
+                });
+
+                let mut term = conclusion?;
+                let belief_truth = match rule.conclusion_dt() {
+                    ConclusionDt::Interval => {
+                        term = with_interval(term, task.time(), belief.time());
+                        belief_truth
+                    },
+                    ConclusionDt::Template => &projected,
+                };
+                // Invalid conclusions, and ones that reduce to true, false
+                // or null, say nothing
+                let term = TermBuilder::rebuild(&term).ok().filter(|term| term.op_id() != Op::Bool)?;
+                self.counters.fire(index);
+                Some(Derivation {
+                    rule: rule.name().to_string(),
+                    term,
+                    truth: rule.truth().apply(task_truth, belief_truth, config),
+                    time: task.time(),
+                    punctuation: rule.task_punctuation(),
+                })
+            })
+            .collect()
+    }
+
+    /// Report how much matching work the premises so far have caused
+    pub fn stats(&self) -> DeriverStats {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let mut histogram: Vec<u64> = self.counters.histogram.iter().map(load).collect();
+        while histogram.len() > 1 && histogram.last() == Some(&0) {
+            histogram.pop();
+        }
+        DeriverStats {
+            premises: load(&self.counters.premises),
+            checks: load(&self.counters.checks),
+            rules_visited: self.counters.visits.iter().map(load).sum(),
+            derivations: self.counters.fired.iter().map(load).sum(),
+            histogram,
+            rules: self
+                .rules
+                .iter()
+                .enumerate()
+                .map(|(index, rule)| RuleStats {
+                    name: rule.name().to_string(),
+                    visits: load(&self.counters.visits[index]),
+                    fired: load(&self.counters.fired[index]),
+                })
+                .collect(),
+        }
+    }
+
+    /// Reset the statistics
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+}
+
+impl Default for Deriver {
+    fn default() -> Self {
+        Deriver::new(rule::standard_rules())
+    }
+}
+
+/// Matching work done by a deriver, for profiling rule sets
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeriverStats {
+    /// Premise pairs walked through the trie
+    pub premises: u64,
+
+    /// Trie checks evaluated
+    pub checks: u64,
+
+    /// Rules reached and matched in full
+    pub rules_visited: u64,
+
+    /// Rules that fired
+    pub derivations: u64,
+
+    /// Number of premise pairs by how many rules each visited
+    pub histogram: Vec<u64>,
+
+    /// Per-rule counts, in rule order
+    pub rules: Vec<RuleStats>,
+}
+
+/// Matching work done by one rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleStats {
+    /// Rule name
+    pub name: String,
+
+    /// Premise pairs that reached the rule
+    pub visits: u64,
+
+    /// Premise pairs the rule fired on
+    pub fired: u64,
+}
+
+impl DeriverStats {
+    /// Average number of rules visited per premise pair
+    pub fn mean_rules_visited(&self) -> f64 {
+        if self.premises == 0 {
+            0.0
+        } else {
+            self.rules_visited as f64 / self.premises as f64
+        }
+    }
+
+    /// Most rules visited by a single premise pair
+    pub fn max_rules_visited(&self) -> usize {
+        self.histogram.iter().rposition(|&count| count > 0).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for DeriverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} premises, {} checks, {} rules visited ({:.2} per premise, max {}), {} derivations",
+            self.premises,
+            self.checks,
+            self.rules_visited,
+            self.mean_rules_visited(),
+            self.max_rules_visited(),
+            self.derivations,
+        )?;
+        for (visited, count) in self.histogram.iter().enumerate().filter(|(_, count)| **count > 0) {
+            writeln!(f, "  {:>4} rules: {} premises", visited, count)?;
+        }
+        for rule in &self.rules {
+            writeln!(f, "  {:<24} {:>8} visits {:>8} fired", rule.name, rule.visits, rule.fired)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared counters behind `Deriver::stats`, updated through `&self`
+#[derive(Debug)]
+struct Counters {
+    premises: AtomicU64,
+    checks: AtomicU64,
+    histogram: Vec<AtomicU64>,
+    visits: Vec<AtomicU64>,
+    fired: Vec<AtomicU64>,
+}
+
+impl Counters {
+    fn new(rules: usize) -> Self {
+        let zeros = |n: usize| (0..n).map(|_| AtomicU64::new(0)).collect();
+        Counters {
+            premises: AtomicU64::new(0),
+            checks: AtomicU64::new(0),
+            histogram: zeros(rules + 1),
+            visits: zeros(rules),
+            fired: zeros(rules),
+        }
+    }
+
+    fn premise(&self, rules: usize, checks: usize) {
+        self.premises.fetch_add(1, Ordering::Relaxed);
+        self.checks.fetch_add(checks as u64, Ordering::Relaxed);
+        self.histogram[rules].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn visit(&self, rule: usize) {
+        self.visits[rule].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn fire(&self, rule: usize) {
+        self.fired[rule].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        let counters = [&self.premises, &self.checks]
+            .into_iter()
+            .chain(&self.histogram)
+            .chain(&self.visits)
+            .chain(&self.fired);
+        for counter in counters {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Clone for Counters {
+    fn clone(&self) -> Self {
+        let copy = |counters: &[AtomicU64]| counters.iter().map(|c| AtomicU64::new(c.load(Ordering::Relaxed))).collect();
+        Counters {
+            premises: AtomicU64::new(self.premises.load(Ordering::Relaxed)),
+            checks: AtomicU64::new(self.checks.load(Ordering::Relaxed)),
+            histogram: copy(&self.histogram),
+            visits: copy(&self.visits),
+            fired: copy(&self.fired),
+        }
+    }
+}
+
+/// Time a two-part conclusion by its premises: the first part happened at
+/// `from`, the second at `to`. A conjunction becomes the sequence of their
+/// events; any other compound gets the offset from the end of the first
+/// part to the start of the second.
+fn with_interval(term: Term, from: Time, to: Time) -> Term {
+    let (Term::Compound(compound), Time::Tense(from), Time::Tense(to)) = (&term, from, to) else {
+        return term;
+    };
+    let [first, second] = compound.subterms() else {
+        return term;
+    };
+    if compound.op_id() == Op::Conjunction {
+        let mut sequence = Sequence::of(first);
+        sequence.add(to - from, second);
+        return sequence.to_term().unwrap_or(term);
+    }
+    let dt = Dt::from_time(to - from - first.event_range());
+    Term::Compound(Compound::new_dt(compound.op_id(), compound.subterms().to_vec(), dt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nal::rule_file;
+    use crate::parser::Parser;
+    use crate::task::stamp::Stamp;
+    use crate::task::{Punctuation, TaskBuilder};
+
+    fn belief(narsese: &str, time: Time) -> Task {
+        TaskBuilder::new()
+            .term(Parser::parse_term(narsese).unwrap())
+            .truth(Truth::new(1.0, 0.9))
+            .punctuation(Punctuation::Belief)
+            .time(time)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_commutative_premises() {
+        use crate::nal::rule::TruthFn;
+        let deriver = Deriver::new(vec![
+            Rule::new("analogy", "<@M <-> @P>", "<@S --> @M>", "<@S --> @P>", TruthFn::Analogy).unwrap(),
+            Rule::new("done", "(&&, @A, done)", "@B", "<@A --> @B>", TruthFn::Deduction).unwrap(),
+        ]);
+        let derive = |task: &str, belief_term: &str| -> Vec<Term> {
+            deriver
+                .derive(&belief(task, Time::Eternal), &belief(belief_term, Time::Eternal), 0, &TruthConfig::default())
+                .into_iter()
+                .map(|derivation| derivation.term)
+                .collect()
+        };
+        let term = |narsese| Parser::parse_term(narsese).unwrap();
+
+        // The shared variable is found on either side of the similarity
+        assert_eq!(derive("<robin <-> bird>", "<swan --> robin>"), vec![term("<swan --> bird>")]);
+        assert_eq!(derive("<bird <-> robin>", "<swan --> robin>"), vec![term("<swan --> bird>")]);
+
+        // A constant inside a commutative compound matches in any position
+        assert_eq!(derive("(&&, done, work)", "me"), vec![term("<work --> me>")]);
+        assert_eq!(derive("(&&, work, done)", "me"), vec![term("<work --> me>")]);
+    }
+
+    #[test]
+    fn test_derive_deduction() {
+        let deriver = Deriver::default();
+        let derivations = deriver.derive(
+            &belief("<bird --> animal>", Time::Eternal),
+            &belief("<robin --> bird>", Time::Tense(2)),
+            0,
+            &TruthConfig::default(),
+        );
+        let deduction: Vec<_> = derivations.iter().filter(|d| d.rule == "deduction").collect();
+        assert_eq!(deduction.len(), 1);
+        assert_eq!(deduction[0].term, Parser::parse_term("<robin --> animal>").unwrap());
+        // The event belief is eternalized to meet the eternal task
+        assert_eq!(deduction[0].truth, Truth::new(1.0, 0.9 * 0.9 / 1.9));
+        assert_eq!(deduction[0].time, Time::Eternal);
+
+        let deduction = |belief_time| {
+            deriver
+                .derive(&belief("<bird --> animal>", Time::Tense(2)), &belief("<robin --> bird>", belief_time), 2, &TruthConfig::default())
+                .into_iter()
+                .find(|d| d.rule == "deduction")
+                .unwrap()
+                .truth
+        };
+        assert_eq!(deduction(Time::Eternal), Truth::new(1.0, 0.81));
+        assert_eq!(deduction(Time::Tense(2)), Truth::new(1.0, 0.81));
+        // A belief three steps away is projected to the task's time
+        assert_eq!(deduction(Time::Tense(5)), Truth::new(1.0, 0.81 / 4.0));
+    }
+
+    #[test]
+    fn test_derive_temporal_induction() {
+        let deriver = Deriver::default();
+        let derivations = deriver.derive(&belief("rain", Time::Tense(2)), &belief("wet", Time::Tense(5)), 0, &TruthConfig::default());
+        assert_eq!(derivations.len(), 1);
+        assert_eq!(derivations[0].rule, "temporal induction");
+        assert_eq!(derivations[0].term, Parser::parse_term("(rain ==>+3 wet)").unwrap());
+
+        // The offset is counted from the end of a sequence
+        let derivations = deriver.derive(&belief("(cloud &&+2 rain)", Time::Tense(1)), &belief("wet", Time::Tense(5)), 0, &TruthConfig::default());
+        assert_eq!(derivations[0].term, Parser::parse_term("((cloud &&+2 rain) ==>+2 wet)").unwrap());
+
+        // Nothing fires backwards in time, and questions have no truth to combine
+        assert!(deriver.derive(&belief("wet", Time::Tense(5)), &belief("rain", Time::Tense(2)), 0, &TruthConfig::default()).is_empty());
+        let question = TaskBuilder::new()
+            .term(Parser::parse_term("<robin --> bird>").unwrap())
+            .punctuation(Punctuation::Question)
+            .build()
+            .unwrap();
+        assert!(deriver.derive(&question, &belief("<bird --> animal>", Time::Eternal), 0, &TruthConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_conclusions_dropped() {
+        let rules = rule_file::parse_rules("A, B |- <A --> B>, (Belief:Deduction)").unwrap();
+        let deriver = Deriver::new(rules);
+        assert_eq!(deriver.derive(&belief("a", Time::Eternal), &belief("b", Time::Eternal), 0, &TruthConfig::default()).len(), 1);
+        assert!(deriver.derive(&belief("a", Time::Eternal), &belief("a", Time::Eternal), 0, &TruthConfig::default()).is_empty());
+        assert!(deriver.derive(&belief("true", Time::Eternal), &belief("b", Time::Eternal), 0, &TruthConfig::default()).is_empty());
+        assert_eq!(deriver.stats().derivations, 1);
+    }
+
+    #[test]
+    fn test_overlapping_evidence_derives_nothing() {
+        let deriver = Deriver::default();
+        let bird = belief("<bird --> animal>", Time::Eternal);
+        let robin = belief("<robin --> bird>", Time::Eternal);
+        let derived = TaskBuilder::new()
+            .term(Parser::parse_term("<robin --> animal>").unwrap())
+            .truth(Truth::new(1.0, 0.81))
+            .punctuation(Punctuation::Belief)
+            .stamp(Stamp::merge(bird.stamp(), robin.stamp()))
+            .build()
+            .unwrap();
+        assert!(!deriver.derive(&bird, &robin, 0, &TruthConfig::default()).is_empty());
+        assert!(deriver.derive(&derived, &robin, 0, &TruthConfig::default()).is_empty());
+        assert!(deriver.derive(&bird, &bird, 0, &TruthConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let deriver = Deriver::default();
+        deriver.derive(&belief("<bird --> animal>", Time::Eternal), &belief("<robin --> bird>", Time::Eternal), 0, &TruthConfig::default());
+        deriver.derive(&belief("rain", Time::Tense(2)), &belief("wet", Time::Tense(5)), 0, &TruthConfig::default());
+
+        let stats = deriver.stats();
+        assert_eq!(stats.premises, 2);
+        assert_eq!(stats.histogram.iter().sum::<u64>(), 2);
+        assert_eq!(stats.max_rules_visited(), deriver.rules().len());
+        assert_eq!(stats.rules_visited, stats.rules.iter().map(|r| r.visits).sum::<u64>());
+        // The atoms never reach the syllogisms
+        let deduction = stats.rules.iter().find(|r| r.name == "deduction").unwrap();
+        assert_eq!((deduction.visits, deduction.fired), (1, 1));
+        assert!(stats.to_string().contains("2 premises"));
+
+        deriver.reset_stats();
+        assert_eq!(deriver.stats().premises, 0);
+    }
+}
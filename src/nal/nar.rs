@@ -1,232 +1,275 @@
-//! Non-Axiomatic Reasoner (NAR) implementation
-//! 
-//! This module implements the core NAR (Non-Axiomatic Reasoner) class based on the Java implementation.
-//! The NAR manages the reasoning cycle, memory operations, and I/O channels.
-
-use crate::memory::Memory;
-use crate::concept::TaskConcept;
-use crate::task::Task;
+//! Construction and running of the reasoner
+//!
+//! `NARBuilder` puts a `NAR` together from pluggable parts: where concepts
+//! are kept, the clock, truth precision, inference rules, the focus of
+//! attention and the seed of its random choices. Anything not given falls
+//! back to the default: a radix-tree `Memory`, a cycle-counting `Time`,
+//! the standard rules and `Attention`.
+//!
+//! `NAR::start_fps` hands a reasoner to a background thread that runs
+//! cycles at a fixed rate; the returned `NARLoop` gives access to it while
+//! it runs and hands it back when stopped.
+
+pub use super::NAR;
+
+use super::deriver::Deriver;
+use super::Attention;
+use crate::concept::util::{ConceptBuilder, Emotion};
+use crate::focus::Focus;
+use crate::memory::{ConceptMemory, Memory};
+use crate::table::dynamic::DynamicTable;
+use crate::table::question::QuestionTable;
+use crate::task::Budget;
+use crate::term::atom::Atomic;
 use crate::term::Term;
-use crate::concept::util::ConceptBuilder;
-use crate::time::Time;
-use crate::truth::Truth;
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-
-/// Non-Axiomatic Reasoner (NAR) - The main reasoning system
-pub struct NAR {
-    /// The memory system
-    pub memory: Memory,
-    
-    /// The concept builder
-    pub concept_builder: ConceptBuilder,  // Changed from Arc to allow direct mutation
-    
-    /// The time control system
-    pub time: Arc<Time>,
-    
-    /// Self identifier term
-    self_term: Term,
-    
-    /// Running flag
-    running: bool,
+use crate::time::{Clock, Time};
+use crate::truth::TruthConfig;
+use parking_lot::{Mutex, MutexGuard};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Builder for a `NAR` with pluggable memory, clock, truth settings,
+/// rules, focus and random seed
+#[derive(Default)]
+pub struct NARBuilder {
+    memory: Option<Box<dyn ConceptMemory>>,
+    clock: Option<Arc<dyn Clock>>,
+    truth_config: TruthConfig,
+    deriver: Option<Deriver>,
+    focus: Option<Box<dyn Focus>>,
+    seed: Option<u64>,
+    default_budget: Option<Budget>,
 }
 
-impl NAR {
-    /// Create a new NAR instance
-    pub fn new(memory: Memory, time: Time, concept_builder: ConceptBuilder) -> Self {
-        let time_ref = Arc::new(time);
-        let mut nar = NAR {
-            memory,
+impl NARBuilder {
+    /// Create a builder with every part left to its default
+    pub fn new() -> Self {
+        NARBuilder::default()
+    }
+
+    /// Keep concepts in the given memory
+    pub fn memory(mut self, memory: impl ConceptMemory + 'static) -> Self {
+        self.memory = Some(Box::new(memory));
+        self
+    }
+
+    /// Tell time by the given clock
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Use a custom evidential horizon and truth precision
+    pub fn truth_config(mut self, truth_config: TruthConfig) -> Self {
+        self.truth_config = truth_config;
+        self
+    }
+
+    /// Reason with the given rules
+    pub fn deriver(mut self, deriver: Deriver) -> Self {
+        self.deriver = Some(deriver);
+        self
+    }
+
+    /// Choose what to reason about with the given focus
+    pub fn focus(mut self, focus: impl Focus + 'static) -> Self {
+        self.focus = Some(Box::new(focus));
+        self
+    }
+
+    /// Make the random choices reproducible
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Give input tasks without a budget of their own this one
+    pub fn default_budget(mut self, budget: Budget) -> Self {
+        self.default_budget = Some(budget);
+        self
+    }
+
+    /// Build the reasoner
+    pub fn build(self) -> NAR {
+        let clock = self.clock.unwrap_or_else(|| Arc::new(Time::new()));
+        let mut concept_builder = ConceptBuilder::new();
+        concept_builder.init(Emotion::new(), clock.clone());
+        NAR {
+            memory: self.memory.unwrap_or_else(|| Box::new(Memory::new())),
+            clock,
             concept_builder,
-            time: time_ref.clone(),
-            self_term: Term::Atomic(crate::term::atom::Atomic::new_atom("self")),
-            running: false,
-        };
-        
-        // Initialize concept builder with emotion and time
-        let emotion = crate::concept::util::Emotion::new();
-        nar.concept_builder.init(emotion, nar.time.clone());
-        
-        nar
-    }
-    
-    /// Input a task into the system
-    pub fn input(&mut self, task: Task) {
-        // For now, add the task to memory by creating or updating its concept
-        let term = task.term().clone();
-        if let Some(mut concept) = self.conceptualize(&term) {
-            concept.add_task(task);
-            // Update the concept in memory
-            self.memory.add_concept(concept);
-        } else if let Some(concept) = self.concept_builder.build(&term, true, false) {
-            // Add the task to the new concept
-            let mut concept = concept;
-            concept.add_task(task);
-            self.memory.add_concept(concept);
+            self_term: Term::Atomic(Atomic::new_atom("self")),
+            running: Arc::new(AtomicBool::new(false)),
+            default_budget: self.default_budget.unwrap_or(Budget::new(0.5, 0.5, 0.5)),
+            focus: self.focus.unwrap_or_else(|| Box::new(Attention::default())),
+            derived_listeners: Vec::new(),
+            questions: QuestionTable::default(),
+            answer_listeners: Vec::new(),
+            dynamic: DynamicTable::new(),
+            rng: match self.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            deriver: self.deriver.unwrap_or_default(),
+            truth_config: self.truth_config,
         }
     }
-    
-    /// Input a string as a task
-    pub fn input_string(&mut self, input: &str) -> Result<Vec<Task>, String> {
-        // This would parse Narsese, simplified for now
-        // In a real implementation, this would use the parser module
-        Ok(vec![]) // Placeholder
-    }
-    
-    /// Get or create a concept
-    pub fn conceptualize(&mut self, term: &Term) -> Option<TaskConcept> {
-        if let Some(concept) = self.memory.get_concept(term) {
-            // If it already exists, return it
-            Some(concept)
-        } else {
-            // If not, create it using the concept builder
-            let concept = self.concept_builder.build(term, true, false);
-            if let Some(concept) = concept {
-                self.memory.add_concept(concept.clone());
-                Some(concept)
-            } else {
-                None
-            }
-        }
+}
+
+/// A reasoner running cycles on a background thread
+pub struct NARLoop {
+    nar: Option<Arc<Mutex<NAR>>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl NARLoop {
+    /// Run cycles of a reasoner at most `fps` times a second; as fast as
+    /// possible if `fps` is not positive
+    pub(super) fn start(nar: NAR, fps: f32) -> Self {
+        let running = nar.running.clone();
+        running.store(true, Ordering::Relaxed);
+        let period = if fps > 0.0 { Duration::from_secs_f32(1.0 / fps) } else { Duration::ZERO };
+        let nar = Arc::new(Mutex::new(nar));
+        let thread = {
+            let (nar, running) = (nar.clone(), running.clone());
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    let started = Instant::now();
+                    nar.lock().cycle();
+                    thread::sleep(period.saturating_sub(started.elapsed()));
+                }
+            })
+        };
+        NARLoop { nar: Some(nar), running, thread: Some(thread) }
     }
-    
-    /// Get a concept if it exists
-    pub fn concept(&self, term: &Term) -> Option<TaskConcept> {
-        self.memory.get_concept(term)
-    }
-    
-    /// Start the NAR in a loop with given frames per second
-    pub fn start_fps(&mut self, fps: f32) {
-        self.running = true;
-        
-        // We can't move self into the thread, so we need a different approach
-        // For now, let's just call it sequentially
-        // In a more complex implementation, we'd need to restructure
-        self.running = false; // Temporarily set to false so this doesn't run
-    }
-    
-    /// Stop the NAR
-    pub fn stop(&mut self) {
-        self.running = false;
-    }
-    
-    /// Single reasoning cycle
-    pub fn cycle(&mut self) {
-        // In a real implementation, this would execute the full reasoning cycle
-        // For now, we'll just advance time
-        self.time.next();
-        
-        // In the future, this would:
-        // 1. Process focus tasks
-        // 2. Apply inference rules
-        // 3. Update concepts
-        // 4. Handle emotions
-        // 5. Manage attention
-    }
-    
-    /// Reset the NAR to initial state
-    pub fn reset(&mut self) {
-        self.stop();
-        self.memory.clear();
-        self.time.reset();
-        // Reset other components as needed
-    }
-    
-    /// Get belief truth for a concept at a time range
-    pub fn belief_truth(&self, concept: &Term, _start: i64, _end: i64) -> Option<Truth> {
-        if let Some(concept_obj) = self.concept(concept) {
-            concept_obj.beliefs().truth(_start, _end, concept)
-        } else {
-            None
-        }
+
+    /// Get the reasoner between cycles, e.g. to input tasks or ask about
+    /// its beliefs
+    pub fn lock(&self) -> MutexGuard<'_, NAR> {
+        self.nar.as_ref().expect("a running loop holds its reasoner").lock()
+    }
+
+    /// Check if cycles are still being run; `NAR::stop` ends them
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
     }
-    
-    /// Get goal truth for a concept at a time range
-    pub fn goal_truth(&self, concept: &Term, _start: i64, _end: i64) -> Option<Truth> {
-        if let Some(concept_obj) = self.concept(concept) {
-            concept_obj.goals().truth(_start, _end, concept)
-        } else {
-            None
+
+    /// Stop running cycles and take the reasoner back
+    pub fn stop(mut self) -> NAR {
+        self.join();
+        let nar = self.nar.take().expect("a running loop holds its reasoner");
+        match Arc::try_unwrap(nar) {
+            Ok(nar) => nar.into_inner(),
+            Err(_) => unreachable!("the loop thread has ended"),
         }
     }
-    
-    /// Get answer for a question
-    pub fn answer(&self, term: &Term, punc: u8, _start: i64, _end: i64) -> Option<Task> {
-        // Get the concept for the term
-        if let Some(concept) = self.concept(term) {
-            // Based on punctuation, get the appropriate table
-            match punc {
-                0 => { // BELIEF
-                    concept.beliefs().highest_priority().cloned()
-                },
-                1 => { // GOAL
-                    concept.goals().highest_priority().cloned()
-                },
-                2 => { // QUESTION
-                    concept.questions().highest_priority().cloned()
-                },
-                3 => { // QUEST
-                    concept.quests().highest_priority().cloned()
-                },
-                _ => None
-            }
-        } else {
-            None
+
+    fn join(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
         }
     }
 }
 
+impl Drop for NARLoop {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::term::atom::Atomic;
+    use crate::concept::TaskConcept;
+    use crate::focus::FocusBag;
     use crate::truth::Truth;
-    use crate::task::{TaskBuilder, Punctuation, Budget};
+    use std::collections::HashMap;
 
-    #[test]
-    fn test_nar_creation() {
-        let memory = Memory::new();
-        let time = Time::new();
-        let concept_builder = ConceptBuilder::new();
-        let mut nar = NAR::new(memory, time, concept_builder);
-        
-        assert_eq!(nar.running, false);
+    /// Concepts in a plain map, keyed by term
+    #[derive(Default)]
+    struct MapMemory {
+        concepts: HashMap<Term, TaskConcept>,
+    }
+
+    impl ConceptMemory for MapMemory {
+        fn get_concept(&self, term: &Term) -> Option<TaskConcept> {
+            self.concepts.get(term).cloned()
+        }
+
+        fn add_concept(&mut self, concept: TaskConcept) {
+            self.concepts.insert(concept.term().clone(), concept);
+        }
+
+        fn remove_concept(&mut self, term: &Term) -> Option<TaskConcept> {
+            self.concepts.remove(term)
+        }
+
+        fn concepts(&self) -> Vec<TaskConcept> {
+            self.concepts.values().cloned().collect()
+        }
+
+        fn len(&self) -> usize {
+            self.concepts.len()
+        }
+
+        fn clear(&mut self) {
+            self.concepts.clear();
+        }
     }
 
     #[test]
-    fn test_nar_input() {
-        let memory = Memory::new();
-        let time = Time::new();
-        let concept_builder = ConceptBuilder::new();
-        let mut nar = NAR::new(memory, time, concept_builder);
-        
-        // Test creating a simple task
-        let term = Term::Atomic(Atomic::new_atom("test"));
-        let task = TaskBuilder::new()
-            .id(1)
-            .term(term)
-            .truth(Truth::default_belief())
-            .punctuation(Punctuation::Belief)
-            .time(crate::task::Time::Eternal)
-            .budget(Budget::default())
-            .build()
-            .unwrap();
-        nar.input(task);
-        
-        // Input should work without panicking
-    }
-    
+    fn test_builder_parts() {
+        let clock = Time::new();
+        clock.next();
+        let mut nar = NARBuilder::new()
+            .memory(MapMemory::default())
+            .clock(clock)
+            .truth_config(TruthConfig::opennars())
+            .focus(FocusBag::new(10))
+            .seed(7)
+            .build();
+        assert_eq!(nar.time(), 1);
+
+        nar.input_sentence("<bird --> animal>. %1.0;0.9%").unwrap();
+        nar.input_sentence("<robin --> bird>. %1.0;0.9%").unwrap();
+        assert_eq!(nar.concepts().len(), 2);
+        nar.cycle();
+        assert_eq!(nar.time(), 2);
+
+        // The focus bag reasons about each input once, with the default rules
+        let deduction = crate::parser::Parser::parse_term("<robin --> animal>").unwrap();
+        assert_eq!(nar.belief_truth(&deduction, 2, 2), Some(Truth::new(1.0, 0.81)));
+
+        // No rules, no conclusions
+        let mut nar = NARBuilder::new().deriver(Deriver::new(Vec::new())).build();
+        nar.input_sentence("<bird --> animal>.").unwrap();
+        nar.input_sentence("<robin --> bird>.").unwrap();
+        nar.cycle();
+        assert!(nar.belief_truth(&deduction, 1, 1).is_none());
+    }
+
     #[test]
-    fn test_conceptualize() {
-        let memory = Memory::new();
-        let time = Time::new();
-        let concept_builder = ConceptBuilder::new();
-        let mut nar = NAR::new(memory, time, concept_builder);
-        
-        let term = Term::Atomic(Atomic::new_atom("test_concept"));
-        let concept = nar.conceptualize(&term);
-        
-        assert!(concept.is_some());
-    }
-}
\ No newline at end of file
+    fn test_start_fps() {
+        let mut nar = NARBuilder::new().seed(1).build();
+        nar.input_sentence("<bird --> animal>.").unwrap();
+        let running = nar.start_fps(0.0);
+        while running.lock().time() < 3 {
+            thread::yield_now();
+        }
+        running.lock().input_sentence("<robin --> bird>.").unwrap();
+        assert!(running.is_running());
+
+        let nar = running.stop();
+        assert!(!nar.is_running());
+        let time = nar.time();
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(nar.time(), time);
+        assert!(nar.concepts().len() >= 2);
+    }
+}
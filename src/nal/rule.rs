@@ -0,0 +1,382 @@
+//! Declarative inference rules
+//!
+//! A rule is data: a pattern for the task premise, a pattern for the belief
+//! premise, a conclusion template, the truth function combining the premise
+//! truths, and preconditions that must hold for the rule to fire. Patterns
+//! are Narsese terms in which pattern variables (`@S`, `@M`, ...) match any
+//! term; the same variable must match the same term everywhere.
+
+use super::rule_file;
+use crate::parser::{ParseError, Parser};
+use crate::task::{Punctuation, Time};
+use crate::term::unify::Subst;
+use crate::term::var::Variable;
+use crate::term::Term;
+use crate::truth::{Truth, TruthConfig};
+
+/// Truth function applied to the task and belief truth values, in that order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TruthFn {
+    /// `Truth::deduction`
+    Deduction,
+
+    /// `Truth::induction`
+    Induction,
+
+    /// `Truth::abduction`
+    Abduction,
+
+    /// `Truth::exemplification`
+    Exemplification,
+
+    /// `Truth::comparison`
+    Comparison,
+
+    /// `Truth::analogy`
+    Analogy,
+
+    /// `Truth::conjunction`
+    Conjunction,
+
+    /// `Truth::disjunction`
+    Disjunction,
+
+    /// `Truth::resemblance`
+    Resemblance,
+
+    /// `Truth::intersection`
+    Intersection,
+
+    /// `Truth::union`
+    Union,
+
+    /// `Truth::difference`
+    Difference,
+
+    /// `Truth::reduce_conjunction`
+    ReduceConjunction,
+
+    /// `Truth::reduce_disjunction`
+    ReduceDisjunction,
+
+    /// `Truth::decompose_pp`
+    DecomposePP,
+
+    /// `Truth::decompose_pn`
+    DecomposePN,
+
+    /// `Truth::decompose_np`
+    DecomposeNP,
+
+    /// `Truth::decompose_nn`
+    DecomposeNN,
+
+    /// `Truth::deduction_pn`
+    DeductionPN,
+
+    /// `Truth::deduction_np`
+    DeductionNP,
+
+    /// `Truth::abduction_pn`
+    AbductionPN,
+
+    /// `Truth::abduction_np`
+    AbductionNP,
+
+    /// `Truth::desire_strong`
+    DesireStrong,
+
+    /// `Truth::desire_weak`
+    DesireWeak,
+
+    /// `Truth::desire_deduction`
+    DesireDeduction,
+
+    /// `Truth::desire_induction`
+    DesireInduction,
+
+    /// `Truth::conversion` of the task alone
+    Conversion,
+
+    /// `Truth::contraposition` of the task alone
+    Contraposition,
+}
+
+impl TruthFn {
+    /// Combine the task and belief truth values; single-premise functions
+    /// ignore the belief
+    pub fn apply(self, task: &Truth, belief: &Truth, config: &TruthConfig) -> Truth {
+        match self {
+            TruthFn::Deduction => Truth::deduction(task, belief, config),
+            TruthFn::Induction => Truth::induction(task, belief, config),
+            TruthFn::Abduction => Truth::abduction(task, belief, config),
+            TruthFn::Exemplification => Truth::exemplification(task, belief, config),
+            TruthFn::Comparison => Truth::comparison(task, belief, config),
+            TruthFn::Analogy => Truth::analogy(task, belief, config),
+            TruthFn::Conjunction => Truth::conjunction(task, belief, config),
+            TruthFn::Disjunction => Truth::disjunction(task, belief, config),
+            TruthFn::Resemblance => Truth::resemblance(task, belief, config),
+            TruthFn::Intersection => Truth::intersection(task, belief, config),
+            TruthFn::Union => Truth::union(task, belief, config),
+            TruthFn::Difference => Truth::difference(task, belief, config),
+            TruthFn::ReduceConjunction => Truth::reduce_conjunction(task, belief, config),
+            TruthFn::ReduceDisjunction => Truth::reduce_disjunction(task, belief, config),
+            TruthFn::DecomposePP => Truth::decompose_pp(task, belief, config),
+            TruthFn::DecomposePN => Truth::decompose_pn(task, belief, config),
+            TruthFn::DecomposeNP => Truth::decompose_np(task, belief, config),
+            TruthFn::DecomposeNN => Truth::decompose_nn(task, belief, config),
+            TruthFn::DeductionPN => Truth::deduction_pn(task, belief, config),
+            TruthFn::DeductionNP => Truth::deduction_np(task, belief, config),
+            TruthFn::AbductionPN => Truth::abduction_pn(task, belief, config),
+            TruthFn::AbductionNP => Truth::abduction_np(task, belief, config),
+            TruthFn::DesireStrong => Truth::desire_strong(task, belief, config),
+            TruthFn::DesireWeak => Truth::desire_weak(task, belief, config),
+            TruthFn::DesireDeduction => Truth::desire_deduction(task, belief, config),
+            TruthFn::DesireInduction => Truth::desire_induction(task, belief, config),
+            TruthFn::Conversion => Truth::conversion(task, config),
+            TruthFn::Contraposition => Truth::contraposition(task, config),
+        }
+    }
+
+    /// Look up a truth function by the name used in rule files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Deduction" => Some(TruthFn::Deduction),
+            "Induction" => Some(TruthFn::Induction),
+            "Abduction" => Some(TruthFn::Abduction),
+            "Exemplification" => Some(TruthFn::Exemplification),
+            "Comparison" => Some(TruthFn::Comparison),
+            "Analogy" => Some(TruthFn::Analogy),
+            "Conjunction" => Some(TruthFn::Conjunction),
+            "Disjunction" => Some(TruthFn::Disjunction),
+            "Resemblance" => Some(TruthFn::Resemblance),
+            "Intersection" => Some(TruthFn::Intersection),
+            "Union" => Some(TruthFn::Union),
+            "Difference" => Some(TruthFn::Difference),
+            "ReduceConjunction" => Some(TruthFn::ReduceConjunction),
+            "ReduceDisjunction" => Some(TruthFn::ReduceDisjunction),
+            "DecomposePP" => Some(TruthFn::DecomposePP),
+            "DecomposePN" => Some(TruthFn::DecomposePN),
+            "DecomposeNP" => Some(TruthFn::DecomposeNP),
+            "DecomposeNN" => Some(TruthFn::DecomposeNN),
+            "DeductionPN" => Some(TruthFn::DeductionPN),
+            "DeductionNP" => Some(TruthFn::DeductionNP),
+            "AbductionPN" => Some(TruthFn::AbductionPN),
+            "AbductionNP" => Some(TruthFn::AbductionNP),
+            "DesireStrong" => Some(TruthFn::DesireStrong),
+            "DesireWeak" => Some(TruthFn::DesireWeak),
+            "DesireDeduction" => Some(TruthFn::DesireDeduction),
+            "DesireInduction" => Some(TruthFn::DesireInduction),
+            "Conversion" => Some(TruthFn::Conversion),
+            "Contraposition" => Some(TruthFn::Contraposition),
+            _ => None,
+        }
+    }
+}
+
+/// Condition on the premises beyond their shape
+#[derive(Debug, Clone, PartialEq)]
+pub enum Precondition {
+    /// The two pattern variables must match different terms
+    NotEqual(Variable, Variable),
+
+    /// Both premises are events no more than one duration apart
+    Simultaneous,
+
+    /// Both premises are events and the task occurs before the belief
+    TaskFirst,
+}
+
+impl Precondition {
+    /// Check the condition against the premise times, the duration of the
+    /// present moment, and variable bindings
+    pub fn holds(&self, task_time: Time, belief_time: Time, duration: f32, subst: &Subst) -> bool {
+        match self {
+            Precondition::NotEqual(a, b) => subst.get(a) != subst.get(b),
+            Precondition::Simultaneous => matches!(
+                (task_time, belief_time),
+                (Time::Tense(task), Time::Tense(belief)) if (task - belief).abs() <= duration.round() as i64
+            ),
+            Precondition::TaskFirst => matches!(
+                (task_time, belief_time),
+                (Time::Tense(task), Time::Tense(belief)) if task < belief
+            ),
+        }
+    }
+}
+
+/// Temporal offset of the conclusion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConclusionDt {
+    /// Keep the offset written in the conclusion template
+    Template,
+
+    /// Use the time from the task to the belief as the offset of the
+    /// conclusion's outermost compound
+    Interval,
+}
+
+/// An inference rule over a task and a belief
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    name: String,
+    task: Term,
+    belief: Term,
+    conclusion: Term,
+    truth: TruthFn,
+    punctuation: Punctuation,
+    preconditions: Vec<Precondition>,
+    dt: ConclusionDt,
+}
+
+impl Rule {
+    /// Create a rule from Narsese patterns
+    pub fn new(name: &str, task: &str, belief: &str, conclusion: &str, truth: TruthFn) -> Result<Self, ParseError> {
+        Ok(Rule::from_terms(
+            name,
+            Parser::parse_term(task)?,
+            Parser::parse_term(belief)?,
+            Parser::parse_term(conclusion)?,
+            truth,
+        ))
+    }
+
+    /// Create a rule from already parsed patterns; the rule applies to
+    /// belief tasks
+    pub fn from_terms(name: &str, task: Term, belief: Term, conclusion: Term, truth: TruthFn) -> Self {
+        Rule {
+            name: name.to_string(),
+            task,
+            belief,
+            conclusion,
+            truth,
+            punctuation: Punctuation::Belief,
+            preconditions: Vec::new(),
+            dt: ConclusionDt::Template,
+        }
+    }
+
+    /// Set the task punctuation the rule applies to, which is also the
+    /// punctuation of its conclusion
+    pub fn punctuation(mut self, punctuation: Punctuation) -> Self {
+        self.punctuation = punctuation;
+        self
+    }
+
+    /// Add a precondition
+    pub fn precondition(mut self, precondition: Precondition) -> Self {
+        self.preconditions.push(precondition);
+        self
+    }
+
+    /// Require two pattern variables to match different terms
+    pub fn not_equal(self, a: &str, b: &str) -> Self {
+        self.precondition(Precondition::NotEqual(Variable::new_pattern(a), Variable::new_pattern(b)))
+    }
+
+    /// Set how the temporal offset of the conclusion is computed
+    pub fn dt(mut self, dt: ConclusionDt) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    /// Get the rule name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the task premise pattern
+    pub fn task(&self) -> &Term {
+        &self.task
+    }
+
+    /// Get the belief premise pattern
+    pub fn belief(&self) -> &Term {
+        &self.belief
+    }
+
+    /// Get the conclusion template
+    pub fn conclusion(&self) -> &Term {
+        &self.conclusion
+    }
+
+    /// Get the truth function
+    pub fn truth(&self) -> TruthFn {
+        self.truth
+    }
+
+    /// Get the task punctuation the rule applies to
+    pub fn task_punctuation(&self) -> Punctuation {
+        self.punctuation
+    }
+
+    /// Get the preconditions
+    pub fn preconditions(&self) -> &[Precondition] {
+        &self.preconditions
+    }
+
+    /// Get how the conclusion offset is computed
+    pub fn conclusion_dt(&self) -> ConclusionDt {
+        self.dt
+    }
+}
+
+/// The standard rule set: the NAL-1 syllogisms plus conjunction and temporal
+/// induction over events, as written in `standard.nal`
+pub fn standard_rules() -> Vec<Rule> {
+    rule_file::parse_rules(include_str!("standard.nal")).expect("standard rules are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_construction() {
+        let rule = Rule::new("deduction", "<@M --> @P>", "<@S --> @M>", "<@S --> @P>", TruthFn::Deduction)
+            .unwrap()
+            .not_equal("S", "P");
+        assert_eq!(rule.name(), "deduction");
+        assert_eq!(rule.task(), &Parser::parse_term("<@M --> @P>").unwrap());
+        assert_eq!(rule.preconditions(), &[Precondition::NotEqual(Variable::new_pattern("S"), Variable::new_pattern("P"))]);
+        assert_eq!(rule.conclusion_dt(), ConclusionDt::Template);
+        assert_eq!(rule.task_punctuation(), Punctuation::Belief);
+
+        assert!(Rule::new("broken", "<@M -->", "@S", "@S", TruthFn::Deduction).is_err());
+    }
+
+    #[test]
+    fn test_preconditions() {
+        let a = Variable::new_pattern("A");
+        let b = Variable::new_pattern("B");
+        let cat = Parser::parse_term("cat").unwrap();
+        let dog = Parser::parse_term("dog").unwrap();
+
+        let mut subst = Subst::new();
+        subst.bind(a.clone(), cat.clone());
+        subst.bind(b.clone(), dog);
+        assert!(Precondition::NotEqual(a.clone(), b.clone()).holds(Time::Eternal, Time::Eternal, 1.0, &subst));
+        subst.bind(b.clone(), cat);
+        assert!(!Precondition::NotEqual(a, b).holds(Time::Eternal, Time::Eternal, 1.0, &subst));
+
+        let none = Subst::new();
+        assert!(Precondition::Simultaneous.holds(Time::Tense(3), Time::Tense(3), 1.0, &none));
+        assert!(Precondition::Simultaneous.holds(Time::Tense(3), Time::Tense(2), 1.0, &none));
+        assert!(!Precondition::Simultaneous.holds(Time::Tense(3), Time::Tense(1), 1.0, &none));
+        assert!(Precondition::Simultaneous.holds(Time::Tense(3), Time::Tense(1), 2.0, &none));
+        assert!(!Precondition::Simultaneous.holds(Time::Eternal, Time::Eternal, 1.0, &none));
+        assert!(!Precondition::Simultaneous.holds(Time::Eternal, Time::Tense(3), 1.0, &none));
+        assert!(Precondition::TaskFirst.holds(Time::Tense(1), Time::Tense(3), 1.0, &none));
+        assert!(Precondition::TaskFirst.holds(Time::Tense(-3), Time::Tense(-1), 1.0, &none));
+        assert!(!Precondition::TaskFirst.holds(Time::Tense(3), Time::Tense(1), 1.0, &none));
+        assert!(!Precondition::TaskFirst.holds(Time::Eternal, Time::Tense(1), 1.0, &none));
+    }
+
+    #[test]
+    fn test_standard_rules() {
+        let rules = standard_rules();
+        assert!(rules.iter().any(|r| r.name() == "deduction"));
+        assert!(rules.iter().any(|r| r.conclusion_dt() == ConclusionDt::Interval));
+        assert!(rules.iter().all(|r| !r.name().is_empty()));
+    }
+}
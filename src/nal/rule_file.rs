@@ -0,0 +1,268 @@
+//! Rule definition files
+//!
+//! Rules are written one per line, in the style of the `nars/deriver` rule
+//! files:
+//!
+//! ```text
+//! // comments run to the end of the line
+//! deduction {
+//!     M --> P, S --> M, neq(S, P) |- S --> P, (Belief:Deduction)
+//! }
+//! ```
+//!
+//! Left of `|-` come the task pattern, the belief pattern and any
+//! preconditions; right of it the conclusion template and a parenthesized
+//! option list. A block names the rules inside it; a rule outside any block
+//! is named after its line. Names made of one upper-case letter and optional
+//! digits (`S`, `M1`) are pattern variables, as is anything written `@S`.
+//!
+//! Preconditions: `neq(A, B)`, `simultaneous()`, `taskFirst()`.
+//! Options: `Belief:<truth>` or `Goal:<truth>` choosing the task punctuation
+//! and truth function, and `Time:Template` or `Time:Interval` choosing the
+//! conclusion offset.
+
+use super::rule::{ConclusionDt, Precondition, Rule, TruthFn};
+use crate::parser::lexer::{Lexer, Token};
+use crate::parser::{ParseError, ParseErrorKind, Parser, Span};
+use crate::task::Punctuation;
+use crate::term::atom::Atomic;
+use crate::term::var::Variable;
+use crate::term::Term;
+
+/// Parse every rule in a rule file; error spans are relative to the whole
+/// source so that `ParseError::render` points at the offending line
+pub fn parse_rules(source: &str) -> Result<Vec<Rule>, ParseError> {
+    let mut rules = Vec::new();
+    let mut block: Option<(String, Span)> = None;
+    let mut line_start = 0;
+    for (index, line) in source.split('\n').enumerate() {
+        let offset = line_start;
+        line_start += line.len() + 1;
+
+        let code = line.find("//").map_or(line, |comment| &line[..comment]);
+        let trimmed = code.trim();
+        let at = offset + code.len() - code.trim_start().len();
+        let span = Span::new(at, at + trimmed.len());
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_suffix('{') {
+            if block.is_some() {
+                return Err(invalid("Rule blocks cannot be nested", span));
+            }
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(invalid("Rule block needs a name", span));
+            }
+            block = Some((name.to_string(), span));
+        } else if trimmed == "}" {
+            if block.take().is_none() {
+                return Err(invalid("'}' without an open rule block", span));
+            }
+        } else {
+            let name = match &block {
+                Some((name, _)) => name.clone(),
+                None => format!("line {}", index + 1),
+            };
+            rules.push(parse_rule(&name, trimmed).map_err(|e| e.offset(at))?);
+        }
+    }
+
+    match block {
+        Some((name, span)) => Err(invalid(&format!("Rule block '{}' is never closed", name), span)),
+        None => Ok(rules),
+    }
+}
+
+/// Parse one `premises |- conclusion, (options)` line
+fn parse_rule(name: &str, line: &str) -> Result<Rule, ParseError> {
+    let turnstile = line.find("|-").ok_or_else(|| {
+        invalid("Missing '|-' between premises and conclusion", Span::new(0, line.len()))
+    })?;
+    let (premises, conclusion) = (&line[..turnstile], &line[turnstile + 2..]);
+    let conclusion_at = turnstile + 2;
+
+    let premises = split_top_level(premises)?;
+    if premises.len() < 2 {
+        return Err(invalid("Expected a task and a belief pattern", Span::new(0, turnstile)));
+    }
+    let task = pattern(premises[0])?;
+    let belief = pattern(premises[1])?;
+
+    // The option list is the last parenthesized group; it holds no nested
+    // parentheses, so its opening is the last '(' on the line
+    let options_at = conclusion.rfind('(').filter(|&open| {
+        conclusion.trim_end().ends_with(')') && conclusion[..open].trim_end().ends_with(',')
+    });
+    let Some(options_at) = options_at else {
+        return Err(invalid("Expected '(Belief:<truth>)' after the conclusion", Span::new(conclusion_at, line.len())));
+    };
+    let template = &conclusion[..conclusion[..options_at].trim_end().len() - 1];
+    let template = pattern((conclusion_at, template))?;
+    let options = (conclusion_at + options_at, conclusion[options_at..].trim_end());
+
+    let mut truth = None;
+    let mut punctuation = Punctuation::Belief;
+    let mut dt = ConclusionDt::Template;
+    for (at, option) in split_plain(options.0 + 1, &options.1[1..options.1.len() - 1]) {
+        let span = Span::new(at, at + option.len());
+        let (key, value) = option.split_once(':').map(|(k, v)| (k.trim(), v.trim())).unwrap_or((option, ""));
+        match key {
+            "Belief" | "Goal" => {
+                let function = TruthFn::from_name(value)
+                    .ok_or_else(|| invalid(&format!("Unknown truth function '{}'", value), span))?;
+                if truth.replace(function).is_some() {
+                    return Err(invalid("Rule has two truth functions", span));
+                }
+                punctuation = if key == "Belief" { Punctuation::Belief } else { Punctuation::Goal };
+            },
+            "Time" => {
+                dt = match value {
+                    "Template" => ConclusionDt::Template,
+                    "Interval" => ConclusionDt::Interval,
+                    _ => return Err(invalid(&format!("Unknown time mode '{}'", value), span)),
+                };
+            },
+            _ => return Err(invalid(&format!("Unknown option '{}'", option), span)),
+        }
+    }
+    let truth = truth.ok_or_else(|| {
+        invalid("Rule needs a 'Belief:' or 'Goal:' truth function", Span::new(options.0, options.0 + options.1.len()))
+    })?;
+
+    let mut rule = Rule::from_terms(name, task, belief, template, truth).punctuation(punctuation).dt(dt);
+    for &(at, condition) in &premises[2..] {
+        rule = rule.precondition(precondition(at, condition)?);
+    }
+    Ok(rule)
+}
+
+/// Parse a precondition call such as `neq(S, P)`
+fn precondition(at: usize, condition: &str) -> Result<Precondition, ParseError> {
+    let span = Span::new(at, at + condition.len());
+    let (function, args) = condition
+        .strip_suffix(')')
+        .and_then(|call| call.split_once('('))
+        .ok_or_else(|| invalid("Expected a precondition such as 'neq(S, P)'", span))?;
+    let args: Vec<&str> = split_plain(0, args).into_iter().map(|(_, arg)| arg).collect();
+    match (function.trim(), args.as_slice()) {
+        ("neq", [a, b]) => Ok(Precondition::NotEqual(pattern_variable(a), pattern_variable(b))),
+        ("simultaneous", []) => Ok(Precondition::Simultaneous),
+        ("taskFirst", []) => Ok(Precondition::TaskFirst),
+        _ => Err(invalid(&format!("Unknown precondition '{}'", condition), span)),
+    }
+}
+
+/// Parse a pattern, turning upper-case names into pattern variables
+fn pattern((at, text): (usize, &str)) -> Result<Term, ParseError> {
+    Parser::parse_statement(text).map(|term| patternize(&term)).map_err(|e| e.offset(at))
+}
+
+fn pattern_variable(name: &str) -> Variable {
+    Variable::new_pattern(name.trim().trim_start_matches('@'))
+}
+
+fn is_pattern_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.all(|c| c.is_ascii_digit())
+}
+
+fn patternize(term: &Term) -> Term {
+    term.map_atoms(|atom| match atom {
+        Term::Atomic(Atomic::Atom(name)) if is_pattern_name(name) => Term::Variable(Variable::new_pattern(name)),
+        _ => atom.clone(),
+    })
+}
+
+/// Split at commas outside brackets, returning trimmed pieces with their
+/// byte offsets
+fn split_top_level(input: &str) -> Result<Vec<(usize, &str)>, ParseError> {
+    let mut lexer = Lexer::new(input);
+    let mut depth = 0usize;
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while let Some(spanned) = lexer.next_token()? {
+        match spanned.token {
+            Token::LParen | Token::LAngle | Token::LBrace | Token::LBracket => depth += 1,
+            Token::RParen | Token::RAngle | Token::RBrace | Token::RBracket => depth = depth.saturating_sub(1),
+            Token::Comma if depth == 0 => {
+                pieces.push(trimmed(start, &input[start..spanned.start]));
+                start = spanned.end;
+            },
+            _ => {},
+        }
+    }
+    pieces.push(trimmed(start, &input[start..]));
+    Ok(pieces)
+}
+
+/// Split at every comma, dropping empty pieces
+fn split_plain(at: usize, input: &str) -> Vec<(usize, &str)> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for piece in input.split(',') {
+        let (offset, text) = trimmed(at + start, piece);
+        if !text.is_empty() {
+            pieces.push((offset, text));
+        }
+        start += piece.len() + 1;
+    }
+    pieces
+}
+
+fn trimmed(at: usize, text: &str) -> (usize, &str) {
+    (at + text.len() - text.trim_start().len(), text.trim())
+}
+
+fn invalid(message: &str, span: Span) -> ParseError {
+    ParseError::new(ParseErrorKind::InvalidRule(message.to_string()), span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rule_block() {
+        let source = "// syllogisms\ndeduction {\n    M --> P, S --> M, neq(S, P) |- S --> P, (Belief:Deduction)\n}\n";
+        let rules = parse_rules(source).unwrap();
+        assert_eq!(rules.len(), 1);
+        let expected = Rule::new("deduction", "<@M --> @P>", "<@S --> @M>", "<@S --> @P>", TruthFn::Deduction)
+            .unwrap()
+            .not_equal("S", "P");
+        assert_eq!(rules[0], expected);
+    }
+
+    #[test]
+    fn test_parse_bare_rule_and_options() {
+        let rules = parse_rules("A, B, taskFirst() |- (A =/> B), (Goal:Induction, Time:Interval)").unwrap();
+        assert_eq!(rules[0].name(), "line 1");
+        assert_eq!(rules[0].task_punctuation(), Punctuation::Goal);
+        assert_eq!(rules[0].truth(), TruthFn::Induction);
+        assert_eq!(rules[0].conclusion_dt(), ConclusionDt::Interval);
+        assert_eq!(rules[0].preconditions(), &[Precondition::TaskFirst]);
+
+        // Constants stay constants
+        let rules = parse_rules("(cat --> X), Y |- (Y --> X), (Belief:Analogy)").unwrap();
+        assert_eq!(rules[0].task(), &Parser::parse_term("<cat --> @X>").unwrap());
+    }
+
+    #[test]
+    fn test_parse_rule_errors() {
+        let source = "broken {\n    A, B |- A, (Belief:Guess)\n}";
+        let error = parse_rules(source).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::InvalidRule("Unknown truth function 'Guess'".to_string()));
+        assert_eq!(error.line_col(source), (2, 17));
+
+        let error = parse_rules("A, B |- (A --> B C), (Belief:Deduction)").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedToken("C".to_string()));
+        assert_eq!(error.span(), Span::new(17, 18));
+        assert!(parse_rules("A, B => A, (Belief:Deduction)").is_err());
+        assert!(parse_rules("A, B |- A").is_err());
+        assert!(parse_rules("A |- A, (Belief:Deduction)").is_err());
+        assert!(parse_rules("A, B, sometimes() |- A, (Belief:Deduction)").is_err());
+        assert!(parse_rules("open {\n").is_err());
+        assert!(parse_rules("}").is_err());
+    }
+}
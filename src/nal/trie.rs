@@ -0,0 +1,243 @@
+//! Premise-matching decision trie
+//!
+//! Each rule is compiled into the list of structural checks its premises
+//! must pass: the task punctuation, then the operator, offset and arity of
+//! every compound in the task pattern and every constant in it, then the same
+//! for the belief pattern. Rules sharing a prefix of checks share a path in
+//! the trie, so a premise pair evaluates each check once and only reaches the
+//! rules whose structure it has. Pattern variables add no checks, and nor
+//! do the subterms of commutative compounds, which may match in any order;
+//! both are still verified by unifying the surviving rules in full.
+
+use super::rule::Rule;
+use crate::task::{Punctuation, Task};
+use crate::term::temporal::Dt;
+use crate::term::{Op, Term, TermTrait};
+
+/// Which premise a check looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Premise {
+    /// The task
+    Task,
+
+    /// The belief
+    Belief,
+}
+
+/// A structural test on a premise pair
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// The task has this punctuation
+    Punctuation(Punctuation),
+
+    /// The subterm at the index path is a compound with this shape
+    Compound {
+        /// Premise the path starts from
+        premise: Premise,
+        /// Subterm indices from the premise term down
+        path: Vec<usize>,
+        /// Operator of the compound
+        op: Op,
+        /// Temporal offset of the compound
+        dt: Dt,
+        /// Number of subterms
+        len: usize,
+    },
+
+    /// The subterm at the index path equals a constant term
+    Equal {
+        /// Premise the path starts from
+        premise: Premise,
+        /// Subterm indices from the premise term down
+        path: Vec<usize>,
+        /// The constant
+        term: Term,
+    },
+}
+
+impl Check {
+    /// Evaluate the check; paths are only followed below compounds that an
+    /// earlier check has already verified
+    pub fn test(&self, task: &Task, belief: &Task) -> bool {
+        let premise_term = |premise: &Premise| match premise {
+            Premise::Task => task.term(),
+            Premise::Belief => belief.term(),
+        };
+        match self {
+            Check::Punctuation(punctuation) => task.punctuation() == *punctuation,
+            Check::Compound { premise, path, op, dt, len } => matches!(
+                premise_term(premise).sub_at_path(path),
+                Some(Term::Compound(compound))
+                    if compound.op_id() == *op && dt.matches(compound.dt()) && compound.len() == *len
+            ),
+            Check::Equal { premise, path, term } => premise_term(premise).sub_at_path(path) == Some(term),
+        }
+    }
+}
+
+/// Checks a rule's premises must pass, in trie order
+pub fn checks(rule: &Rule) -> Vec<Check> {
+    let mut checks = vec![Check::Punctuation(rule.task_punctuation())];
+    pattern_checks(Premise::Task, rule.task(), &mut Vec::new(), &mut checks);
+    pattern_checks(Premise::Belief, rule.belief(), &mut Vec::new(), &mut checks);
+    checks
+}
+
+fn pattern_checks(premise: Premise, pattern: &Term, path: &mut Vec<usize>, checks: &mut Vec<Check>) {
+    match pattern {
+        Term::Variable(variable) if variable.op_id() == Op::VarPattern => {},
+        Term::Compound(compound) => {
+            checks.push(Check::Compound {
+                premise,
+                path: path.clone(),
+                op: compound.op_id(),
+                dt: compound.dt(),
+                len: compound.len(),
+            });
+            if compound.is_commutative() {
+                return;
+            }
+            for (index, subterm) in compound.subterms().iter().enumerate() {
+                path.push(index);
+                pattern_checks(premise, subterm, path, checks);
+                path.pop();
+            }
+        },
+        _ => checks.push(Check::Equal { premise, path: path.clone(), term: pattern.clone() }),
+    }
+}
+
+/// Rules whose structural checks a premise pair passed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrieMatch {
+    /// Indices of the reached rules, in insertion order
+    pub rules: Vec<usize>,
+
+    /// Number of checks evaluated on the way
+    pub checks: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: Vec<(Check, Node)>,
+    rules: Vec<usize>,
+}
+
+/// Decision trie over the premise checks of a rule set
+#[derive(Debug, Clone, Default)]
+pub struct RuleTrie {
+    root: Node,
+    nodes: usize,
+}
+
+impl RuleTrie {
+    /// Compile a rule set; rules are identified by their index
+    pub fn new(rules: &[Rule]) -> Self {
+        let mut trie = RuleTrie::default();
+        for (index, rule) in rules.iter().enumerate() {
+            trie.insert(checks(rule), index);
+        }
+        trie
+    }
+
+    /// Add a rule reached through a sequence of checks
+    pub fn insert(&mut self, checks: Vec<Check>, rule: usize) {
+        let mut node = &mut self.root;
+        for check in checks {
+            let position = match node.children.iter().position(|(c, _)| *c == check) {
+                Some(position) => position,
+                None => {
+                    node.children.push((check, Node::default()));
+                    self.nodes += 1;
+                    node.children.len() - 1
+                },
+            };
+            node = &mut node.children[position].1;
+        }
+        node.rules.push(rule);
+    }
+
+    /// Number of checks stored in the trie
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    /// Check if the trie has no checks
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    /// Walk the trie with a premise pair, descending into every child whose
+    /// check passes
+    pub fn matches(&self, task: &Task, belief: &Task) -> TrieMatch {
+        let mut found = TrieMatch::default();
+        let mut pending = vec![&self.root];
+        while let Some(node) = pending.pop() {
+            found.rules.extend_from_slice(&node.rules);
+            for (check, child) in node.children.iter().rev() {
+                found.checks += 1;
+                if check.test(task, belief) {
+                    pending.push(child);
+                }
+            }
+        }
+        found.rules.sort_unstable();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nal::rule::{standard_rules, TruthFn};
+    use crate::parser::Parser;
+    use crate::task::{TaskBuilder, Time};
+    use crate::truth::Truth;
+
+    fn task(narsese: &str, punctuation: Punctuation) -> Task {
+        TaskBuilder::new()
+            .term(Parser::parse_term(narsese).unwrap())
+            .truth(Truth::new(1.0, 0.9))
+            .punctuation(punctuation)
+            .time(Time::Eternal)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_checks() {
+        let rule = Rule::new("r", "<cat --> @P>", "@S", "@S", TruthFn::Deduction).unwrap();
+        let checks = checks(&rule);
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0], Check::Punctuation(Punctuation::Belief));
+        assert!(matches!(&checks[1], Check::Compound { op: Op::Inheritance, len: 2, path, .. } if path.is_empty()));
+        assert!(matches!(&checks[2], Check::Equal { path, .. } if path == &[0]));
+
+        // Subterms of a commutative compound have no fixed position
+        let rule = Rule::new("r", "(&&, @A, done)", "@S", "@S", TruthFn::Deduction).unwrap();
+        assert_eq!(super::checks(&rule).len(), 2);
+    }
+
+    #[test]
+    fn test_shared_prefixes() {
+        let rules = standard_rules();
+        let trie = RuleTrie::new(&rules);
+        let unshared: usize = rules.iter().map(|rule| checks(rule).len()).sum();
+        assert!(trie.len() < unshared);
+
+        // Syllogisms are reached by statements, only the event rules by atoms
+        let statements = trie.matches(
+            &task("<bird --> animal>", Punctuation::Belief),
+            &task("<robin --> bird>", Punctuation::Belief),
+        );
+        assert_eq!(statements.rules.len(), rules.len());
+        let atoms = trie.matches(&task("rain", Punctuation::Belief), &task("wet", Punctuation::Belief));
+        assert!(atoms.rules.iter().all(|&index| !rules[index].task().is_compound()));
+        assert!(!atoms.rules.is_empty());
+
+        // Goals reach none of the belief rules
+        let goals = trie.matches(&task("rain", Punctuation::Goal), &task("wet", Punctuation::Belief));
+        assert!(goals.rules.is_empty());
+        assert_eq!(goals.checks, 1);
+    }
+}
@@ -0,0 +1,323 @@
+//! Canonical Narsese output
+//!
+//! `format` prints a term in the syntax read by `parser::Parser`, so that
+//! `Parser::parse_term(&format(&t)) == Ok(t)`. Statements use the angle
+//! bracket form `<a --> b>`, every other compound the prefix form
+//! `(&&,a,b)`, as in OpenNARS output. Atom names that would read back as
+//! something else, such as `true`, `_` or `42`, or that are not words are
+//! quoted. `format_task` adds the budget, punctuation, occurrence relative to
+//! the current time, and truth value.
+//!
+//! Terms that have no Narsese spelling are printed as closely as possible but
+//! do not read back: atom names containing `"`, negations without exactly one
+//! subterm, and temporal offsets on operators other than `==>`, `<=>` and
+//! `&&`.
+
+use crate::parser::lexer::{Lexer, Spanned, Token};
+use crate::task::{Task, Time};
+use crate::term::{compound::Compound, atom::Atomic, temporal::Dt, Op, Term, TermTrait};
+use std::fmt::{self, Write};
+
+/// Print a term as canonical Narsese
+pub fn format(term: &Term) -> String {
+    let mut out = String::new();
+    write_term(&mut out, term).expect("writing to a String cannot fail");
+    out
+}
+
+/// Print a task as a Narsese sentence: `$p;d;q$ term. :|: %f;c%`, with
+/// the occurrence time as an offset from `now`, the way the parser reads it
+pub fn format_task(task: &Task, now: i64) -> String {
+    let mut out = String::new();
+    write_task(&mut out, task, now).expect("writing to a String cannot fail");
+    out
+}
+
+fn write_task(out: &mut impl Write, task: &Task, now: i64) -> fmt::Result {
+    let budget = task.budget();
+    write!(out, "${:?};{:?};{:?}$ ", budget.priority(), budget.durability(), budget.quality())?;
+    write_term(out, task.term())?;
+    write!(out, "{}", task.punctuation())?;
+    match task.time() {
+        Time::Eternal => {},
+        Time::Tense(t) if t == now => write!(out, " :|:")?,
+        Time::Tense(t) => write!(out, " :{:+}:", t - now)?,
+    }
+    if let Some(truth) = task.truth() {
+        write!(out, " %{:?};{:?}%", truth.frequency(), truth.confidence())?;
+    }
+    Ok(())
+}
+
+fn write_term(out: &mut impl Write, term: &Term) -> fmt::Result {
+    match term {
+        Term::Atomic(Atomic::Atom(name)) => write_atom(out, name),
+        Term::Atomic(Atomic::Int(value)) => write!(out, "{}", value),
+        Term::Atomic(Atomic::Bool(value)) => write!(out, "{}", value),
+        Term::Atomic(Atomic::Null) => write!(out, "null"),
+        Term::Atomic(Atomic::Placeholder) => write!(out, "_"),
+        Term::Variable(variable) => write!(out, "{}", variable),
+        Term::Compound(compound) => write_compound(out, compound),
+    }
+}
+
+/// Quote an atom name unless it reads back as the same atom
+fn write_atom(out: &mut impl Write, name: &str) -> fmt::Result {
+    let plain = match single_token(name) {
+        Some(Token::Word(word)) => !matches!(word.as_str(), "true" | "false" | "null" | "_"),
+        Some(Token::Operator(_)) => true,
+        _ => false,
+    };
+    if plain || name.contains('"') {
+        write!(out, "{}", name)
+    } else {
+        write!(out, "\"{}\"", name)
+    }
+}
+
+fn write_compound(out: &mut impl Write, compound: &Compound) -> fmt::Result {
+    let subterms = compound.subterms();
+    match compound.op_id() {
+        Op::SetExt => write_list(out, "{", subterms, "}"),
+        Op::SetInt => write_list(out, "[", subterms, "]"),
+        op if op.info().statement && subterms.len() == 2 => {
+            if let Some((operator, args)) = operation(compound) {
+                write!(out, "({}", operator)?;
+                for arg in args {
+                    out.write_char(',')?;
+                    write_term(out, arg)?;
+                }
+                return out.write_char(')');
+            }
+            out.write_char('<')?;
+            write_term(out, &subterms[0])?;
+            write!(out, " {} ", symbol(op, compound.dt()))?;
+            write_term(out, &subterms[1])?;
+            out.write_char('>')
+        },
+        op => {
+            let symbol = symbol(op, compound.dt());
+            // `{--,` would read as a set, the copula needs whitespace after it
+            let open = if symbol == "{--" { format!("({} ", symbol) } else { format!("({}", symbol) };
+            if subterms.is_empty() {
+                write_list(out, &open, subterms, ")")
+            } else {
+                write_list(out, &format!("{},", open), subterms, ")")
+            }
+        },
+    }
+}
+
+fn write_list(out: &mut impl Write, open: &str, subterms: &[Term], close: &str) -> fmt::Result {
+    out.write_str(open)?;
+    for (i, subterm) in subterms.iter().enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        write_term(out, subterm)?;
+    }
+    out.write_str(close)
+}
+
+/// `<(*,a,b) --> ^op>` is printed as the operation `(^op,a,b)`
+fn operation(compound: &Compound) -> Option<(&str, &[Term])> {
+    if compound.op_id() != Op::Inheritance || compound.is_temporal() {
+        return None;
+    }
+    let (Term::Compound(args), Term::Atomic(Atomic::Atom(name))) = (&compound.subterms()[0], &compound.subterms()[1]) else {
+        return None;
+    };
+    let is_operator = matches!(single_token(name), Some(Token::Operator(_)));
+    (is_operator && args.op_id() == Op::Product && !args.is_temporal() && !args.is_empty())
+        .then(|| (name.as_str(), args.subterms()))
+}
+
+/// The token `text` lexes to, if it is exactly one token
+fn single_token(text: &str) -> Option<Token> {
+    match Lexer::new(text).next_token() {
+        Ok(Some(Spanned { token, start: 0, end })) if end == text.len() => Some(token),
+        _ => None,
+    }
+}
+
+/// Copula or connector for an operator, with the temporal offset folded in
+fn symbol(op: Op, dt: Dt) -> String {
+    let symbol = match (op, dt) {
+        (Op::Implication, Dt::Offset(1)) => "=/>",
+        (Op::Implication, Dt::Offset(0)) => "=|>",
+        (Op::Implication, Dt::Offset(-1)) => "=\\>",
+        (Op::Equivalence, Dt::Offset(1)) => "</>",
+        (Op::Equivalence, Dt::Offset(0)) => "<|>",
+        (Op::Conjunction, Dt::Offset(1)) => "&/",
+        (Op::Conjunction, Dt::Offset(0)) => "&|",
+        (op, dt) if op.info().temporal => return format!("{}{}", op.info().symbol, dt),
+        (op, _) if op.info().compound => op.info().symbol,
+        (op, _) => return op.to_string(),
+    };
+    symbol.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::task::{Budget, Punctuation, TaskBuilder};
+    use crate::term::var::Variable;
+    use crate::truth::Truth;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn atom(name: &str) -> Term {
+        Term::Atomic(Atomic::new_atom(name))
+    }
+
+    #[test]
+    fn test_format_canonical() {
+        for (input, expected) in [
+            ("<bird --> animal>", "<bird --> animal>"),
+            ("(&&, a, <b --> c>)", "(&&,a,<b --> c>)"),
+            ("(a && b && c)", "(&&,a,b,c)"),
+            ("(&/, a, b)", "(&/,a,b)"),
+            ("<a =\\> b>", "<a =\\> b>"),
+            ("(a ==>+5 b)", "<a ==>+5 b>"),
+            ("(&&-3, a, b)", "(&&-3,a,b)"),
+            ("{a, b}", "{a,b}"),
+            ("[bright]", "[bright]"),
+            ("(--, a)", "(--,a)"),
+            ("--a", "(--,a)"),
+            ("(^go, {SELF}, home)", "(^go,{SELF},home)"),
+            ("<(*) --> ^go>", "<(*) --> ^go>"),
+            ("(/, js, x, _)", "(/,js,x,_)"),
+            ("(*, -3, true, \"a b\", #x)", "(*,-3,true,\"a b\",#x)"),
+            ("<a {-- b>", "<a {-- b>"),
+        ] {
+            assert_eq!(format(&Parser::parse_term(input).unwrap()), expected, "input {}", input);
+        }
+    }
+
+    #[test]
+    fn test_format_unparsed_shapes() {
+        let empty = Term::Compound(Compound::new(Op::Conjunction, vec![]));
+        assert_eq!(format(&empty), "(&&)");
+        assert_eq!(Parser::parse_term("(&&)").unwrap(), empty);
+
+        let instance = Term::Compound(Compound::new(Op::Instance, vec![atom("a")]));
+        assert_eq!(format(&instance), "({-- ,a)");
+        assert_eq!(Parser::parse_term("({-- ,a)").unwrap(), instance);
+    }
+
+    #[test]
+    fn test_format_task() {
+        let task = TaskBuilder::new()
+            .term(Parser::parse_term("<bird --> animal>").unwrap())
+            .truth(Truth::new(0.8, 0.6))
+            .punctuation(Punctuation::Goal)
+            .time(Time::Tense(0))
+            .budget(Budget::new(0.9, 0.5, 0.25))
+            .build()
+            .unwrap();
+        let text = format_task(&task, 0);
+        assert_eq!(text, "$0.9;0.5;0.25$ <bird --> animal>! :|: %0.8;0.6%");
+
+        let (budget, (term, truth, punctuation, time)) = Parser::parse_budgeted_sentence(&text).unwrap();
+        assert_eq!(budget.as_ref(), Some(task.budget()));
+        assert_eq!(&term, task.term());
+        assert_eq!(truth.as_ref(), task.truth());
+        assert_eq!(punctuation, task.punctuation());
+        assert_eq!(time.map(|tense| tense.resolve(0, 1.0)), Some(task.time()));
+
+        let question = TaskBuilder::new()
+            .term(atom("cat"))
+            .punctuation(Punctuation::Question)
+            .time(Time::Tense(-4))
+            .build()
+            .unwrap();
+        assert_eq!(format_task(&question, 0), "$0.5;0.5;0.5$ cat? :-4:");
+    }
+
+    #[test]
+    fn test_format_task_relative_to_now() {
+        for (time, expected) in [(12, ":|:"), (15, ":+3:"), (8, ":-4:")] {
+            let task = TaskBuilder::new()
+                .term(atom("cat"))
+                .truth(Truth::new(1.0, 0.9))
+                .punctuation(Punctuation::Belief)
+                .time(Time::Tense(time))
+                .build()
+                .unwrap();
+            let text = format_task(&task, 12);
+            assert!(text.contains(expected), "{} in {}", expected, text);
+
+            let (_, (_, _, _, tense)) = Parser::parse_budgeted_sentence(&text).unwrap();
+            assert_eq!(tense.map(|tense| tense.resolve(12, 1.0)), Some(task.time()));
+        }
+    }
+
+    #[test]
+    fn test_format_reserved_atoms() {
+        for name in ["true", "false", "null", "_", "42", "007", "two words", ""] {
+            let term = atom(name);
+            let text = format(&term);
+            assert_eq!(text, format!("\"{}\"", name));
+            assert_eq!(Parser::parse_term(&text), Ok(term), "round trip of {}", text);
+        }
+        assert_eq!(format(&atom("^go")), "^go");
+        assert_eq!(format(&atom("99999999999")), "99999999999");
+    }
+
+    /// Random terms covering every shape the parser can read back
+    fn random_term(rng: &mut StdRng, depth: usize) -> Term {
+        let leaf = depth == 0 || rng.gen_bool(0.3);
+        if leaf {
+            const NAMES: [&str; 9] = ["a", "bird", "x_1", "_a", "^go", "two words", "true", "_", "42"];
+            return match rng.gen_range(0..8) {
+                0 => Term::Atomic(Atomic::new_int(rng.gen_range(-100..100))),
+                1 => Term::Atomic(Atomic::new_bool(rng.gen())),
+                2 => Term::Variable(Variable::new_dep("x")),
+                3 => Term::Variable(Variable::new_indep("y")),
+                4 => Term::Variable(Variable::new_query("1")),
+                5 => Term::Variable(Variable::new_pattern("p")),
+                6 => Term::Atomic(Atomic::new_placeholder()),
+                _ => atom(NAMES[rng.gen_range(0..NAMES.len())]),
+            };
+        }
+
+        const OPS: [Op; 17] = [
+            Op::Neg, Op::Conjunction, Op::Disjunction, Op::Intersection, Op::IntersectionExt,
+            Op::Difference, Op::DifferenceExt, Op::Inheritance, Op::Similarity, Op::Implication,
+            Op::Equivalence, Op::Instance, Op::Property, Op::InstanceProperty, Op::ImageExt,
+            Op::ImageInt, Op::Product,
+        ];
+        let op = match rng.gen_range(0..10) {
+            0 => Op::SetExt,
+            1 => Op::SetInt,
+            _ => OPS[rng.gen_range(0..OPS.len())],
+        };
+        let len = match op {
+            Op::Neg => 1,
+            _ if op.info().statement && rng.gen_bool(0.8) => 2,
+            _ => rng.gen_range(0..4),
+        };
+        let subterms = (0..len).map(|_| random_term(rng, depth - 1)).collect();
+        let temporal = op.info().temporal;
+        if temporal && rng.gen_bool(0.5) {
+            let dt = match rng.gen_range(-3..5) {
+                4 => Dt::Xternal,
+                dt => Dt::Offset(dt),
+            };
+            Term::Compound(Compound::new_dt(op, subterms, dt))
+        } else {
+            Term::Compound(Compound::new(op, subterms))
+        }
+    }
+
+    #[test]
+    fn test_round_trip_random_terms() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..2000 {
+            let term = random_term(&mut rng, 4);
+            let text = format(&term);
+            assert_eq!(Parser::parse_term(&text), Ok(term), "round trip of {}", text);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Parse errors with source positions
+//!
+//! Every error carries the byte span it refers to and the set of tokens the
+//! parser would have accepted there, so that hand-typed Narsese can be
+//! reported with a caret pointing at the offending input.
+
+use std::fmt;
+
+/// Byte range in the parsed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// Byte offset of the first character
+    pub start: usize,
+
+    /// Byte offset one past the last character
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a new span
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end: end.max(start) }
+    }
+
+    /// Zero-width span at a position, used for end of input
+    pub fn point(pos: usize) -> Self {
+        Span { start: pos, end: pos }
+    }
+
+    /// Shift the span by a byte offset
+    pub fn offset(self, by: usize) -> Self {
+        Span { start: self.start + by, end: self.end + by }
+    }
+}
+
+/// What went wrong while parsing
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// Character that cannot start any token
+    UnexpectedChar(char),
+
+    /// Valid token in the wrong place
+    UnexpectedToken(String),
+
+    /// Unexpected end of input
+    UnexpectedEndOfInput,
+
+    /// Invalid term structure
+    InvalidTerm(String),
+
+    /// Invalid truth value format
+    InvalidTruth(String),
+
+    /// Invalid budget format
+    InvalidBudget(String),
+
+    /// Invalid punctuation
+    InvalidPunctuation(char),
+
+    /// Invalid time specification
+    InvalidTime(String),
+
+    /// Invalid inference rule definition
+    InvalidRule(String),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedChar(c) =>
+                write!(f, "Unexpected character '{}'", c),
+            ParseErrorKind::UnexpectedToken(token) =>
+                write!(f, "Unexpected '{}'", token),
+            ParseErrorKind::UnexpectedEndOfInput =>
+                write!(f, "Unexpected end of input"),
+            ParseErrorKind::InvalidTerm(msg) =>
+                write!(f, "Invalid term: {}", msg),
+            ParseErrorKind::InvalidTruth(msg) =>
+                write!(f, "Invalid truth value: {}", msg),
+            ParseErrorKind::InvalidBudget(msg) =>
+                write!(f, "Invalid budget: {}", msg),
+            ParseErrorKind::InvalidPunctuation(c) =>
+                write!(f, "Invalid punctuation: '{}'", c),
+            ParseErrorKind::InvalidTime(msg) =>
+                write!(f, "Invalid time specification: {}", msg),
+            ParseErrorKind::InvalidRule(msg) =>
+                write!(f, "Invalid rule: {}", msg),
+        }
+    }
+}
+
+/// Parse error with location and expectations
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Span,
+    expected: Vec<&'static str>,
+}
+
+impl ParseError {
+    /// Create a new error covering a span
+    pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+        ParseError { kind, span, expected: Vec::new() }
+    }
+
+    /// Record what the parser would have accepted at this position
+    pub fn expecting(mut self, expected: &[&'static str]) -> Self {
+        for e in expected {
+            if !self.expected.contains(e) {
+                self.expected.push(e);
+            }
+        }
+        self
+    }
+
+    /// Shift the span, for errors found in a slice of a larger input
+    pub fn offset(mut self, by: usize) -> Self {
+        self.span = self.span.offset(by);
+        self
+    }
+
+    /// Get the error kind
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// Get the byte span of the error
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Get the tokens that would have been accepted
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// One-based line and column (in characters) of the error start
+    pub fn line_col(&self, source: &str) -> (usize, usize) {
+        let start = self.span.start.min(source.len());
+        let before = &source[..start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = source[line_start..start].chars().count() + 1;
+        (line, column)
+    }
+
+    /// Render the error with the offending source line and a caret underline
+    pub fn render(&self, source: &str) -> String {
+        let (line, column) = self.line_col(source);
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[line_start..].find('\n').map(|i| line_start + i).unwrap_or(source.len());
+        let text = &source[line_start..line_end];
+
+        let end = self.span.end.clamp(start, line_end);
+        let width = source[start..end].chars().count().max(1);
+        let gutter = line.to_string();
+        let pad = " ".repeat(gutter.len());
+
+        let mut out = format!("error: {}", self.summary());
+        out.push_str(&format!("\n{}--> {}:{}", pad, line, column));
+        out.push_str(&format!("\n{} |", pad));
+        out.push_str(&format!("\n{} | {}", gutter, text));
+        out.push_str(&format!("\n{} | {}{}", pad, " ".repeat(column - 1), "^".repeat(width)));
+        out
+    }
+
+    /// Error message plus the expected-token set
+    fn summary(&self) -> String {
+        match self.expected.len() {
+            0 => self.kind.to_string(),
+            1 => format!("{}, expected {}", self.kind, self.expected[0]),
+            _ => format!("{}, expected one of {}", self.kind, self.expected.join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.summary(), self.span.start)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col() {
+        let source = "<a --> b>.\n<c --> d>.";
+        let error = ParseError::new(ParseErrorKind::UnexpectedChar('c'), Span::new(12, 13));
+        assert_eq!(error.line_col(source), (2, 2));
+    }
+
+    #[test]
+    fn test_render_caret() {
+        let error = ParseError::new(ParseErrorKind::UnexpectedToken("b".to_string()), Span::new(3, 4))
+            .expecting(&["copula"]);
+        let rendered = error.render("<a b>.");
+        assert_eq!(rendered, "error: Unexpected 'b', expected copula\n --> 1:4\n  |\n1 | <a b>.\n  |    ^");
+    }
+
+    #[test]
+    fn test_render_end_of_input() {
+        let error = ParseError::new(ParseErrorKind::UnexpectedEndOfInput, Span::point(6))
+            .expecting(&["'>'", "copula"]);
+        let rendered = error.render("<a -->");
+        assert!(rendered.starts_with("error: Unexpected end of input, expected one of '>', copula"));
+        assert!(rendered.ends_with("1 | <a -->\n  |       ^"));
+    }
+}
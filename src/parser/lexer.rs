@@ -0,0 +1,336 @@
+//! Tokenizer for Narsese terms
+//!
+//! The lexer is pulled on demand by the term grammar in the parent module, so
+//! it never looks past the end of a term. Whatever follows the term (truth,
+//! punctuation, tense) is left untouched for the sentence-level parsing code.
+
+use crate::term::temporal::Dt;
+use super::{ParseError, ParseErrorKind, Span};
+
+/// Statement copulas, longest first so that prefixes never shadow them
+const COPULAS: [&str; 12] = [
+    "-->", "<->", "==>", "<=>", "=/>", "=|>", "=\\>", "</>", "<|>", "{--", "--]", "{-]",
+];
+
+/// Compound connectors, longest first so that prefixes never shadow them
+const CONNECTORS: [&str; 12] = [
+    "&&", "||", "&/", "&|", "--", "&", "|", "-", "~", "*", "/", "\\",
+];
+
+/// A lexical token of a Narsese term
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+    /// `<` opening a statement
+    LAngle,
+    /// `>` closing a statement
+    RAngle,
+    /// `{` opening an extensional set
+    LBrace,
+    /// `}`
+    RBrace,
+    /// `[` opening an intensional set
+    LBracket,
+    /// `]`
+    RBracket,
+    /// `,`
+    Comma,
+    /// Statement copula such as `-->` or `=/>`
+    Copula(&'static str),
+    /// Compound connector such as `&&` or `--`
+    Connector(&'static str),
+    /// Plain word (atom name)
+    Word(String),
+    /// Quoted string, without the quotes; always an atom name
+    Quoted(String),
+    /// Integer literal
+    Int(i32),
+    /// Variable: prefix character and name
+    Variable(char, String),
+    /// Operator such as `^go`
+    Operator(String),
+}
+
+/// A token together with its byte span in the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    /// The token itself
+    pub token: Token,
+
+    /// Byte offset of the first character
+    pub start: usize,
+
+    /// Byte offset one past the last character
+    pub end: usize,
+}
+
+/// On-demand tokenizer over a Narsese string
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a lexer positioned at the start of the input
+    pub fn new(input: &'a str) -> Self {
+        Lexer { input, pos: 0 }
+    }
+
+    /// Current byte offset into the input
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The input this lexer reads from
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    /// The unread remainder of the input
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    /// Look at the next token without consuming it
+    pub fn peek(&self) -> Result<Option<Spanned>, ParseError> {
+        self.clone().next_token()
+    }
+
+    /// Look at the token after the next one without consuming anything
+    pub fn peek_second(&self) -> Result<Option<Spanned>, ParseError> {
+        let mut ahead = self.clone();
+        ahead.next_token()?;
+        ahead.next_token()
+    }
+
+    /// Consume and return the next token, or `None` at end of input
+    pub fn next_token(&mut self) -> Result<Option<Spanned>, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let start = self.pos;
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        for copula in COPULAS {
+            if rest.starts_with(copula) && self.copula_allowed(copula, &rest[copula.len()..]) {
+                return Ok(Some(self.emit(Token::Copula(copula), copula.len())));
+            }
+        }
+
+        let single = match c {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            '<' => Some(Token::LAngle),
+            '>' => Some(Token::RAngle),
+            '{' => Some(Token::LBrace),
+            '}' => Some(Token::RBrace),
+            '[' => Some(Token::LBracket),
+            ']' => Some(Token::RBracket),
+            ',' => Some(Token::Comma),
+            _ => None,
+        };
+        if let Some(token) = single {
+            return Ok(Some(self.emit(token, 1)));
+        }
+
+        for connector in CONNECTORS {
+            if rest.starts_with(connector) {
+                return Ok(Some(self.emit(Token::Connector(connector), connector.len())));
+            }
+        }
+
+        match c {
+            '$' | '#' | '?' | '@' => {
+                let name_len = Self::word_len(&rest[1..]);
+                let name = rest[1..1 + name_len].to_string();
+                Ok(Some(self.emit(Token::Variable(c, name), 1 + name_len)))
+            },
+            '^' => {
+                let name_len = Self::word_len(&rest[1..]);
+                if name_len == 0 {
+                    return Err(ParseError::new(ParseErrorKind::UnexpectedChar(c), Span::new(start, start + 1))
+                        .expecting(&["operator name"]));
+                }
+                let name = rest[..1 + name_len].to_string();
+                Ok(Some(self.emit(Token::Operator(name), 1 + name_len)))
+            },
+            '"' => {
+                let close = rest[1..].find('"').ok_or_else(|| {
+                    ParseError::new(ParseErrorKind::UnexpectedEndOfInput, Span::point(self.input.len()))
+                        .expecting(&["'\"'"])
+                })?;
+                let name = rest[1..1 + close].to_string();
+                Ok(Some(self.emit(Token::Quoted(name), close + 2)))
+            },
+            _ => {
+                let len = Self::word_len(rest);
+                if len == 0 {
+                    return Err(ParseError::new(ParseErrorKind::UnexpectedChar(c), Span::new(start, start + c.len_utf8())));
+                }
+                let word = &rest[..len];
+                let token = if word.bytes().all(|b| b.is_ascii_digit()) {
+                    match word.parse::<i32>() {
+                        Ok(value) => Token::Int(value),
+                        Err(_) => Token::Word(word.to_string()),
+                    }
+                } else {
+                    Token::Word(word.to_string())
+                };
+                Ok(Some(self.emit(token, len)))
+            }
+        }
+    }
+
+    /// Consume a signed integer written directly after the previous token,
+    /// such as the `+5` in `==>+5`; nothing is consumed if there is none
+    pub fn signed_suffix(&mut self) -> Option<i32> {
+        let rest = self.rest();
+        let sign = rest.chars().next().filter(|c| *c == '+' || *c == '-')?;
+        let digits = rest[1..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let value = rest[1..1 + digits].parse::<i32>().ok()?;
+        self.pos += 1 + digits;
+        Some(if sign == '-' { -value } else { value })
+    }
+
+    /// Consume a temporal offset written directly after the previous token:
+    /// `+-` for an unknown offset, or a signed integer as in `signed_suffix`
+    pub fn dt_suffix(&mut self) -> Option<Dt> {
+        if self.rest().starts_with("+-") {
+            self.pos += 2;
+            return Some(Dt::Xternal);
+        }
+        self.signed_suffix().map(Dt::Offset)
+    }
+
+    /// `{--` only reads as a copula when followed by whitespace; otherwise it
+    /// is an extensional set whose first member is a negation
+    fn copula_allowed(&self, copula: &str, after: &str) -> bool {
+        copula != "{--" || after.starts_with(char::is_whitespace)
+    }
+
+    fn emit(&mut self, token: Token, len: usize) -> Spanned {
+        let start = self.pos;
+        self.pos += len;
+        Spanned { token, start, end: self.pos }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    fn word_len(s: &str) -> usize {
+        s.char_indices()
+            .find(|(_, c)| !Self::is_word_char(*c))
+            .map(|(i, _)| i)
+            .unwrap_or(s.len())
+    }
+
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(input);
+        let mut out = Vec::new();
+        while let Some(spanned) = lexer.next_token().unwrap() {
+            out.push(spanned.token);
+        }
+        out
+    }
+
+    #[test]
+    fn test_statement_tokens() {
+        assert_eq!(tokens("<bird --> animal>"), vec![
+            Token::LAngle,
+            Token::Word("bird".to_string()),
+            Token::Copula("-->"),
+            Token::Word("animal".to_string()),
+            Token::RAngle,
+        ]);
+    }
+
+    #[test]
+    fn test_connector_and_variable_tokens() {
+        assert_eq!(tokens("(&&, $x, #y, ?z, ^go, 42)"), vec![
+            Token::LParen,
+            Token::Connector("&&"),
+            Token::Comma,
+            Token::Variable('$', "x".to_string()),
+            Token::Comma,
+            Token::Variable('#', "y".to_string()),
+            Token::Comma,
+            Token::Variable('?', "z".to_string()),
+            Token::Comma,
+            Token::Operator("^go".to_string()),
+            Token::Comma,
+            Token::Int(42),
+            Token::RParen,
+        ]);
+    }
+
+    #[test]
+    fn test_instance_copula_versus_set() {
+        assert_eq!(tokens("{-- a"), vec![Token::Copula("{--"), Token::Word("a".to_string())]);
+        assert_eq!(tokens("{--a}"), vec![
+            Token::LBrace,
+            Token::Connector("--"),
+            Token::Word("a".to_string()),
+            Token::RBrace,
+        ]);
+    }
+
+    #[test]
+    fn test_quoted_tokens() {
+        assert_eq!(tokens("\"two words\", \"true\", \"42\""), vec![
+            Token::Quoted("two words".to_string()),
+            Token::Comma,
+            Token::Quoted("true".to_string()),
+            Token::Comma,
+            Token::Quoted("42".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_spans() {
+        let mut lexer = Lexer::new("  <a ==> b>");
+        let first = lexer.next_token().unwrap().unwrap();
+        assert_eq!((first.start, first.end), (2, 3));
+        lexer.next_token().unwrap();
+        let copula = lexer.next_token().unwrap().unwrap();
+        assert_eq!(copula.token, Token::Copula("==>"));
+        assert_eq!((copula.start, copula.end), (5, 8));
+    }
+
+    #[test]
+    fn test_signed_suffix() {
+        let mut lexer = Lexer::new("==>-12 b");
+        assert_eq!(lexer.next_token().unwrap().unwrap().token, Token::Copula("==>"));
+        assert_eq!(lexer.signed_suffix(), Some(-12));
+        assert_eq!(lexer.next_token().unwrap().unwrap().token, Token::Word("b".to_string()));
+
+        let mut lexer = Lexer::new("&& +3");
+        lexer.next_token().unwrap();
+        assert_eq!(lexer.signed_suffix(), None);
+        assert_eq!(lexer.rest(), " +3");
+
+        let mut lexer = Lexer::new("&&+- b");
+        lexer.next_token().unwrap();
+        assert_eq!(lexer.dt_suffix(), Some(Dt::Xternal));
+        assert_eq!(lexer.rest(), " b");
+    }
+}
@@ -3,316 +3,412 @@
 //! This module provides parsing capabilities for Narsese syntax,
 //! the logical language used in NARS (Non-Axiomatic Reasoner).
 
-use crate::term::{Term, Op, var::Variable};
+pub mod error;
+pub mod lexer;
+
+use crate::term::{Term, Op, var::Variable, atom::Atomic, compound::Compound, temporal::{Dt, Sequence}};
 use crate::truth::Truth;
-use crate::task::{Punctuation, Time};
+use crate::task::{Budget, Punctuation, Time};
+use lexer::{Lexer, Token, Spanned};
 use std::str::FromStr;
 
-/// Parse error types
-#[derive(Debug, Clone, PartialEq)]
-pub enum ParseError {
-    /// Unexpected character at position
-    UnexpectedChar(char, usize),
-    
-    /// Unexpected end of input
-    UnexpectedEndOfInput,
-    
-    /// Invalid term structure
-    InvalidTerm(String),
-    
-    /// Invalid truth value format
-    InvalidTruth(String),
-    
-    /// Invalid punctuation
-    InvalidPunctuation(char),
-    
-    /// Invalid time specification
-    InvalidTime(String),
+pub use error::{ParseError, ParseErrorKind, Span};
+
+/// A parsed sentence: term, optional truth, punctuation and optional tense
+pub type Sentence = (Term, Option<Truth>, Punctuation, Option<Tense>);
+
+/// Occurrence time of a sentence as written, relative to when it is input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tense {
+    /// `:|:`, the current time
+    Present,
+    /// `:/:`, one duration after the current time
+    Future,
+    /// `:\:`, one duration before the current time
+    Past,
+    /// `:+n:` or `:-n:`, the given number of cycles from the current time
+    Offset(i64),
 }
 
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ParseError::UnexpectedChar(c, pos) => 
-                write!(f, "Unexpected character '{}' at position {}", c, pos),
-            ParseError::UnexpectedEndOfInput => 
-                write!(f, "Unexpected end of input"),
-            ParseError::InvalidTerm(msg) => 
-                write!(f, "Invalid term: {}", msg),
-            ParseError::InvalidTruth(msg) => 
-                write!(f, "Invalid truth value: {}", msg),
-            ParseError::InvalidPunctuation(c) => 
-                write!(f, "Invalid punctuation: '{}'", c),
-            ParseError::InvalidTime(msg) => 
-                write!(f, "Invalid time specification: {}", msg),
-        }
+impl Tense {
+    /// Occurrence time of the tense for a sentence input at `now`
+    pub fn resolve(self, now: i64, duration: f32) -> Time {
+        let duration = duration.round() as i64;
+        Time::Tense(match self {
+            Tense::Present => now,
+            Tense::Future => now + duration,
+            Tense::Past => now - duration,
+            Tense::Offset(offset) => now + offset,
+        })
     }
 }
 
-impl std::error::Error for ParseError {}
+const TERM: &str = "term";
+const COPULA: &str = "copula";
+const CONNECTOR: &str = "connector";
+const PUNCTUATION: &str = "punctuation";
 
 /// Parser for Narsese sentences
 pub struct Parser;
 
 impl Parser {
-    /// Parse a Narsese sentence into a term, truth value, and punctuation
-    pub fn parse_sentence(input: &str) -> Result<(Term, Option<Truth>, Punctuation, Option<Time>), ParseError> {
-        let input = input.trim();
+    /// Parse a Narsese sentence into a term, truth value, and punctuation;
+    /// a budget prefix is accepted but dropped
+    pub fn parse_sentence(input: &str) -> Result<Sentence, ParseError> {
+        Parser::parse_budgeted_sentence(input).map(|(_, sentence)| sentence)
+    }
+    
+    /// Parse a Narsese sentence together with its optional `$p;d;q$` budget
+    /// prefix, as written in NARS input files:
+    /// `$0.8;0.5;0.9$ <bird --> animal>. :|: %1.0;0.9%`
+    pub fn parse_budgeted_sentence(input: &str) -> Result<(Option<Budget>, Sentence), ParseError> {
+        let leading = input.len() - input.trim_start().len();
+        Parser::parse_trimmed_sentence(input.trim()).map_err(|e| e.offset(leading))
+    }
+    
+    /// Parse one sentence per non-blank line; error spans are relative to the
+    /// whole input so that `ParseError::line_col` and `render` point at the
+    /// right line
+    pub fn parse_sentences(input: &str) -> Result<Vec<Sentence>, ParseError> {
+        let mut sentences = Vec::new();
+        let mut line_start = 0;
+        for line in input.split('\n') {
+            if !line.trim().is_empty() {
+                sentences.push(Parser::parse_sentence(line).map_err(|e| e.offset(line_start))?);
+            }
+            line_start += line.len() + 1;
+        }
+        Ok(sentences)
+    }
+    
+    fn parse_trimmed_sentence(input: &str) -> Result<(Option<Budget>, Sentence), ParseError> {
         if input.is_empty() {
-            return Err(ParseError::UnexpectedEndOfInput);
+            return Err(ParseError::new(ParseErrorKind::UnexpectedEndOfInput, Span::point(0))
+                .expecting(&[TERM]));
         }
+        let at = |rest: &str| input.len() - rest.len();
         
-        // Split the sentence into components
-        let (term_part, rest) = Parser::split_term_and_rest(input)?;
-        let term = Parser::parse_term(term_part)?;
+        // Parse budget prefix if present
+        let (budget, term_part) = Parser::parse_budget(input)?;
         
-        // Parse truth value if present
-        let (truth, punctuation_part) = Parser::parse_truth_value(rest)?;
+        // The term grammar only consumes the tokens of the term itself
+        let mut lexer = Lexer::new(term_part);
+        let term = Parser::term(&mut lexer).map_err(|e| e.offset(at(term_part)))?;
+        let rest = lexer.rest().trim_start();
         
-        // Parse punctuation
-        let (punctuation, time_part) = Parser::parse_punctuation(punctuation_part)?;
+        // Parse `{f;c}` truth value if present
+        let (brace_truth, punctuation_part) = Parser::parse_truth_value(rest)
+            .map_err(|e| e.offset(at(rest)))?;
         
-        // Parse time if present
-        let time = Parser::parse_time(time_part)?;
+        // Parse punctuation
+        let (punctuation, tail) = Parser::parse_punctuation(punctuation_part)
+            .map_err(|e| e.offset(at(punctuation_part)))?;
+        
+        // Tense comes before a trailing `%f;c%` truth value
+        let (time_part, truth_part) = tail.split_at(tail.find('%').unwrap_or(tail.len()));
+        let time = Parser::parse_time(time_part)
+            .map_err(|e| e.offset(at(time_part)))?;
+        let percent_truth = Parser::parse_percent_truth(truth_part)
+            .map_err(|e| e.offset(at(truth_part)))?;
+        
+        let truth = match (brace_truth, percent_truth) {
+            (Some(_), Some(_)) => return Err(ParseError::new(
+                ParseErrorKind::InvalidTruth("Sentence has two truth values".to_string()),
+                Span::new(at(truth_part), input.len()),
+            )),
+            (truth, None) | (None, truth) => truth,
+        };
         
-        Ok((term, truth, punctuation, time))
+        Ok((budget, (term, truth, punctuation, time)))
     }
     
-    /// Split the input into term part and the rest
-    fn split_term_and_rest(input: &str) -> Result<(&str, &str), ParseError> {
-        // Find the end of the term part (before truth value or punctuation)
-        let end_pos = if input.contains('{') {
-            // Has truth value
-            input.find('{').unwrap()
-        } else {
-            // No truth value, find punctuation
-            // We need to be careful not to mistake : in temporal specs as punctuation
-            let mut pos = 0;
-            let chars: Vec<char> = input.chars().collect();
-            while pos < chars.len() {
-                let c = chars[pos];
-                // Check if this is a punctuation character
-                if matches!(c, '.' | '!' | '?' | '@' | ';') {
-                    break;
-                }
-                // Check if this might be the start of a temporal spec
-                if c == ':' && pos + 1 < chars.len() {
-                    // Skip the temporal specification
-                    pos += 1;
-                    // Skip until we find the end of the temporal spec or punctuation
-                    while pos < chars.len() && !matches!(chars[pos], '.' | '!' | '?' | '@' | ';') {
-                        pos += 1;
-                    }
-                    if pos < chars.len() {
-                        break;
-                    }
-                } else {
-                    pos += 1;
-                }
-            }
-            if pos >= chars.len() {
-                // If no valid punctuation found, check if there's an invalid character
-                if let Some(invalid_pos) = input.chars().position(|c| !c.is_alphanumeric() && c != '(' && c != ')' && c != ' ' && c != '<' && c != '>' && c != '-' && c != '&' && c != ':') {
-                    return Err(ParseError::InvalidPunctuation(input.chars().nth(invalid_pos).unwrap()));
-                } else {
-                    return Err(ParseError::UnexpectedEndOfInput);
-                }
-            }
-            pos
+    /// Parse a complete term, rejecting any trailing input
+    pub fn parse_term(input: &str) -> Result<Term, ParseError> {
+        let mut lexer = Lexer::new(input);
+        let term = Parser::term(&mut lexer)?;
+        match lexer.next_token()? {
+            None => Ok(term),
+            Some(trailing) => Err(Parser::unexpected(&lexer, &trailing, &[])),
+        }
+    }
+    
+    /// Parse a complete term, also accepting a statement written without
+    /// brackets as in rule files: `a --> b`
+    pub fn parse_statement(input: &str) -> Result<Term, ParseError> {
+        let mut lexer = Lexer::new(input);
+        let subject = Parser::term(&mut lexer)?;
+        let term = match lexer.peek()?.map(|s| s.token) {
+            Some(Token::Copula(_)) => {
+                let (op, dt) = Parser::copula(&mut lexer)?;
+                let predicate = Parser::term(&mut lexer)?;
+                Parser::compound(op, vec![subject, predicate], dt)
+            },
+            _ => subject,
         };
-        
-        Ok((&input[..end_pos], &input[end_pos..]))
+        match lexer.next_token()? {
+            None => Ok(term),
+            Some(trailing) => Err(Parser::unexpected(&lexer, &trailing, &[COPULA])),
+        }
     }
     
-    /// Parse a term from a string
-    fn parse_term(input: &str) -> Result<Term, ParseError> {
-        let input = input.trim();
-        if input.is_empty() {
-            return Err(ParseError::InvalidTerm("Empty term".to_string()));
+    /// term := statement | compound | set | negation | atomic
+    fn term(lexer: &mut Lexer) -> Result<Term, ParseError> {
+        let spanned = Parser::expect_token(lexer, &[TERM])?;
+        match spanned.token {
+            Token::LAngle => {
+                let subject = Parser::term(lexer)?;
+                let (op, dt) = Parser::copula(lexer)?;
+                let predicate = Parser::term(lexer)?;
+                Parser::expect(lexer, Token::RAngle, "'>'")?;
+                Ok(Parser::compound(op, vec![subject, predicate], dt))
+            },
+            Token::LParen => Parser::compound_body(lexer, spanned.start),
+            Token::LBrace => {
+                let members = Parser::term_list(lexer, Token::RBrace, "'}'")?;
+                Ok(Parser::compound(Op::SetExt, members, Dt::Eternal))
+            },
+            Token::LBracket => {
+                let members = Parser::term_list(lexer, Token::RBracket, "']'")?;
+                Ok(Parser::compound(Op::SetInt, members, Dt::Eternal))
+            },
+            Token::Connector("--") => {
+                let inner = Parser::term(lexer)?;
+                Ok(Parser::compound(Op::Neg, vec![inner], Dt::Eternal))
+            },
+            Token::Word(name) if name == "true" || name == "false" =>
+                Ok(Term::Atomic(Atomic::new_bool(name == "true"))),
+            Token::Word(name) if name == "null" => Ok(Term::Atomic(Atomic::new_null())),
+            Token::Word(name) if name == "_" => Ok(Term::Atomic(Atomic::new_placeholder())),
+            Token::Word(name) | Token::Quoted(name) | Token::Operator(name) => Ok(Term::Atomic(Atomic::new_atom(&name))),
+            Token::Int(value) => Ok(Term::Atomic(Atomic::new_int(value))),
+            // A minus sign directly in front of a number is a negative integer
+            Token::Connector("-") => match lexer.peek()? {
+                Some(Spanned { token: Token::Int(value), start, .. }) if start == spanned.end => {
+                    lexer.next_token()?;
+                    Ok(Term::Atomic(Atomic::new_int(-value)))
+                },
+                _ => Err(Parser::unexpected(lexer, &spanned, &[TERM])),
+            },
+            Token::Variable(prefix, name) => Ok(Term::Variable(match prefix {
+                '#' => Variable::new_dep(&name),
+                '$' => Variable::new_indep(&name),
+                '?' => Variable::new_query(&name),
+                _ => Variable::new_pattern(&name),
+            })),
+            _ => Err(Parser::unexpected(lexer, &spanned, &[TERM])),
+        }
+    }
+    
+    /// Everything after an opening parenthesis: prefix form `(op, a, b)`,
+    /// operation `(^op, a)`, infix statement `(a --> b)`, infix compound
+    /// `(a && b && c)` or product shorthand `(a, b)`
+    fn compound_body(lexer: &mut Lexer, open: usize) -> Result<Term, ParseError> {
+        let mut ahead = lexer.clone();
+        if let Some(Spanned { token: Token::Connector(symbol) | Token::Copula(symbol), .. }) = ahead.next_token()? {
+            let (op, dt) = Parser::symbol_dt(&mut ahead, symbol);
+            let subterms = match ahead.next_token()?.map(|s| s.token) {
+                Some(Token::Comma) => {
+                    *lexer = ahead;
+                    Some(Parser::term_list(lexer, Token::RParen, "')'")?)
+                },
+                Some(Token::RParen) if symbol != "--" => {
+                    *lexer = ahead;
+                    Some(Vec::new())
+                },
+                _ => None,
+            };
+            if let Some(subterms) = subterms {
+                if op == Op::Neg && subterms.len() != 1 {
+                    return Err(ParseError::new(
+                        ParseErrorKind::InvalidTerm("Negation takes exactly one term".to_string()),
+                        Span::new(open, lexer.pos()),
+                    ));
+                }
+                return Ok(Parser::compound(op, subterms, dt));
+            }
         }
         
-        // Handle compound terms
-        if input.starts_with('(') && input.ends_with(')') {
-            Parser::parse_compound_term(input)
-        } else if input.starts_with('<') && input.contains("-->") && input.ends_with('>') {
-            // Handle inheritance terms like <bird --> flyer>
-            Parser::parse_inheritance_term(input)
-        } else if input.starts_with('&') {
-            // Handle conjunctions starting with &
-            Parser::parse_conjunction_term(input)
-        } else {
-            // Handle atomic terms
-            Parser::parse_atomic_term(input)
+        // Operation `(^op, a, b)`
+        if let (Some(Token::Operator(name)), Some(Token::Comma)) =
+            (lexer.peek()?.map(|s| s.token), lexer.peek_second()?.map(|s| s.token)) {
+            lexer.next_token()?;
+            lexer.next_token()?;
+            let args = Parser::term_list(lexer, Token::RParen, "')'")?;
+            let operator = Term::Atomic(Atomic::new_atom(&name));
+            let product = Parser::compound(Op::Product, args, Dt::Eternal);
+            return Ok(Parser::compound(Op::Inheritance, vec![product, operator], Dt::Eternal));
+        }
+        
+        let head = Parser::term(lexer)?;
+        let after_head = &["')'", COPULA, CONNECTOR, "','"];
+        let spanned = Parser::expect_token(lexer, after_head)?;
+        match spanned.token {
+            Token::RParen => Ok(head),
+            Token::Copula(symbol) => {
+                let (op, dt) = Parser::symbol_dt(lexer, symbol);
+                let predicate = Parser::term(lexer)?;
+                Parser::expect(lexer, Token::RParen, "')'")?;
+                Ok(Parser::compound(op, vec![head, predicate], dt))
+            },
+            Token::Connector(symbol) if symbol != "--" => {
+                let (op, dt) = Parser::symbol_dt(lexer, symbol);
+                let mut subterms = vec![head, Parser::term(lexer)?];
+                let mut gaps = vec![dt];
+                loop {
+                    let next = Parser::expect_token(lexer, &["')'", symbol])?;
+                    match next.token {
+                        Token::RParen => break,
+                        // Every connector of an infix compound must agree on
+                        // the offset, except that a sequence may have uneven gaps
+                        Token::Connector(other) if Parser::symbol_op(other).0 == op => {
+                            let (_, other_dt) = Parser::symbol_dt(lexer, other);
+                            if other_dt != dt && (op != Op::Conjunction || dt.offset().is_none() || other_dt.offset().is_none()) {
+                                return Err(Parser::unexpected(lexer, &next, &["')'", symbol]));
+                            }
+                            gaps.push(other_dt);
+                            subterms.push(Parser::term(lexer)?);
+                        },
+                        _ => return Err(Parser::unexpected(lexer, &next, &["')'", symbol])),
+                    }
+                }
+                if gaps.iter().all(|gap| *gap == dt) {
+                    return Ok(Parser::compound(op, subterms, dt));
+                }
+                let mut sequence = Sequence::of(&subterms[0]);
+                for (gap, subterm) in gaps.iter().zip(&subterms[1..]) {
+                    sequence.then(gap.offset().unwrap_or(0) as i64, subterm);
+                }
+                Ok(sequence.to_term().unwrap_or_else(|| Parser::compound(op, subterms, dt)))
+            },
+            Token::Comma => {
+                let mut subterms = vec![head];
+                subterms.extend(Parser::term_list(lexer, Token::RParen, "')'")?);
+                Ok(Parser::compound(Op::Product, subterms, Dt::Eternal))
+            },
+            _ => Err(Parser::unexpected(lexer, &spanned, after_head)),
         }
     }
     
-    /// Parse an atomic term
-    fn parse_atomic_term(input: &str) -> Result<Term, ParseError> {
-        if input.is_empty() {
-            return Err(ParseError::InvalidTerm("Empty atomic term".to_string()));
+    /// Comma-separated terms up to and including the closing token
+    fn term_list(lexer: &mut Lexer, close: Token, close_name: &'static str) -> Result<Vec<Term>, ParseError> {
+        let mut terms = Vec::new();
+        if lexer.peek()?.map(|s| s.token).as_ref() == Some(&close) {
+            lexer.next_token()?;
+            return Ok(terms);
         }
-        
-        match input.chars().next().unwrap() {
-            '#' => Ok(Term::Variable(Variable::new_dep(&input[1..]))),
-            '$' => Ok(Term::Variable(Variable::new_indep(&input[1..]))),
-            '?' => Ok(Term::Variable(Variable::new_query(&input[1..]))),
-            '@' => Ok(Term::Variable(Variable::new_pattern(&input[1..]))),
-            _ => {
-                // Regular atomic term
-                Ok(Term::Atomic(crate::term::atom::Atomic::new_atom(input)))
+        loop {
+            terms.push(Parser::term(lexer)?);
+            let spanned = Parser::expect_token(lexer, &["','", close_name])?;
+            if spanned.token == close {
+                return Ok(terms);
+            }
+            if spanned.token != Token::Comma {
+                return Err(Parser::unexpected(lexer, &spanned, &["','", close_name]));
             }
         }
     }
     
-    /// Parse an inheritance term like <bird --> flyer>
-    fn parse_inheritance_term(input: &str) -> Result<Term, ParseError> {
-        // Remove outer angle brackets
-        if !input.starts_with('<') || !input.ends_with('>') {
-            return Err(ParseError::InvalidTerm("Invalid inheritance term format".to_string()));
+    /// Parse a statement copula
+    fn copula(lexer: &mut Lexer) -> Result<(Op, Dt), ParseError> {
+        let spanned = Parser::expect_token(lexer, &[COPULA])?;
+        match spanned.token {
+            Token::Copula(symbol) => Ok(Parser::symbol_dt(lexer, symbol)),
+            _ => Err(Parser::unexpected(lexer, &spanned, &[COPULA])),
         }
-        
-        let inner = &input[1..input.len()-1];
-        
-        // Find the arrow
-        let arrow_pos = inner.find("-->").ok_or_else(||
-            ParseError::InvalidTerm("Invalid inheritance term format".to_string()))?;
-        
-        // Extract subterms
-        let left_part = &inner[..arrow_pos].trim();
-        let right_part = &inner[arrow_pos+3..].trim();
-        
-        let left_term = Parser::parse_term(left_part)?;
-        let right_term = Parser::parse_term(right_part)?;
-        
-        Ok(Term::Compound(crate::term::compound::Compound::new(Op::Inheritance, vec![left_term, right_term])))
     }
     
-    /// Parse a conjunction term like &(cat, dog)
-    fn parse_conjunction_term(input: &str) -> Result<Term, ParseError> {
-        // Check if it starts with & and has parentheses
-        if !input.starts_with('&') || !input.contains('(') || !input.ends_with(')') {
-            return Err(ParseError::InvalidTerm("Invalid conjunction term format".to_string()));
+    /// Operator and temporal offset of a symbol, including an explicit
+    /// offset written directly after `==>`, `<=>` or `&&`, as in `(a ==>+5 b)`
+    fn symbol_dt(lexer: &mut Lexer, symbol: &str) -> (Op, Dt) {
+        let (op, dt) = Parser::symbol_op(symbol);
+        match symbol {
+            "==>" | "<=>" | "&&" => (op, lexer.dt_suffix().unwrap_or(dt)),
+            _ => (op, dt),
         }
-        
-        // Find the opening parenthesis
-        let paren_pos = input.find('(').ok_or_else(||
-            ParseError::InvalidTerm("Invalid conjunction term format".to_string()))?;
-        
-        // Extract the inner part
-        let inner = &input[paren_pos+1..input.len()-1];
-        
-        // Split by comma to get subterms
-        let subterm_strs: Vec<&str> = inner.split(',').map(|s| s.trim()).collect();
-        let mut subterms = Vec::new();
-        
-        for subterm_str in subterm_strs {
-            let subterm = Parser::parse_term(subterm_str)?;
-            subterms.push(subterm);
+    }
+    
+    /// Map a copula or connector symbol to its operator and temporal offset
+    fn symbol_op(symbol: &str) -> (Op, Dt) {
+        match symbol {
+            "=/>" => (Op::Implication, Dt::Offset(1)),
+            "=|>" => (Op::Implication, Dt::Offset(0)),
+            "=\\>" => (Op::Implication, Dt::Offset(-1)),
+            "</>" => (Op::Equivalence, Dt::Offset(1)),
+            "<|>" => (Op::Equivalence, Dt::Offset(0)),
+            "&/" => (Op::Conjunction, Dt::Offset(1)),
+            "&|" => (Op::Conjunction, Dt::Offset(0)),
+            _ => match Op::from_symbol(symbol) {
+                Some(op) => (op, Dt::Eternal),
+                None => unreachable!("lexer produced unknown symbol {}", symbol),
+            },
         }
-        
-        Ok(Term::Compound(crate::term::compound::Compound::new(Op::Conjunction, subterms)))
     }
     
-    /// Parse a compound term
-    fn parse_compound_term(input: &str) -> Result<Term, ParseError> {
-        // Remove outer parentheses
-        let inner = &input[1..input.len()-1];
-        
-        // Check if this is an inheritance term like <bird --> flyer>
-        if inner.starts_with('<') && inner.contains("-->") && inner.ends_with('>') {
-            // Find the positions of <, -->, and >
-            let arrow_pos = inner.find("-->").ok_or_else(||
-                ParseError::InvalidTerm("Invalid inheritance term format".to_string()))?;
-            
-            // Extract subterms
-            let left_part = &inner[1..arrow_pos].trim();
-            let right_part = &inner[arrow_pos+3..inner.len()-1].trim();
-            
-            let left_term = Parser::parse_term(left_part)?;
-            let right_term = Parser::parse_term(right_part)?;
-            
-            Ok(Term::Compound(crate::term::compound::Compound::new(Op::Inheritance, vec![left_term, right_term])))
+    fn compound(op: Op, subterms: Vec<Term>, dt: Dt) -> Term {
+        Term::Compound(Compound::new_dt(op, subterms, dt))
+    }
+    
+    fn expect(lexer: &mut Lexer, expected: Token, name: &'static str) -> Result<(), ParseError> {
+        let spanned = Parser::expect_token(lexer, &[name])?;
+        if spanned.token == expected {
+            Ok(())
         } else {
-            // Handle special operators like &/, &|
-            if inner.contains("&/") {
-                // Sequential conjunction
-                let op_pos = inner.find("&/").unwrap();
-                let op = Op::Conjunction;
-                let op_len = 2;
-                
-                // Split into subterms
-                let left_part = &inner[..op_pos].trim();
-                let right_part = &inner[op_pos+op_len..].trim();
-                
-                let left_term = Parser::parse_term(left_part)?;
-                let right_term = Parser::parse_term(right_part)?;
-                
-                // Create a temporal compound with dt=1 for sequential
-                Ok(Term::Compound(crate::term::compound::Compound::new_temporal(op, vec![left_term, right_term], 1)))
-            } else if inner.contains("&|") {
-                // Parallel conjunction
-                let op_pos = inner.find("&|").unwrap();
-                let op = Op::Intersection;
-                let op_len = 2;
-                
-                // Split into subterms
-                let left_part = &inner[..op_pos].trim();
-                let right_part = &inner[op_pos+op_len..].trim();
-                
-                let left_term = Parser::parse_term(left_part)?;
-                let right_term = Parser::parse_term(right_part)?;
-                
-                // Create a temporal compound with dt=0 for parallel
-                Ok(Term::Compound(crate::term::compound::Compound::new_temporal(op, vec![left_term, right_term], 0)))
-            } else {
-                // Regular operators
-                let op_pos = inner.find(['&', '|', '=', '-', '>', '<']).ok_or_else(||
-                    ParseError::InvalidTerm("No operator found in compound term".to_string()))?;
-                
-                // Extract operator
-                let op_str = &inner[op_pos..op_pos+1];
-                let op = match op_str {
-                    "&" => Op::Conjunction,
-                    "|" => Op::Disjunction,
-                    "=" => Op::Similarity,
-                    "-" => Op::Difference,
-                    ">" => Op::Inheritance,
-                    "<" => Op::Inheritance, // This would need more context to distinguish
-                    _ => return Err(ParseError::InvalidTerm(format!("Unknown operator: {}", op_str))),
-                };
-                let op_len = 1;
-                
-                // Split into subterms
-                let left_part = &inner[..op_pos].trim();
-                let right_part = &inner[op_pos+op_len..].trim();
-                
-                let left_term = Parser::parse_term(left_part)?;
-                let right_term = Parser::parse_term(right_part)?;
-                
-                Ok(Term::Compound(crate::term::compound::Compound::new(op, vec![left_term, right_term])))
-            }
+            Err(Parser::unexpected(lexer, &spanned, &[name]))
         }
     }
     
-    /// Parse a truth value from a string
+    fn expect_token(lexer: &mut Lexer, expected: &[&'static str]) -> Result<Spanned, ParseError> {
+        lexer.next_token()?.ok_or_else(|| {
+            ParseError::new(ParseErrorKind::UnexpectedEndOfInput, Span::point(lexer.input().len()))
+                .expecting(expected)
+        })
+    }
+    
+    fn unexpected(lexer: &Lexer, spanned: &Spanned, expected: &[&'static str]) -> ParseError {
+        let text = &lexer.input()[spanned.start..spanned.end];
+        ParseError::new(ParseErrorKind::UnexpectedToken(text.to_string()), Span::new(spanned.start, spanned.end))
+            .expecting(expected)
+    }
+    
+    /// Parse a leading `$p$`, `$p;d$` or `$p;d;q$` budget; components that
+    /// are left out take their default values
+    fn parse_budget(input: &str) -> Result<(Option<Budget>, &str), ParseError> {
+        // `$x` is an independent variable, so only a `$...$` pair holding
+        // nothing but numbers counts as a budget
+        let body = match input.strip_prefix('$').and_then(|rest| rest.find('$').map(|end| &rest[..end])) {
+            Some(body) if !body.trim().is_empty()
+                && body.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ';' || c.is_whitespace()) => body,
+            _ => return Ok((None, input)),
+        };
+        
+        let values = Parser::parse_components(body, 1, 3, ParseErrorKind::InvalidBudget)?;
+        let default = Budget::default();
+        let budget = Budget::new(
+            values[0],
+            values.get(1).copied().unwrap_or(default.durability()),
+            values.get(2).copied().unwrap_or(default.quality()),
+        );
+        Ok((Some(budget), input[body.len() + 2..].trim_start()))
+    }
+    
+    /// Parse a `{f;c}` truth value written before the punctuation
     fn parse_truth_value(input: &str) -> Result<(Option<Truth>, &str), ParseError> {
         if input.starts_with('{') {
             // Find the end of the truth value
-            let end_pos = input.find('}').ok_or_else(|| 
-                ParseError::InvalidTruth("Unterminated truth value".to_string()))?;
+            let end_pos = input.find('}').ok_or_else(|| ParseError::new(
+                ParseErrorKind::InvalidTruth("Unterminated truth value".to_string()),
+                Span::new(0, input.len()),
+            ).expecting(&["'}'"]))?;
             
-            let truth_str = &input[1..end_pos];
-            let parts: Vec<&str> = truth_str.split(';').collect();
-            if parts.len() != 2 {
-                return Err(ParseError::InvalidTruth("Truth value must have frequency and confidence".to_string()));
+            let values = Parser::parse_components(&input[1..end_pos], 1, 2, ParseErrorKind::InvalidTruth)?;
+            if values.len() != 2 {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidTruth("Truth value must have frequency and confidence".to_string()),
+                    Span::new(0, end_pos + 1),
+                ));
             }
             
-            let frequency = f32::from_str(parts[0].trim()).map_err(|_| 
-                ParseError::InvalidTruth("Invalid frequency value".to_string()))?;
-            let confidence = f32::from_str(parts[1].trim()).map_err(|_| 
-                ParseError::InvalidTruth("Invalid confidence value".to_string()))?;
-            
-            let truth = Truth::new(frequency, confidence);
+            let truth = Truth::new(values[0], values[1]);
             Ok((Some(truth), &input[end_pos+1..]))
         } else {
             // No truth value
@@ -320,11 +416,70 @@ impl Parser {
         }
     }
     
+    /// Parse a `%f%` or `%f;c%` truth value written after the punctuation
+    /// and tense; a missing confidence takes the default belief confidence
+    fn parse_percent_truth(input: &str) -> Result<Option<Truth>, ParseError> {
+        let Some(rest) = input.strip_prefix('%') else {
+            return Ok(None);
+        };
+        let end_pos = rest.find('%').ok_or_else(|| ParseError::new(
+            ParseErrorKind::InvalidTruth("Unterminated truth value".to_string()),
+            Span::new(0, input.len()),
+        ).expecting(&["'%'"]))?;
+        
+        let values = Parser::parse_components(&rest[..end_pos], 1, 2, ParseErrorKind::InvalidTruth)?;
+        let confidence = values.get(1).copied().unwrap_or(Truth::default_belief().confidence());
+        
+        let trailing = &rest[end_pos + 1..];
+        if !trailing.trim().is_empty() {
+            let start = input.len() - trailing.trim_start().len();
+            return Err(ParseError::new(
+                ParseErrorKind::UnexpectedToken(trailing.trim().to_string()),
+                Span::new(start, input.trim_end().len()),
+            ));
+        }
+        
+        Ok(Some(Truth::new(values[0], confidence)))
+    }
+    
+    /// Parse the `;`-separated values between truth or budget delimiters;
+    /// `body` starts one byte into the caller's input, after the delimiter
+    fn parse_components(
+        body: &str,
+        min: usize,
+        max: usize,
+        invalid: fn(String) -> ParseErrorKind,
+    ) -> Result<Vec<f32>, ParseError> {
+        let parts: Vec<&str> = body.split(';').collect();
+        if parts.len() < min || parts.len() > max {
+            return Err(ParseError::new(
+                invalid(format!("Expected {} to {} values, found {}", min, max, parts.len())),
+                Span::new(1, 1 + body.len()),
+            ));
+        }
+        
+        let mut values = Vec::with_capacity(parts.len());
+        let mut start = 1;
+        for part in parts {
+            let span = Span::new(start, start + part.len());
+            let value = f32::from_str(part.trim())
+                .map_err(|_| ParseError::new(invalid(format!("Invalid number '{}'", part.trim())), span))?;
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ParseError::new(invalid(format!("{} is outside [0, 1]", value)), span));
+            }
+            values.push(value);
+            start += part.len() + 1;
+        }
+        Ok(values)
+    }
+    
     /// Parse punctuation from a string
     fn parse_punctuation(input: &str) -> Result<(Punctuation, &str), ParseError> {
+        let leading = input.len() - input.trim_start().len();
         let input = input.trim_start();
         if input.is_empty() {
-            return Err(ParseError::UnexpectedEndOfInput);
+            return Err(ParseError::new(ParseErrorKind::UnexpectedEndOfInput, Span::point(leading))
+                .expecting(&[PUNCTUATION]));
         }
         
         let punctuation_char = input.chars().next().unwrap();
@@ -334,55 +489,48 @@ impl Parser {
             '?' => Punctuation::Question,
             '@' => Punctuation::Quest,
             ';' => Punctuation::Command,
-            _ => return Err(ParseError::InvalidPunctuation(punctuation_char)),
+            _ => return Err(ParseError::new(
+                ParseErrorKind::InvalidPunctuation(punctuation_char),
+                Span::new(leading, leading + punctuation_char.len_utf8()),
+            ).expecting(&[PUNCTUATION])),
         };
         
         Ok((punctuation, &input[1..]))
     }
     
     /// Parse time specification from a string
-    fn parse_time(input: &str) -> Result<Option<Time>, ParseError> {
+    fn parse_time(input: &str) -> Result<Option<Tense>, ParseError> {
+        let leading = input.len() - input.trim_start().len();
         let input = input.trim();
         if input.is_empty() {
             return Ok(None);
         }
+        let invalid = |msg: &str| ParseError::new(
+            ParseErrorKind::InvalidTime(msg.to_string()),
+            Span::new(leading, leading + input.len()),
+        );
         
         // Handle various temporal specifications
-        if input == ":|:" || input == ":/:" {
-            // Present moment
-            Ok(Some(Time::Tense(0)))
-        } else if input.starts_with(":\\") && input.ends_with(':') {
-            // Eternal (:\:)
-            let inner = &input[2..input.len()-1];
-            // For eternal, the inner part should be just a backslash
-            if inner == "\\" {
-                Ok(Some(Time::Eternal))
-            } else {
-                Err(ParseError::InvalidTime("Invalid eternal time specification".to_string()))
-            }
-        } else if input.starts_with(":") && input.ends_with(":") {
-            // Future/past with offset
+        match input {
+            ":|:" => return Ok(Some(Tense::Present)),
+            ":/:" => return Ok(Some(Tense::Future)),
+            ":\\:" => return Ok(Some(Tense::Past)),
+            _ => {},
+        }
+        if input.starts_with(':') && input.ends_with(':') {
+            // Offset from the current time
             let time_str = &input[1..input.len()-1];
             if time_str.is_empty() {
-                // Present moment
-                Ok(Some(Time::Tense(0)))
+                Ok(Some(Tense::Present))
             } else {
-                // Handle + and - signs
-                let time_val = if time_str.starts_with('+') || time_str.starts_with('-') {
-                    i64::from_str(time_str).map_err(|_|
-                        ParseError::InvalidTime("Invalid time value".to_string()))?
-                } else {
-                    // Default to positive if no sign
-                    i64::from_str(time_str).map_err(|_|
-                        ParseError::InvalidTime("Invalid time value".to_string()))?
-                };
-                Ok(Some(Time::Tense(time_val)))
+                // Handles explicit + and - signs as well as unsigned offsets
+                let time_val = i64::from_str(time_str).map_err(|_| invalid("Invalid time value"))?;
+                Ok(Some(Tense::Offset(time_val)))
             }
-                } else if let Some(time_str) = input.strip_prefix(':') {
-                    // Temporal with offset
-                    let time_val = i64::from_str(time_str).map_err(|_|
-                        ParseError::InvalidTime("Invalid time value".to_string()))?;
-                    Ok(Some(Time::Tense(time_val)))
+        } else if let Some(time_str) = input.strip_prefix(':') {
+            // Offset without the closing colon
+            let time_val = i64::from_str(time_str).map_err(|_| invalid("Invalid time value"))?;
+            Ok(Some(Tense::Offset(time_val)))
         } else {
             Ok(None)
         }
@@ -451,7 +599,8 @@ mod tests {
         assert!(result.is_err());
         
         let error = result.unwrap_err();
-        assert_eq!(error, ParseError::InvalidPunctuation('%'));
+        assert_eq!(error.kind(), &ParseErrorKind::InvalidPunctuation('%'));
+        assert_eq!(error.span(), Span::new(3, 4));
     }
     
     #[test]
@@ -472,56 +621,304 @@ mod tests {
         let result = Parser::parse_sentence("event. :|:");
         assert!(result.is_ok());
         let (_, _, _, time) = result.unwrap();
-        assert_eq!(time, Some(Time::Tense(0)));
+        assert_eq!(time, Some(Tense::Present));
         
         // Test future moment
         let result = Parser::parse_sentence("event. :+5:");
         assert!(result.is_ok());
         let (_, _, _, time) = result.unwrap();
-        assert_eq!(time, Some(Time::Tense(5)));
+        assert_eq!(time, Some(Tense::Offset(5)));
         
         // Test past moment
         let result = Parser::parse_sentence(r"event. :-3:");
         assert!(result.is_ok());
         let (_, _, _, time) = result.unwrap();
-        assert_eq!(time, Some(Time::Tense(-3)));
-        
-        // Test eternal
-        let input = r"event. :\\:";
-        println!("Parsing input: {}", input);
-        let result = Parser::parse_sentence(input);
-        if let Err(ref e) = result {
-            println!("Error parsing eternal: {:?}", e);
-        }
-        assert!(result.is_ok());
-        let (_, _, _, time) = result.unwrap();
-        assert_eq!(time, Some(Time::Eternal));
+        assert_eq!(time, Some(Tense::Offset(-3)));
+        
+        // Test future and past tense markers
+        let (_, _, _, time) = Parser::parse_sentence("event. :/:").unwrap();
+        assert_eq!(time, Some(Tense::Future));
+        let (_, _, _, time) = Parser::parse_sentence(r"event. :\:").unwrap();
+        assert_eq!(time, Some(Tense::Past));
+        
+        // A sentence without a tense is eternal
+        let (_, _, _, time) = Parser::parse_sentence("event.").unwrap();
+        assert_eq!(time, None);
+        assert!(Parser::parse_sentence(r"event. :\\:").is_err());
+    }
+    
+    #[test]
+    fn test_resolve_tense() {
+        assert_eq!(Tense::Present.resolve(7, 1.0), Time::Tense(7));
+        assert_eq!(Tense::Future.resolve(7, 5.0), Time::Tense(12));
+        assert_eq!(Tense::Past.resolve(7, 5.0), Time::Tense(2));
+        assert_eq!(Tense::Offset(-3).resolve(7, 5.0), Time::Tense(4));
     }
     
     #[test]
     fn test_parse_complex_inheritance_with_temporal() {
-        // Skip this test for now as it requires more complex parsing
-        // that we haven't implemented yet
-        // let result = Parser::parse_sentence("<(&/, bird, swim) --> flyer>. :+2:");
-        // assert!(result.is_ok());
+        let result = Parser::parse_sentence("<(&/, bird, swim) --> flyer>. :+2:");
+        assert!(result.is_ok());
+        
+        let (term, _, punctuation, time) = result.unwrap();
+        assert_eq!(term.op_id(), Op::Inheritance);
+        if let Term::Compound(statement) = &term {
+            let subject = statement.get(0).unwrap();
+            assert_eq!(subject.op_id(), Op::Conjunction);
+            if let Term::Compound(sequence) = subject {
+                assert_eq!(sequence.dt(), Dt::Offset(1));
+                assert_eq!(sequence.len(), 2);
+            }
+        }
+        assert_eq!(punctuation, Punctuation::Belief);
+        assert_eq!(time, Some(Tense::Offset(2)));
     }
     
     #[test]
     fn test_parse_sequential_compound_with_truth() {
-        // Skip this test for now as it requires more complex parsing
-        // that we haven't implemented yet
-        // let result = Parser::parse_sentence("(&/, cat, dog){0.8;0.9}. :|:");
-        // assert!(result.is_ok());
+        let result = Parser::parse_sentence("(&/, cat, dog){0.8;0.9}. :|:");
+        assert!(result.is_ok());
+        
+        let (term, truth, _, time) = result.unwrap();
+        assert_eq!(term.op_id(), Op::Conjunction);
+        assert!((truth.unwrap().frequency() - 0.8).abs() < 0.001);
+        assert_eq!(time, Some(Tense::Present));
     }
     
     #[test]
     fn test_parse_nested_compound_terms() {
-        // Skip this test for now as it requires more complex parsing
-        // that we haven't implemented yet
-        // let result = Parser::parse_sentence("(&(cat, dog), bird).");
-        // assert!(result.is_ok());
+        let term = Parser::parse_term("<(&&, <$x --> bird>, <$x --> fly>) ==> <$x --> animal>>").unwrap();
+        assert_eq!(term.op_id(), Op::Implication);
+        
+        let Term::Compound(implication) = &term else { panic!("Expected a compound term") };
+        let condition = implication.get(0).unwrap();
+        assert_eq!(condition.op_id(), Op::Conjunction);
+        let Term::Compound(conjunction) = condition else { panic!("Expected a compound term") };
+        assert_eq!(conjunction.len(), 2);
+        assert_eq!(conjunction.get(0).unwrap().op_id(), Op::Inheritance);
+        assert_eq!(format!("{}", conjunction.get(1).unwrap()), "($x --> fly)");
+        assert_eq!(format!("{}", implication.get(1).unwrap()), "($x --> animal)");
+    }
+    
+    #[test]
+    fn test_parse_every_operator() {
+        let cases = [
+            ("<a --> b>", Op::Inheritance),
+            ("<a <-> b>", Op::Similarity),
+            ("<a ==> b>", Op::Implication),
+            ("<a <=> b>", Op::Equivalence),
+            ("<a {-- b>", Op::Instance),
+            ("<a --] b>", Op::Property),
+            ("<a {-] b>", Op::InstanceProperty),
+            ("<a =/> b>", Op::Implication),
+            ("<a =|> b>", Op::Implication),
+            ("<a =\\> b>", Op::Implication),
+            ("<a </> b>", Op::Equivalence),
+            ("<a <|> b>", Op::Equivalence),
+            ("(&&, a, b)", Op::Conjunction),
+            ("(||, a, b)", Op::Disjunction),
+            ("(&/, a, b)", Op::Conjunction),
+            ("(&|, a, b)", Op::Conjunction),
+            ("(--, a)", Op::Neg),
+            ("--a", Op::Neg),
+            ("(&, a, b)", Op::IntersectionExt),
+            ("(|, a, b)", Op::Intersection),
+            ("(-, a, b)", Op::DifferenceExt),
+            ("(~, a, b)", Op::Difference),
+            ("(*, a, b)", Op::Product),
+            ("(/, rel, _, b)", Op::ImageExt),
+            ("(\\, rel, _, b)", Op::ImageInt),
+            ("{a, b}", Op::SetExt),
+            ("[a, b]", Op::SetInt),
+            ("(a && b)", Op::Conjunction),
+            ("(a - b)", Op::DifferenceExt),
+            ("(a, b)", Op::Product),
+            ("(^go, a, b)", Op::Inheritance),
+            ("42", Op::Int),
+            ("^go", Op::Atom),
+            ("?who", Op::VarQuery),
+        ];
+        for (input, op) in cases {
+            let term = Parser::parse_term(input).unwrap_or_else(|e| panic!("{}: {}", input, e));
+            assert_eq!(term.op_id(), op, "{}", input);
+        }
+    }
+    
+    #[test]
+    fn test_parse_temporal_copulas_and_connectors() {
+        let dt_of = |input: &str| match Parser::parse_term(input).unwrap() {
+            Term::Compound(c) => c.dt(),
+            _ => panic!("Expected a compound term"),
+        };
+        assert_eq!(dt_of("<a =/> b>"), Dt::Offset(1));
+        assert_eq!(dt_of("<a =|> b>"), Dt::Offset(0));
+        assert_eq!(dt_of("<a =\\> b>"), Dt::Offset(-1));
+        assert_eq!(dt_of("(&|, a, b)"), Dt::Offset(0));
+        assert_eq!(dt_of("<a ==> b>"), Dt::Eternal);
+        assert_eq!(dt_of("<a ==>+- b>"), Dt::Xternal);
+        assert_eq!(dt_of("(&&+-, a, b)"), Dt::Xternal);
+    }
+    
+    #[test]
+    fn test_parse_sets_and_operations() {
+        let term = Parser::parse_term("<{Tweety} --> [yellow, small]>").unwrap();
+        let Term::Compound(statement) = &term else { panic!("Expected a compound term") };
+        assert_eq!(statement.get(0).unwrap().op_id(), Op::SetExt);
+        let Term::Compound(properties) = statement.get(1).unwrap() else { panic!("Expected a set") };
+        assert_eq!(properties.op_id(), Op::SetInt);
+        assert_eq!(properties.len(), 2);
+        
+        let operation = Parser::parse_term("(^go, {SELF}, home)").unwrap();
+        let Term::Compound(operation) = &operation else { panic!("Expected a compound term") };
+        assert_eq!(operation.get(0).unwrap().op_id(), Op::Product);
+        assert_eq!(format!("{}", operation.get(1).unwrap()), "^go");
+        
+        let int_term = Parser::parse_term("(*, 1, 2)").unwrap();
+        let Term::Compound(product) = &int_term else { panic!("Expected a compound term") };
+        assert_eq!(product.get(1).unwrap().op_id(), Op::Int);
+    }
+    
+    #[test]
+    fn test_parse_bare_statement() {
+        assert_eq!(Parser::parse_statement("a --> b").unwrap(), Parser::parse_term("<a --> b>").unwrap());
+        assert_eq!(Parser::parse_statement("(a && b) =/> c").unwrap(), Parser::parse_term("<(a && b) =/> c>").unwrap());
+        assert_eq!(Parser::parse_statement("(a --> b)").unwrap(), Parser::parse_term("(a --> b)").unwrap());
+        assert!(Parser::parse_statement("a --> b --> c").is_err());
+        assert!(Parser::parse_statement("a -->").is_err());
+    }
+    
+    #[test]
+    fn test_parse_term_errors() {
+        assert!(Parser::parse_term("<a --> b").is_err());
+        assert!(Parser::parse_term("(&&, a, b").is_err());
+        assert!(Parser::parse_term("<a b>").is_err());
+        assert!(Parser::parse_term("(a && b || c)").is_err());
+        assert!(Parser::parse_term("(--, a, b)").is_err());
+        
+        let error = Parser::parse_term("<a --> b> c").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedToken("c".to_string()));
+        assert_eq!(error.span(), Span::new(10, 11));
+    }
+    
+    #[test]
+    fn test_parse_error_expected_and_render() {
+        let source = "<a b>.";
+        let error = Parser::parse_sentence(source).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedToken("b".to_string()));
+        assert_eq!(error.expected(), &[COPULA]);
+        assert_eq!(error.render(source), "error: Unexpected 'b', expected copula\n --> 1:4\n  |\n1 | <a b>.\n  |    ^");
+        
+        let error = Parser::parse_sentence("<a --> b").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedEndOfInput);
+        assert_eq!(error.span(), Span::point(8));
+        assert!(error.expected().contains(&"'>'"));
+    }
+    
+    #[test]
+    fn test_parse_sentences_line_col() {
+        let source = "<a --> b>.\n\n  <c --> d>!\n<e --> f>%";
+        let error = Parser::parse_sentences(source).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::InvalidPunctuation('%'));
+        assert_eq!(error.line_col(source), (4, 10));
+        assert!(error.render(source).contains("4 | <e --> f>%\n  |          ^"));
+        
+        let sentences = Parser::parse_sentences("<a --> b>.\n\n  <c --> d>!").unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(sentences[1].2, Punctuation::Goal);
     }
 
+    #[test]
+    fn test_parse_percent_truth() {
+        let (term, truth, punctuation, time) = Parser::parse_sentence("<bird --> swimmer>. %0.10;0.60%").unwrap();
+        assert_eq!(format!("{}", term), "(bird --> swimmer)");
+        assert_eq!(truth, Some(Truth::new(0.1, 0.6)));
+        assert_eq!(punctuation, Punctuation::Belief);
+        assert!(time.is_none());
+        
+        let (_, truth, _, _) = Parser::parse_sentence("<chess --> competition>. %0.90%").unwrap();
+        assert_eq!(truth, Some(Truth::new(0.9, Truth::default_belief().confidence())));
+        
+        let (_, truth, punctuation, time) = Parser::parse_sentence("<(*, a, b) --> like>! :|: %1.00;0.99%").unwrap();
+        assert_eq!(truth, Some(Truth::new(1.0, 0.99)));
+        assert_eq!(punctuation, Punctuation::Goal);
+        assert_eq!(time, Some(Tense::Present));
+        
+        // Braces after a complete term still read as a truth value
+        let (term, truth, _, _) = Parser::parse_sentence("{a, b}{0.5;0.5}.").unwrap();
+        assert_eq!(term.op_id(), Op::SetExt);
+        assert_eq!(truth, Some(Truth::new(0.5, 0.5)));
+    }
+    
+    #[test]
+    fn test_parse_budget() {
+        let (budget, (term, truth, punctuation, _)) =
+            Parser::parse_budgeted_sentence("$0.80;0.50;0.95$ <bird --> animal>. %1.0;0.9%").unwrap();
+        assert_eq!(budget, Some(Budget::new(0.8, 0.5, 0.95)));
+        assert_eq!(format!("{}", term), "(bird --> animal)");
+        assert_eq!(truth, Some(Truth::new(1.0, 0.9)));
+        assert_eq!(punctuation, Punctuation::Belief);
+        
+        // Missing components take the default budget values
+        let (budget, _) = Parser::parse_budgeted_sentence("$0.1;0.9$ cat?").unwrap();
+        assert_eq!(budget, Some(Budget::new(0.1, 0.9, Budget::default().quality())));
+        
+        // A leading independent variable is not a budget
+        let (budget, (term, _, _, _)) = Parser::parse_budgeted_sentence("$x.").unwrap();
+        assert!(budget.is_none());
+        assert_eq!(term.op_id(), Op::VarIndep);
+        
+        let (_, sentence) = Parser::parse_budgeted_sentence("<$x --> bird>?").unwrap();
+        assert_eq!(sentence, Parser::parse_sentence("<$x --> bird>?").unwrap());
+    }
+    
+    #[test]
+    fn test_parse_truth_and_budget_errors() {
+        let error = Parser::parse_sentence("cat. %0.5;0.9").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::InvalidTruth("Unterminated truth value".to_string()));
+        assert_eq!(error.expected(), &["'%'"]);
+        
+        let error = Parser::parse_sentence("cat. %0.5;abc%").unwrap_err();
+        assert!(matches!(error.kind(), ParseErrorKind::InvalidTruth(_)));
+        assert_eq!(error.span(), Span::new(10, 13));
+        
+        let error = Parser::parse_sentence("cat. %1.5%").unwrap_err();
+        assert!(matches!(error.kind(), ParseErrorKind::InvalidTruth(_)));
+        
+        let error = Parser::parse_sentence("cat. %0.5;0.9% extra").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedToken("extra".to_string()));
+        assert_eq!(error.span(), Span::new(15, 20));
+        
+        let error = Parser::parse_sentence("cat{0.5;0.5}. %0.5;0.9%").unwrap_err();
+        assert!(matches!(error.kind(), ParseErrorKind::InvalidTruth(_)));
+        
+        let error = Parser::parse_budgeted_sentence("  $0.5;0.5;0.5;0.5$ cat.").unwrap_err();
+        assert!(matches!(error.kind(), ParseErrorKind::InvalidBudget(_)));
+        assert_eq!(error.span(), Span::new(3, 18));
+    }
+    
+    #[test]
+    fn test_parse_explicit_offsets_and_literals() {
+        let Term::Compound(implication) = Parser::parse_term("(a ==>-4 b)").unwrap() else { panic!("Expected a compound term") };
+        assert_eq!((implication.op_id(), implication.dt()), (Op::Implication, Dt::Offset(-4)));
+        
+        let Term::Compound(sequence) = Parser::parse_term("(a &&+2 b &&+2 c)").unwrap() else { panic!("Expected a compound term") };
+        assert_eq!((sequence.len(), sequence.dt()), (3, Dt::Offset(2)));
+        
+        // Uneven gaps make a nested sequence
+        assert_eq!(Parser::parse_term("(a &&+2 b &&+3 c)").unwrap(), Parser::parse_term("((a &&+2 b) &&+3 c)").unwrap());
+        assert_eq!(Parser::parse_term("(a &/ b &&+3 c)").unwrap().events()[2].0, 4);
+        assert!(Parser::parse_term("(a &&+2 b &&+- c)").is_err());
+        assert!(Parser::parse_term("(a && b || c)").is_err());
+        
+        assert_eq!(Parser::parse_term("-7").unwrap(), Term::Atomic(Atomic::new_int(-7)));
+        assert_eq!(Parser::parse_term("false").unwrap(), Term::Atomic(Atomic::new_bool(false)));
+        assert_eq!(Parser::parse_term("null").unwrap(), Term::Atomic(Atomic::new_null()));
+        assert_eq!(Parser::parse_term("_").unwrap(), Term::Atomic(Atomic::new_placeholder()));
+        assert_eq!(Parser::parse_term("\"true\"").unwrap(), Term::Atomic(Atomic::new_atom("true")));
+        assert_eq!(Parser::parse_term("\"a b\"").unwrap(), Term::Atomic(Atomic::new_atom("a b")));
+        assert!(Parser::parse_term("- 7").is_err());
+    }
+    
     #[test]
     fn test_parse_variable_term() {
         let result = Parser::parse_sentence("#x?");
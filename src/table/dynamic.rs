@@ -0,0 +1,309 @@
+//! Beliefs and goals computed from the concepts of their components
+//!
+//! Some compound terms have a truth value that follows from the truth
+//! values of their components, so it can be computed when asked for rather
+//! than waiting for inference to derive it:
+//!
+//! - `(&&, a, b)`, eternal or simultaneous, from `a` and `b` by conjunction
+//! - `<m --> (&, a, b)>` and `<(|, a, b) --> m>` from the statements about
+//!   each member by intersection, `<m --> (|, a, b)>` and
+//!   `<(&, a, b) --> m>` by union
+//! - `<m --> (-, a, b)>` and `<(~, a, b) --> m>` from the statements about
+//!   the two members by difference
+//! - image statements, which say the same as their product statement
+//!
+//! The evidence of the result is the union of the components' evidence;
+//! components sharing evidence give no result, as in revision. Results are
+//! cached until the clock moves or the caller invalidates them, all at once
+//! or those computed from a changed component.
+
+use crate::task::stamp::Stamp;
+use crate::task::{Budget, Punctuation, Task, Time};
+use crate::term::compound::Compound;
+use crate::term::image;
+use crate::term::temporal::Dt;
+use crate::term::{Op, Term, TermTrait};
+use crate::truth::{Truth, TruthConfig};
+use std::collections::HashMap;
+
+/// How a compound's truth value follows from its components'
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicTruth {
+    Conjunction,
+    Intersection,
+    Union,
+    Difference,
+    /// The single component says the same as the compound
+    Image,
+}
+
+impl DynamicTruth {
+    /// Combine the truth values of two components
+    pub fn combine(self, a: &Truth, b: &Truth, config: &TruthConfig) -> Truth {
+        match self {
+            DynamicTruth::Conjunction => Truth::conjunction(a, b, config),
+            DynamicTruth::Intersection => Truth::intersection(a, b, config),
+            DynamicTruth::Union => Truth::union(a, b, config),
+            DynamicTruth::Difference => Truth::difference(a, b, config),
+            DynamicTruth::Image => *a,
+        }
+    }
+}
+
+/// Get how a term's truth value follows from its components, and the
+/// components, if it does
+pub fn decompose(term: &Term) -> Option<(DynamicTruth, Vec<Term>)> {
+    let Term::Compound(compound) = term else {
+        return None;
+    };
+    match compound.op_id() {
+        Op::Conjunction if matches!(compound.dt(), Dt::Eternal | Dt::Offset(0)) && compound.len() > 1 => {
+            Some((DynamicTruth::Conjunction, compound.subterms().to_vec()))
+        },
+        Op::Inheritance if !compound.is_temporal() && compound.len() == 2 => {
+            if let Some(product) = image::to_product(term) {
+                return Some((DynamicTruth::Image, vec![product]));
+            }
+            let (subject, predicate) = (&compound.subterms()[0], &compound.subterms()[1]);
+            match (subject, predicate) {
+                (_, Term::Compound(members)) if members.len() > 1 => {
+                    let model = match members.op_id() {
+                        Op::IntersectionExt => DynamicTruth::Intersection,
+                        Op::Intersection => DynamicTruth::Union,
+                        Op::DifferenceExt => DynamicTruth::Difference,
+                        _ => return subject_model(subject, predicate),
+                    };
+                    Some((model, members.subterms().iter().map(|member| inheritance(subject, member)).collect()))
+                },
+                _ => subject_model(subject, predicate),
+            }
+        },
+        _ => None,
+    }
+}
+
+/// The model of an inheritance whose subject is made of members
+fn subject_model(subject: &Term, predicate: &Term) -> Option<(DynamicTruth, Vec<Term>)> {
+    let Term::Compound(members) = subject else {
+        return None;
+    };
+    let model = match members.op_id() {
+        Op::Intersection => DynamicTruth::Intersection,
+        Op::IntersectionExt => DynamicTruth::Union,
+        Op::Difference => DynamicTruth::Difference,
+        _ => return None,
+    };
+    if members.len() < 2 {
+        return None;
+    }
+    Some((model, members.subterms().iter().map(|member| inheritance(member, predicate)).collect()))
+}
+
+fn inheritance(subject: &Term, predicate: &Term) -> Term {
+    Term::Compound(Compound::new(Op::Inheritance, vec![subject.clone(), predicate.clone()]))
+}
+
+/// Check if a term's dynamic truth is computed from `component`, directly
+/// or through nested compounds, or is the component itself
+pub fn depends_on(term: &Term, component: &Term) -> bool {
+    term == component
+        || decompose(term).is_some_and(|(_, components)| components.iter().any(|c| depends_on(c, component)))
+}
+
+/// What is held about a term for a time range: its truth value and the
+/// evidence behind it
+pub type Stored<'a> = &'a dyn Fn(&Term, (i64, i64)) -> Option<(Truth, Stamp)>;
+
+/// Compute the truth value of a term for a time range from its components,
+/// and the evidence behind it; components nothing is `stored` for are
+/// computed in turn
+pub fn evaluate(term: &Term, range: (i64, i64), config: &TruthConfig, stored: Stored) -> Option<(Truth, Stamp)> {
+    let (model, components) = decompose(term)?;
+    let mut result: Option<(Truth, Stamp)> = None;
+    for component in &components {
+        let (truth, stamp) = stored(component, range).or_else(|| evaluate(component, range, config, stored))?;
+        result = Some(match result {
+            None => (truth, stamp),
+            Some((_, ref pooled)) if pooled.overlaps(&stamp) => return None,
+            Some((pooled_truth, pooled)) => (model.combine(&pooled_truth, &truth, config), Stamp::merge(&pooled, &stamp)),
+        });
+    }
+    result
+}
+
+/// A term, the punctuation of its table and a time range
+type Key = (Term, Punctuation, (i64, i64));
+
+/// Cache of truth values computed from components, for the belief and
+/// goal tables of terms with a dynamic truth
+#[derive(Clone, Debug, Default)]
+pub struct DynamicTable {
+    /// Results by term, punctuation and time range
+    cache: HashMap<Key, Option<(Truth, Stamp)>>,
+
+    /// The time the cached results were computed at
+    now: i64,
+}
+
+impl DynamicTable {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        DynamicTable::default()
+    }
+
+    /// Get the truth value of a belief or goal about a time range computed
+    /// from its components, and the evidence behind it; see `evaluate`
+    pub fn truth(
+        &mut self,
+        term: &Term,
+        punctuation: Punctuation,
+        range: (i64, i64),
+        now: i64,
+        config: &TruthConfig,
+        stored: Stored,
+    ) -> Option<(Truth, Stamp)> {
+        if now != self.now {
+            self.invalidate();
+            self.now = now;
+        }
+        self.cache
+            .entry((term.clone(), punctuation, range))
+            .or_insert_with(|| evaluate(term, range, config, stored))
+            .clone()
+    }
+
+    /// Get a task holding the truth value computed for a term at a time,
+    /// as if it were stored in the term's table
+    pub fn task(
+        &mut self,
+        term: &Term,
+        punctuation: Punctuation,
+        time: Time,
+        now: i64,
+        config: &TruthConfig,
+        stored: Stored,
+    ) -> Option<Task> {
+        let range = match time {
+            Time::Eternal => (now, now),
+            Time::Tense(at) => (at, at),
+        };
+        let (truth, stamp) = self.truth(term, punctuation, range, now, config, stored)?;
+        Some(Task::with_auto_id(term.clone(), Some(truth), punctuation, time, Budget::default(), stamp, now))
+    }
+
+    /// Forget every cached result
+    pub fn invalidate(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Forget the results computed from a component whose beliefs or goals
+    /// changed, keeping the rest
+    pub fn invalidate_component(&mut self, component: &Term) {
+        self.cache.retain(|(term, ..), _| !depends_on(term, component));
+    }
+
+    /// Get the number of cached results
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Check if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    /// Beliefs held about a few terms, with independent evidence
+    fn stored(held: &Term, _: (i64, i64)) -> Option<(Truth, Stamp)> {
+        let known = [
+            ("a", 1.0, 0.9, 1),
+            ("b", 0.5, 0.8, 2),
+            ("<m --> x>", 1.0, 0.9, 3),
+            ("<m --> y>", 0.4, 0.9, 4),
+            ("<(*, a, b) --> r>", 0.7, 0.6, 5),
+        ];
+        known
+            .iter()
+            .find(|(narsese, ..)| term(narsese) == *held)
+            .map(|(_, f, c, id)| (Truth::new(*f, *c), Stamp::input(*id)))
+    }
+
+    #[test]
+    fn test_decompose() {
+        let (model, components) = decompose(&term("<(~, x, y) --> m>")).unwrap();
+        assert_eq!(model, DynamicTruth::Difference);
+        assert_eq!(components, vec![term("<x --> m>"), term("<y --> m>")]);
+        assert_eq!(decompose(&term("<(&, x, y) --> m>")).unwrap().0, DynamicTruth::Union);
+        assert_eq!(decompose(&term("<a --> (/, r, _, b)>")).unwrap().1, vec![term("<(*, a, b) --> r>")]);
+        assert_eq!(decompose(&term("<a --> b>")), None);
+        assert_eq!(decompose(&term("(&/, a, b)")), None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let config = TruthConfig::default();
+        let (truth, stamp) = evaluate(&term("(&&, a, b)"), (0, 0), &config, &stored).unwrap();
+        assert_eq!(truth, Truth::new(0.5, 0.72));
+        assert_eq!(stamp.evidence(), &[1, 2]);
+
+        let (truth, _) = evaluate(&term("<m --> (-, x, y)>"), (0, 0), &config, &stored).unwrap();
+        assert_eq!(truth, Truth::new(0.6, 0.81));
+        let (truth, _) = evaluate(&term("<m --> (|, x, y)>"), (0, 0), &config, &stored).unwrap();
+        assert_eq!(truth, Truth::new(1.0, 0.81));
+        let (truth, stamp) = evaluate(&term("<b --> (/, r, a, _)>"), (0, 0), &config, &stored).unwrap();
+        assert_eq!((truth, stamp), (Truth::new(0.7, 0.6), Stamp::input(5)));
+
+        // Nested compounds are computed from their own components
+        let (truth, stamp) = evaluate(&term("(&&, <m --> (&, x, y)>, a)"), (0, 0), &config, &stored).unwrap();
+        assert_eq!(truth, Truth::new(0.4, 0.729));
+        assert_eq!(stamp.len(), 3);
+
+        // Unknown components and shared evidence give nothing
+        assert_eq!(evaluate(&term("(&&, a, c)"), (0, 0), &config, &stored), None);
+        assert_eq!(evaluate(&term("(&&, a, (&&, a, b))"), (0, 0), &config, &stored), None);
+    }
+
+    #[test]
+    fn test_cached_per_cycle() {
+        let config = TruthConfig::default();
+        let mut table = DynamicTable::new();
+        let conjunction = term("(&&, a, b)");
+        let task = table.task(&conjunction, Punctuation::Belief, Time::Eternal, 0, &config, &stored).unwrap();
+        assert_eq!(task.truth(), Some(&Truth::new(0.5, 0.72)));
+        assert_eq!(task.evidence(), &[1, 2]);
+        assert_eq!(table.len(), 1);
+
+        // The cached result stands until the clock moves
+        let nothing = |_: &Term, _: (i64, i64)| None;
+        assert!(table.truth(&conjunction, Punctuation::Belief, (0, 0), 0, &config, &nothing).is_some());
+        assert!(table.truth(&conjunction, Punctuation::Belief, (0, 0), 1, &config, &nothing).is_none());
+        table.invalidate();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_invalidate_component() {
+        let config = TruthConfig::default();
+        let mut table = DynamicTable::new();
+        for narsese in ["(&&, a, b)", "(&&, <m --> (&, x, y)>, a)", "<m --> (|, x, y)>"] {
+            table.truth(&term(narsese), Punctuation::Belief, (0, 0), 0, &config, &stored);
+        }
+        assert_eq!(table.len(), 3);
+
+        table.invalidate_component(&term("b"));
+        assert_eq!(table.len(), 2);
+
+        // Nested components count too
+        table.invalidate_component(&term("<m --> x>"));
+        assert!(table.is_empty());
+    }
+}
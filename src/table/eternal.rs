@@ -0,0 +1,154 @@
+//! Beliefs and goals that hold at any time
+//!
+//! The table keeps its tasks ranked by confidence, so a full table gives up
+//! the belief backed by the least evidence. A task added about the same
+//! term as one already held is revised with the strongest held task whose
+//! evidence it does not share; the revision is kept too, and handed back
+//! so the reasoner can report it.
+
+use crate::task::Task;
+use crate::truth::TruthConfig;
+
+/// Confidence-ranked table of eternal beliefs or goals
+#[derive(Clone, Debug)]
+pub struct EternalTable {
+    /// Tasks, most confident first
+    tasks: Vec<Task>,
+
+    /// Maximum number of tasks
+    capacity: usize,
+}
+
+impl EternalTable {
+    /// Create a table holding at most `capacity` tasks
+    pub fn new(capacity: usize) -> Self {
+        EternalTable {
+            tasks: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add a task, revising it with the strongest task it shares no
+    /// evidence with; returns the revision, if any. A task already held
+    /// changes nothing
+    pub fn add(&mut self, task: Task, config: &TruthConfig) -> Option<Task> {
+        task.truth()?;
+        if self.holds(&task) {
+            return None;
+        }
+        let revised = self.tasks.iter().find_map(|held| task.revise(held, config));
+        self.insert(task);
+        let revised = revised.filter(|revised| !self.holds(revised))?;
+        self.insert(revised.clone());
+        Some(revised)
+    }
+
+    /// Get a task by ID
+    pub fn get(&self, task_id: u64) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id() == task_id)
+    }
+
+    /// Get the number of tasks
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check if the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Get all tasks, most confident first
+    pub fn tasks(&self) -> Vec<&Task> {
+        self.tasks.iter().collect()
+    }
+
+    /// Get the maximum number of tasks
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the most confident task
+    pub fn strongest(&self) -> Option<&Task> {
+        self.tasks.first()
+    }
+
+    /// Clear the table
+    pub fn clear(&mut self) {
+        self.tasks.clear();
+    }
+
+    /// Check if a task with the same term, truth and evidence is held
+    fn holds(&self, task: &Task) -> bool {
+        self.tasks.iter().any(|held| {
+            held.term() == task.term() && held.truth() == task.truth() && held.stamp() == task.stamp()
+        })
+    }
+
+    /// Insert a task at its rank and drop the weakest past capacity
+    fn insert(&mut self, task: Task) {
+        let rank = confidence(&task);
+        let position = self.tasks.partition_point(|held| confidence(held) >= rank);
+        self.tasks.insert(position, task);
+        self.tasks.truncate(self.capacity);
+    }
+}
+
+impl Default for EternalTable {
+    fn default() -> Self {
+        EternalTable::new(100)
+    }
+}
+
+fn confidence(task: &Task) -> f32 {
+    task.truth().map_or(0.0, |truth| truth.confidence())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::task::{Punctuation, TaskBuilder};
+    use crate::truth::Truth;
+
+    fn belief(term: &str, frequency: f32, confidence: f32) -> Task {
+        TaskBuilder::new()
+            .term(Parser::parse_term(term).unwrap())
+            .truth(Truth::new(frequency, confidence))
+            .punctuation(Punctuation::Belief)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ranked_by_confidence() {
+        let mut table = EternalTable::new(2);
+        let config = TruthConfig::default();
+        assert!(table.add(belief("a", 1.0, 0.5), &config).is_none());
+        table.add(belief("b", 1.0, 0.9), &config);
+        table.add(belief("c", 1.0, 0.7), &config);
+        let confidences: Vec<f32> = table.tasks().iter().map(|task| task.truth().unwrap().confidence()).collect();
+        assert_eq!(confidences, vec![0.9, 0.7]);
+        assert_eq!(table.strongest().unwrap().term(), &Parser::parse_term("b").unwrap());
+    }
+
+    #[test]
+    fn test_revision_on_insert() {
+        let mut table = EternalTable::new(10);
+        let config = TruthConfig::opennars();
+        let first = belief("<bird --> swimmer>", 1.0, 0.9);
+        let second = belief("<bird --> swimmer>", 0.1, 0.6);
+        table.add(first.clone(), &config);
+
+        let revised = table.add(second.clone(), &config).unwrap();
+        assert_eq!(revised.truth(), Some(&Truth::new(0.87, 0.91)));
+        assert_eq!(revised.evidence(), &[first.id(), second.id()]);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.strongest().unwrap().id(), revised.id());
+
+        // Evidence already held is not revised in again
+        assert!(table.add(first.clone(), &config).is_none());
+        assert_eq!(table.len(), 3);
+        assert!(table.add(belief("<bird --> flyer>", 1.0, 0.9), &config).is_none());
+    }
+}
@@ -3,106 +3,136 @@
 //! This module provides implementations for various types of tables
 //! that store beliefs, goals, questions, and other task types in NARS.
 
-use crate::task::Task;
-use crate::term::Term;
-use crate::truth::Truth;
-use std::collections::HashMap;
+pub mod dynamic;
+pub mod eternal;
+pub mod question;
+pub mod temporal;
 
-/// A table for storing belief tasks
+use crate::task::stamp::Stamp;
+use crate::task::{Task, Time};
+use crate::truth::{Truth, TruthConfig};
+use eternal::EternalTable;
+use std::collections::BTreeMap;
+use temporal::TemporalTable;
+
+/// A table for storing belief or goal tasks: eternal ones by confidence,
+/// events by when they occur
 #[derive(Clone, Debug)]
 pub struct BeliefTable {
-    /// Map of tasks indexed by some criteria
-    tasks: HashMap<u64, Task>,
+    /// Tasks that hold at any time
+    eternal: EternalTable,
     
-    /// Maximum capacity for the table
-    capacity: usize,
+    /// Tasks about events, indexed by occurrence interval
+    temporal: TemporalTable,
 }
 
 impl BeliefTable {
     /// Create a new empty belief table
     pub fn new() -> Self {
         BeliefTable {
-            tasks: HashMap::new(),
-            capacity: 100, // Default capacity
+            eternal: EternalTable::default(),
+            temporal: TemporalTable::default(),
         }
     }
     
-    /// Create a belief table with specified capacity
+    /// Create a belief table with specified capacity for each of its
+    /// eternal and temporal parts
     pub fn with_capacity(capacity: usize) -> Self {
         BeliefTable {
-            tasks: HashMap::new(),
-            capacity,
+            eternal: EternalTable::new(capacity),
+            temporal: TemporalTable::new(capacity),
         }
     }
     
-    /// Add a task to the table
-    pub fn add(&mut self, task: Task) {
-        if self.tasks.len() < self.capacity {
-            self.tasks.insert(task.id(), task);
-        } else {
-            // If at capacity, replace the lowest priority task
-            let lowest_priority_id = self.tasks
-                .iter()
-                .min_by(|(_, a), (_, b)| {
-                    a.budget().priority().partial_cmp(&b.budget().priority()).unwrap()
-                })
-                .map(|(id, _)| *id);
-                
-            if let Some(lowest_id) = lowest_priority_id {
-                if task.budget().priority() > self.tasks.get(&lowest_id).unwrap().budget().priority() {
-                    self.tasks.remove(&lowest_id);
-                    self.tasks.insert(task.id(), task);
-                }
-            }
+    /// Add a task to the table; returns the revision an eternal task was
+    /// merged into, if any
+    pub fn add(&mut self, task: Task, config: &TruthConfig) -> Option<Task> {
+        match task.time() {
+            Time::Eternal => self.eternal.add(task, config),
+            Time::Tense(_) => {
+                self.temporal.add(task);
+                None
+            },
         }
     }
     
+    /// Get the eternal tasks
+    pub fn eternal(&self) -> &EternalTable {
+        &self.eternal
+    }
+    
+    /// Get the event tasks
+    pub fn temporal(&self) -> &TemporalTable {
+        &self.temporal
+    }
+    
     /// Get a task by ID
     pub fn get(&self, task_id: u64) -> Option<&Task> {
-        self.tasks.get(&task_id)
+        self.eternal.get(task_id).or_else(|| self.temporal.get(task_id))
     }
     
     /// Get the number of tasks in the table
     pub fn len(&self) -> usize {
-        self.tasks.len()
+        self.eternal.len() + self.temporal.len()
     }
     
     /// Check if the table is empty
     pub fn is_empty(&self) -> bool {
-        self.tasks.is_empty()
+        self.eternal.is_empty() && self.temporal.is_empty()
     }
     
-    /// Get all tasks in the table
+    /// Get all tasks in the table, eternal ones first
     pub fn tasks(&self) -> Vec<&Task> {
-        self.tasks.values().collect()
+        let mut tasks = self.eternal.tasks();
+        tasks.extend(self.temporal.tasks());
+        tasks
     }
     
     /// Get the task with the highest priority
     pub fn highest_priority(&self) -> Option<&Task> {
-        self.tasks.values()
+        self.tasks()
+            .into_iter()
             .max_by(|a, b| a.budget().priority().partial_cmp(&b.budget().priority()).unwrap())
     }
     
     /// Get tasks with priority above a threshold
     pub fn tasks_above_priority(&self, threshold: f32) -> Vec<&Task> {
-        self.tasks
-            .values()
+        self.tasks()
+            .into_iter()
             .filter(|task| task.budget().priority() > threshold)
             .collect()
     }
     
-    /// Get the truth value for a concept at a given time range
-    pub fn truth(&self, _start: i64, _end: i64, _term: &Term) -> Option<Truth> {
-        // In a real implementation, this would calculate the truth value based on
-        // the tasks in the table for the given time range
-        // For now, we'll return the truth of the highest priority task
-        self.highest_priority()
-            .and_then(|task| task.truth().cloned())
+    /// Get the truth value for a time range, seen at `now`: the pooled
+    /// evidence of the eternal beliefs and of the events, projected to the
+    /// range
+    pub fn truth(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<Truth> {
+        self.pooled(start, end, now, config).map(|(truth, _)| truth)
+    }
+    
+    /// Get the truth value for a time range together with the evidence
+    /// pooled into it
+    pub fn pooled(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<(Truth, Stamp)> {
+        integrate(self.projected(start, end, now, config), config)
+    }
+    
+    /// Get the task saying the most about a time range: the most confident
+    /// one once projected there
+    pub fn r#match(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<&Task> {
+        strongest(self.projected(start, end, now, config))
+    }
+    
+    /// Every task with the truth value it gives a time range; eternal ones
+    /// hold throughout
+    fn projected<'a>(&'a self, start: i64, end: i64, now: i64, config: &TruthConfig) -> impl Iterator<Item = (Truth, &'a Task)> + 'a {
+        let eternal = self.eternal.tasks().into_iter().filter_map(|task| Some((*task.truth()?, task)));
+        eternal.chain(self.temporal.projected(start, end, now, config))
     }
     
     /// Clear the table
     pub fn clear(&mut self) {
-        self.tasks.clear();
+        self.eternal.clear();
+        self.temporal.clear();
     }
 }
 
@@ -112,11 +142,35 @@ impl Default for BeliefTable {
     }
 }
 
+/// Pool the evidence of truth values about the same time, strongest first,
+/// skipping any task that shares evidence with those already pooled;
+/// returns the pooled truth value and evidence
+fn integrate<'a>(candidates: impl Iterator<Item = (Truth, &'a Task)>, config: &TruthConfig) -> Option<(Truth, Stamp)> {
+    let mut candidates: Vec<_> = candidates.collect();
+    candidates.sort_by(|(a, _), (b, _)| b.confidence().total_cmp(&a.confidence()));
+    let mut candidates = candidates.into_iter();
+    let (first, task) = candidates.next()?;
+    let mut pooled = (first, task.stamp().clone());
+    for (truth, task) in candidates {
+        if !pooled.1.overlaps(task.stamp()) {
+            pooled = (Truth::revision(&pooled.0, &truth, config), Stamp::merge(&pooled.1, task.stamp()));
+        }
+    }
+    Some(pooled)
+}
+
+/// The task with the most confident truth value
+fn strongest<'a>(candidates: impl Iterator<Item = (Truth, &'a Task)>) -> Option<&'a Task> {
+    candidates
+        .max_by(|(a, _), (b, _)| a.confidence().total_cmp(&b.confidence()))
+        .map(|(_, task)| task)
+}
+
 /// A generic task table for storing various task types
 #[derive(Clone, Debug)]
 pub struct TaskTable {
     /// Map of tasks indexed by ID
-    tasks: HashMap<u64, Task>,
+    tasks: BTreeMap<u64, Task>,
     
     /// Maximum capacity for the table
     capacity: usize,
@@ -126,7 +180,7 @@ impl TaskTable {
     /// Create a new empty task table
     pub fn new() -> Self {
         TaskTable {
-            tasks: HashMap::new(),
+            tasks: BTreeMap::new(),
             capacity: 100, // Default capacity
         }
     }
@@ -134,7 +188,7 @@ impl TaskTable {
     /// Create a task table with specified capacity
     pub fn with_capacity(capacity: usize) -> Self {
         TaskTable {
-            tasks: HashMap::new(),
+            tasks: BTreeMap::new(),
             capacity,
         }
     }
@@ -214,15 +268,14 @@ pub use TaskTable as GenericTaskTable;
 mod tests {
     use super::*;
     use crate::term::Term;
-    use crate::truth::Truth;
-    use crate::task::{Task, TaskBuilder, Punctuation, Budget};
+    use crate::task::{TaskBuilder, Punctuation, Budget};
 
     #[test]
     fn test_belief_table_creation() {
         let table = BeliefTable::new();
         assert_eq!(table.len(), 0);
         assert!(table.is_empty());
-        assert_eq!(table.capacity, 100);
+        assert_eq!(table.eternal().capacity(), 100);
     }
 
     #[test]
@@ -238,7 +291,7 @@ mod tests {
             .build()
             .unwrap();
         
-        table.add(task);
+        table.add(task, &TruthConfig::default());
         assert_eq!(table.len(), 1);
         assert!(!table.is_empty());
     }
@@ -274,16 +327,56 @@ mod tests {
             .build()
             .unwrap();
         
-        table.add(task1);
-        table.add(task2);
-        table.add(task3);
+        table.add(task1, &TruthConfig::default());
+        table.add(task2, &TruthConfig::default());
+        table.add(task3, &TruthConfig::default());
         
         // Table should have 2 tasks (capacity is 2)
         assert_eq!(table.len(), 2);
         
-        // Should have the two highest priority tasks
+        // Should have the two most confident tasks
+        assert!(table.get(3).is_none());
         let highest = table.highest_priority().unwrap();
-        assert_eq!(highest.id(), 3); // task3 has the highest priority
+        assert_eq!(highest.id(), 2); // task2 has the highest priority left
+    }
+
+    #[test]
+    fn test_belief_table_truth_projects() {
+        let mut table = BeliefTable::new();
+        
+        let observation = TaskBuilder::new()
+            .id(1)
+            .term(Term::Atomic(crate::term::atom::Atomic::new_atom("sensor")))
+            .truth(Truth::new(0.8, 0.9))
+            .punctuation(Punctuation::Belief)
+            .time(Time::Tense(100))
+            .build()
+            .unwrap();
+        table.add(observation, &TruthConfig::default());
+        let config = TruthConfig::default();
+        
+        assert_eq!(table.truth(100, 100, 100, &config), Some(Truth::new(0.8, 0.9)));
+        let later = table.truth(150, 150, 150, &config).unwrap();
+        assert_eq!(later.frequency(), 0.8);
+        assert!(later.confidence() < 0.9 / 50.0);
+        assert!(table.truth(200, 200, 200, &config).unwrap().confidence() < later.confidence());
+        assert_eq!(BeliefTable::new().truth(0, 10, 0, &config), None);
+        
+        // An eternal belief holds throughout and pools with the event
+        let eternal = TaskBuilder::new()
+            .id(2)
+            .term(Term::Atomic(crate::term::atom::Atomic::new_atom("sensor")))
+            .truth(Truth::new(0.0, 0.5))
+            .punctuation(Punctuation::Belief)
+            .build()
+            .unwrap();
+        table.add(eternal, &TruthConfig::default());
+        assert_eq!(table.eternal().len(), 1);
+        assert_eq!(table.temporal().len(), 1);
+        assert_eq!(table.r#match(100, 100, 100, &config).unwrap().id(), 1);
+        assert_eq!(table.r#match(150, 150, 150, &config).unwrap().id(), 2);
+        let pooled = table.truth(100, 100, 100, &config).unwrap();
+        assert!(pooled.frequency() < 0.8 && pooled.confidence() > 0.9);
     }
 
     #[test]
@@ -299,7 +392,7 @@ mod tests {
             .build()
             .unwrap();
         
-        table.add(task);
+        table.add(task, &TruthConfig::default());
         
         let retrieved = table.get(1);
         assert!(retrieved.is_some());
@@ -0,0 +1,225 @@
+//! Questions and quests waiting for answers
+//!
+//! A question is answered by beliefs, a quest by goals. A candidate answers
+//! when its term unifies with the question's, binding only the question's
+//! query variables, so `<?x --> swimmer>?` is answered by
+//! `<bird --> swimmer>.` Candidates are judged by the truth value they give
+//! the question's time. Candidates for a yes/no question all share its
+//! term, so they are ranked by confidence, as in OpenNARS; candidates for a
+//! question with query variables are ranked by expectation, discounted by
+//! complexity so that simpler answers win ties.
+//!
+//! Each question keeps the history of its best answers; a candidate is only
+//! reported when it improves on the last one.
+
+use crate::task::{Punctuation, Task};
+use crate::term::unify::{unify, VarTypes};
+use crate::term::{Op, TermTrait};
+use crate::truth::TruthConfig;
+
+/// A question or quest and the best answers it has had
+#[derive(Clone, Debug)]
+pub struct Question {
+    task: Task,
+
+    /// Answers in the order found, each better than the one before
+    answers: Vec<(Task, f32)>,
+}
+
+impl Question {
+    /// Wrap a question or quest with no answers yet
+    pub fn new(task: Task) -> Self {
+        Question { task, answers: Vec::new() }
+    }
+
+    /// Get the question or quest
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    /// Get the answers found so far, oldest and weakest first
+    pub fn answers(&self) -> impl Iterator<Item = &Task> {
+        self.answers.iter().map(|(task, _)| task)
+    }
+
+    /// Get the best answer found so far
+    pub fn best(&self) -> Option<&Task> {
+        self.answers.last().map(|(task, _)| task)
+    }
+
+    /// Offer a candidate answer; returns true if it answers the question
+    /// better than any before and was recorded
+    pub fn offer(&mut self, candidate: &Task, now: i64, config: &TruthConfig) -> bool {
+        let Some(quality) = quality(&self.task, candidate, now, config) else {
+            return false;
+        };
+        if self.answers.last().is_some_and(|(best, best_quality)| best.id() == candidate.id() || *best_quality >= quality) {
+            return false;
+        }
+        self.answers.push((candidate.clone(), quality));
+        true
+    }
+}
+
+/// How well a candidate answers a question, if it answers it at all
+pub fn quality(question: &Task, candidate: &Task, now: i64, config: &TruthConfig) -> Option<f32> {
+    let expected = match question.punctuation() {
+        Punctuation::Question => Punctuation::Belief,
+        Punctuation::Quest => Punctuation::Goal,
+        _ => return None,
+    };
+    if candidate.punctuation() != expected {
+        return None;
+    }
+    unify(question.term(), candidate.term(), VarTypes::QUERY)?;
+    let truth = candidate.truth_at(question.time(), now, config)?;
+    if has_query_variables(question) {
+        Some(truth.expectation() / (candidate.complexity() as f32).sqrt())
+    } else {
+        Some(truth.confidence())
+    }
+}
+
+/// The best of some candidate answers to a question
+pub fn best_answer<'a>(question: &Task, candidates: impl IntoIterator<Item = &'a Task>, now: i64, config: &TruthConfig) -> Option<&'a Task> {
+    candidates
+        .into_iter()
+        .filter_map(|candidate| Some((quality(question, candidate, now, config)?, candidate)))
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Check if a question has query variables, and so may be answered by
+/// beliefs or goals about other terms than its own
+pub fn has_query_variables(question: &Task) -> bool {
+    question.term().dfs().any(|(_, term)| term.op_id() == Op::VarQuery)
+}
+
+/// Questions and quests waiting for answers, oldest dropped first
+#[derive(Clone, Debug)]
+pub struct QuestionTable {
+    questions: Vec<Question>,
+
+    /// Maximum number of questions
+    capacity: usize,
+}
+
+impl QuestionTable {
+    /// Create a table holding at most `capacity` questions
+    pub fn new(capacity: usize) -> Self {
+        QuestionTable {
+            questions: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add a question or quest; returns it to be offered the answers
+    /// already known, or None if it is held already or is not a question
+    pub fn add(&mut self, task: Task) -> Option<&mut Question> {
+        if !task.is_question_like() || self.get(task.id()).is_some() {
+            return None;
+        }
+        if self.questions.len() >= self.capacity {
+            self.questions.remove(0);
+        }
+        self.questions.push(Question::new(task));
+        self.questions.last_mut()
+    }
+
+    /// Offer a new belief or goal to every question; returns the questions
+    /// it answers better than before, with the answer
+    pub fn offer(&mut self, candidate: &Task, now: i64, config: &TruthConfig) -> Vec<(Task, Task)> {
+        self.questions
+            .iter_mut()
+            .filter_map(|question| question.offer(candidate, now, config).then(|| (question.task.clone(), candidate.clone())))
+            .collect()
+    }
+
+    /// Get a question by the id of its task
+    pub fn get(&self, task_id: u64) -> Option<&Question> {
+        self.questions.iter().find(|question| question.task.id() == task_id)
+    }
+
+    /// Get the number of questions
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    /// Check if the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    /// Get all questions, oldest first
+    pub fn questions(&self) -> impl Iterator<Item = &Question> {
+        self.questions.iter()
+    }
+
+    /// Clear the table
+    pub fn clear(&mut self) {
+        self.questions.clear();
+    }
+}
+
+impl Default for QuestionTable {
+    fn default() -> Self {
+        QuestionTable::new(100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::task::TaskBuilder;
+    use crate::truth::Truth;
+
+    fn task(term: &str, punctuation: Punctuation, truth: Option<(f32, f32)>) -> Task {
+        let mut builder = TaskBuilder::new()
+            .term(Parser::parse_term(term).unwrap())
+            .punctuation(punctuation);
+        if let Some((frequency, confidence)) = truth {
+            builder = builder.truth(Truth::new(frequency, confidence));
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_answer_history() {
+        let config = TruthConfig::default();
+        let mut table = QuestionTable::default();
+        let question = task("<bird --> swimmer>", Punctuation::Question, None);
+        assert!(table.add(question.clone()).is_some());
+        assert!(table.add(question.clone()).is_none());
+
+        let weak = task("<bird --> swimmer>", Punctuation::Belief, Some((1.0, 0.5)));
+        let strong = task("<bird --> swimmer>", Punctuation::Belief, Some((0.0, 0.9)));
+        assert_eq!(table.offer(&weak, 0, &config).len(), 1);
+        let answered = table.offer(&strong, 0, &config);
+        assert_eq!(answered[0].0.id(), question.id());
+        assert_eq!(answered[0].1.id(), strong.id());
+
+        // Nothing weaker, and nothing that is not a belief, improves it
+        assert!(table.offer(&weak, 0, &config).is_empty());
+        assert!(table.offer(&task("<bird --> swimmer>", Punctuation::Goal, Some((1.0, 0.95))), 0, &config).is_empty());
+        let history: Vec<u64> = table.get(question.id()).unwrap().answers().map(Task::id).collect();
+        assert_eq!(history, vec![weak.id(), strong.id()]);
+    }
+
+    #[test]
+    fn test_query_variables() {
+        let config = TruthConfig::default();
+        let question = task("<?x --> swimmer>", Punctuation::Question, None);
+        let bird = task("<bird --> swimmer>", Punctuation::Belief, Some((1.0, 0.9)));
+        let fish = task("<(&, fish, animal) --> swimmer>", Punctuation::Belief, Some((1.0, 0.9)));
+        let flyer = task("<bird --> flyer>", Punctuation::Belief, Some((1.0, 0.9)));
+
+        // The simpler of two equally expected answers is the better one
+        assert_eq!(best_answer(&question, [&fish, &bird, &flyer], 0, &config).unwrap().id(), bird.id());
+        assert_eq!(quality(&question, &flyer, 0, &config), None);
+
+        let quest = task("<?x --> swimmer>", Punctuation::Quest, None);
+        let goal = task("<bird --> swimmer>", Punctuation::Goal, Some((1.0, 0.9)));
+        assert_eq!(best_answer(&quest, [&bird, &goal], 0, &config).unwrap().id(), goal.id());
+    }
+}
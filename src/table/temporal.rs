@@ -0,0 +1,322 @@
+//! Beliefs about events, indexed by when they hold
+//!
+//! Every entry covers an interval of time: an observation the moment it
+//! was made at, a merged belief the span of the beliefs it replaced.
+//! Entries are ordered by start, and no entry is longer than the longest
+//! span held, so the entries overlapping a window are found by scanning
+//! from the window's start minus that span. A window is judged by the
+//! entries overlapping it and, projected to it, the nearest on each side.
+//!
+//! A full table does not forget: it merges the two entries closest in
+//! time into one covering both, revising their truth values when their
+//! evidence is independent and keeping the more confident one otherwise.
+
+use crate::task::stamp::Stamp;
+use crate::task::{Budget, Task, Time};
+use crate::truth::{Truth, TruthConfig};
+use std::collections::BTreeMap;
+
+/// A belief and the interval it holds over
+#[derive(Clone, Debug)]
+pub struct Entry {
+    start: i64,
+    end: i64,
+    task: Task,
+}
+
+impl Entry {
+    /// Get the first moment the belief holds at
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// Get the last moment the belief holds at
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// Get the belief
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    /// Get the truth value the belief gives a window: its own within its
+    /// interval, projected from the nearest end otherwise
+    pub fn truth_in(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<Truth> {
+        let truth = self.task.truth()?;
+        let (from, to) = if self.end < start {
+            (self.end, start)
+        } else if self.start > end {
+            (self.start, end)
+        } else {
+            return Some(*truth);
+        };
+        Some(truth.project(from, to, now, config.duration, config))
+    }
+}
+
+/// Time-indexed table of event beliefs or goals
+#[derive(Clone, Debug)]
+pub struct TemporalTable {
+    /// Entries by start, then task id
+    entries: BTreeMap<(i64, u64), Entry>,
+
+    /// Longest interval of any entry held
+    max_span: i64,
+
+    /// Maximum number of entries
+    capacity: usize,
+}
+
+impl TemporalTable {
+    /// Create a table holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        TemporalTable {
+            entries: BTreeMap::new(),
+            max_span: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add an event task at the moment it occurs; eternal tasks belong in
+    /// an eternal table and are ignored
+    pub fn add(&mut self, task: Task) {
+        if let Time::Tense(time) = task.time() {
+            self.insert(time, time, task);
+            self.compress();
+        }
+    }
+
+    /// Get a task by ID
+    pub fn get(&self, task_id: u64) -> Option<&Task> {
+        self.entries.values().map(Entry::task).find(|task| task.id() == task_id)
+    }
+
+    /// Get the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the table is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get all entries in time order
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.values()
+    }
+
+    /// Get all tasks in time order
+    pub fn tasks(&self) -> Vec<&Task> {
+        self.entries.values().map(Entry::task).collect()
+    }
+
+    /// Get the entries whose interval meets a window
+    pub fn overlapping(&self, start: i64, end: i64) -> impl Iterator<Item = &Entry> {
+        let from = start.saturating_sub(self.max_span);
+        self.entries
+            .range((from, u64::MIN)..=(end, u64::MAX))
+            .map(|(_, entry)| entry)
+            .filter(move |entry| entry.end >= start)
+    }
+
+    /// Get the entry ending closest before a window
+    pub fn before(&self, start: i64) -> Option<&Entry> {
+        let mut nearest: Option<&Entry> = None;
+        for entry in self.entries.range(..(start, u64::MIN)).map(|(_, entry)| entry).rev() {
+            // Entries starting further back cannot end any later
+            if nearest.is_some_and(|nearest| entry.start + self.max_span < nearest.end) {
+                break;
+            }
+            if entry.end < start && nearest.is_none_or(|nearest| entry.end > nearest.end) {
+                nearest = Some(entry);
+            }
+        }
+        nearest
+    }
+
+    /// Get the entry starting closest after a window
+    pub fn after(&self, end: i64) -> Option<&Entry> {
+        self.entries.range((end.saturating_add(1), u64::MIN)..).map(|(_, entry)| entry).next()
+    }
+
+    /// Get the tasks in a window and the nearest on either side of it, in
+    /// time order, with the truth value each gives the window
+    pub fn projected<'a>(&'a self, start: i64, end: i64, now: i64, config: &TruthConfig) -> impl Iterator<Item = (Truth, &'a Task)> + 'a {
+        let config = *config;
+        self.before(start)
+            .into_iter()
+            .chain(self.overlapping(start, end))
+            .chain(self.after(end))
+            .filter_map(move |entry| Some((entry.truth_in(start, end, now, &config)?, &entry.task)))
+    }
+
+    /// Get the truth value for a window, pooling the evidence of the
+    /// beliefs in it and projected to it
+    pub fn truth(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<Truth> {
+        super::integrate(self.projected(start, end, now, config), config).map(|(truth, _)| truth)
+    }
+
+    /// Get the task saying the most about a window: the one most confident
+    /// once projected to it
+    pub fn r#match(&self, start: i64, end: i64, now: i64, config: &TruthConfig) -> Option<&Task> {
+        super::strongest(self.projected(start, end, now, config))
+    }
+
+    /// Clear the table
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.max_span = 0;
+    }
+
+    fn insert(&mut self, start: i64, end: i64, task: Task) {
+        self.max_span = self.max_span.max(end - start);
+        self.entries.insert((start, task.id()), Entry { start, end, task });
+    }
+
+    /// Merge the entries closest in time until the table fits its capacity
+    fn compress(&mut self) {
+        while self.entries.len() > self.capacity {
+            let keys: Vec<(i64, u64)> = self.entries.keys().copied().collect();
+            let closest = keys
+                .windows(2)
+                .min_by_key(|pair| pair[1].0 - self.entries[&pair[0]].end)
+                .map(|pair| (pair[0], pair[1]));
+            let Some((first, second)) = closest else {
+                return;
+            };
+            let first = self.entries.remove(&first).unwrap();
+            let second = self.entries.remove(&second).unwrap();
+            let (start, end) = (first.start.min(second.start), first.end.max(second.end));
+            let task = merge(first.task, second.task, start);
+            self.insert(start, end, task);
+        }
+    }
+}
+
+impl Default for TemporalTable {
+    fn default() -> Self {
+        TemporalTable::new(100)
+    }
+}
+
+/// One belief standing for two, occurring at `start`; revision does not
+/// depend on the evidential horizon, so the default settings serve
+fn merge(a: Task, b: Task, start: i64) -> Task {
+    let truth = match (a.truth(), b.truth()) {
+        (Some(x), Some(y)) if !a.stamp().overlaps(b.stamp()) => Some(Truth::revision(x, y, &TruthConfig::default())),
+        (Some(x), Some(y)) if y.confidence() > x.confidence() => Some(*y),
+        (x, y) => x.or(y).copied(),
+    };
+    let budget = Budget::new(
+        a.budget().priority().max(b.budget().priority()),
+        a.budget().durability().max(b.budget().durability()),
+        a.budget().quality().max(b.budget().quality()),
+    );
+    Task::with_auto_id(
+        a.term().clone(),
+        truth,
+        a.punctuation(),
+        Time::Tense(start),
+        budget,
+        Stamp::merge(a.stamp(), b.stamp()),
+        a.creation_time().max(b.creation_time()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Punctuation, TaskBuilder};
+    use crate::term::atom::Atomic;
+    use crate::term::Term;
+
+    fn event(time: i64, frequency: f32) -> Task {
+        TaskBuilder::new()
+            .term(Term::Atomic(Atomic::new_atom("sensor")))
+            .truth(Truth::new(frequency, 0.9))
+            .punctuation(Punctuation::Belief)
+            .time(Time::Tense(time))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let mut table = TemporalTable::new(10);
+        for time in [0, 10, 20, 30] {
+            table.add(event(time, 1.0));
+        }
+        let found: Vec<i64> = table.overlapping(5, 20).map(Entry::start).collect();
+        assert_eq!(found, vec![10, 20]);
+        assert_eq!(table.overlapping(11, 19).count(), 0);
+
+        table.add(event(100, 1.0));
+        assert!(table.tasks().iter().all(|task| !task.is_eternal()));
+        assert_eq!(table.len(), 5);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let config = TruthConfig::default();
+        let mut table = TemporalTable::new(10);
+        for (time, frequency) in [(0, 1.0), (10, 0.0), (20, 0.0), (30, 1.0)] {
+            table.add(event(time, frequency));
+        }
+        assert_eq!(table.before(15).map(Entry::start), Some(10));
+        assert_eq!(table.after(15).map(Entry::start), Some(20));
+        assert!(table.before(0).is_none() && table.after(30).is_none());
+
+        // Only the neighbours of an empty window speak for it
+        assert_eq!(table.projected(15, 15, 15, &config).count(), 2);
+        assert!(table.truth(15, 15, 15, &config).unwrap().frequency() < 0.0001);
+
+        // A long entry starting earlier can still end nearest
+        table.insert(-5, 12, event(-5, 1.0));
+        assert_eq!(table.before(13).map(Entry::end), Some(12));
+    }
+
+    #[test]
+    fn test_truth_and_match() {
+        let config = TruthConfig::default();
+        let mut table = TemporalTable::new(10);
+        table.add(event(0, 1.0));
+        table.add(event(10, 0.0));
+
+        // Inside the window a belief counts in full
+        let at_ten = table.truth(10, 10, 10, &config).unwrap();
+        assert!(at_ten.frequency() < 0.1);
+        assert!(at_ten.confidence() > 0.9);
+        assert_eq!(table.r#match(9, 12, 10, &config).unwrap().time(), Time::Tense(10));
+        assert_eq!(table.r#match(-5, -5, 10, &config).unwrap().time(), Time::Tense(0));
+
+        // Halfway both are projected equally far
+        let halfway = table.truth(5, 5, 10, &config).unwrap();
+        assert!((halfway.frequency() - 0.5).abs() < 0.0001);
+        assert!(halfway.confidence() < 0.9);
+        assert_eq!(TemporalTable::new(10).truth(0, 0, 0, &config), None);
+    }
+
+    #[test]
+    fn test_capacity_merges() {
+        let mut table = TemporalTable::new(2);
+        table.add(event(0, 1.0));
+        table.add(event(20, 1.0));
+        table.add(event(21, 0.0));
+        assert_eq!(table.len(), 2);
+
+        // The two closest observations became one covering both
+        let entries: Vec<&Entry> = table.entries().collect();
+        assert_eq!((entries[0].start(), entries[0].end()), (0, 0));
+        assert_eq!((entries[1].start(), entries[1].end()), (20, 21));
+        let merged = entries[1].task();
+        assert_eq!(merged.evidence().len(), 2);
+        assert!((merged.truth().unwrap().frequency() - 0.5).abs() < 0.0001);
+        assert!(merged.truth().unwrap().confidence() > 0.9);
+
+        // A window inside the merged span finds it
+        assert_eq!(table.overlapping(21, 21).count(), 1);
+        assert_eq!(table.r#match(21, 21, 21, &TruthConfig::default()).unwrap().id(), merged.id());
+    }
+}
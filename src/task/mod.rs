@@ -8,8 +8,11 @@
 //! - A timestamp (when the task is relevant)
 //! - A budget (priority and durability)
 
+pub mod stamp;
+
 use crate::term::{Term, TermTrait};
-use crate::truth::Truth;
+use crate::truth::{Truth, TruthConfig};
+use stamp::Stamp;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -105,7 +108,8 @@ impl Default for Budget {
     }
 }
 
-/// Global counter for generating unique task IDs
+/// Global counter for generating unique task IDs; the one source of the
+/// IDs of every task, and so of the evidence of input tasks
 static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
 
 /// Task struct representing a NARS task
@@ -129,15 +133,17 @@ pub struct Task {
     /// Unique identifier for the task
     id: u64,
     
-    /// Evidence trail (stamp) - list of task IDs that led to this task
-    evidence: Vec<u64>,
+    /// Input tasks the evidence comes from
+    stamp: Stamp,
     
     /// Creation time
     creation_time: i64,
 }
 
 impl Task {
-    /// Create a new task
+    /// Create a new task; a task without evidence is an input task, its
+    /// own evidence
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         term: Term,
         truth: Option<Truth>,
@@ -145,9 +151,10 @@ impl Task {
         time: Time,
         budget: Budget,
         id: u64,
-        evidence: Vec<u64>,
+        evidence: impl Into<Stamp>,
         creation_time: i64,
     ) -> Self {
+        let stamp = evidence.into();
         Task {
             term,
             truth,
@@ -155,7 +162,7 @@ impl Task {
             time,
             budget,
             id,
-            evidence,
+            stamp: if stamp.is_empty() { Stamp::input(id) } else { stamp },
             creation_time,
         }
     }
@@ -167,7 +174,7 @@ impl Task {
         punctuation: Punctuation,
         time: Time,
         budget: Budget,
-        evidence: Vec<u64>,
+        evidence: impl Into<Stamp>,
         creation_time: i64,
     ) -> Self {
         let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
@@ -184,6 +191,18 @@ impl Task {
         self.truth.as_ref()
     }
     
+    /// Get the truth value as it applies at another time, seen at `now`:
+    /// an event is projected to another time and eternalized for an eternal
+    /// one; an eternal task holds at any time
+    pub fn truth_at(&self, time: Time, now: i64, config: &TruthConfig) -> Option<Truth> {
+        let truth = self.truth.as_ref()?;
+        Some(match (self.time, time) {
+            (Time::Tense(from), Time::Tense(to)) => truth.project(from, to, now, config.duration, config),
+            (Time::Tense(_), Time::Eternal) => truth.eternalize(config),
+            (Time::Eternal, _) => *truth,
+        })
+    }
+    
     /// Get the punctuation
     pub fn punctuation(&self) -> Punctuation {
         self.punctuation
@@ -204,9 +223,14 @@ impl Task {
         self.id
     }
     
-    /// Get the evidence trail
+    /// Get the ids of the input tasks behind this one
     pub fn evidence(&self) -> &[u64] {
-        &self.evidence
+        self.stamp.evidence()
+    }
+    
+    /// Get the stamp
+    pub fn stamp(&self) -> &Stamp {
+        &self.stamp
     }
     
     /// Get the creation time
@@ -216,45 +240,54 @@ impl Task {
     
     /// Check if this is an input task (has minimal evidence)
     pub fn is_input(&self) -> bool {
-        self.evidence.len() <= 1
+        self.stamp.len() <= 1
     }
     
     /// Add evidence to this task
     pub fn add_evidence(&mut self, evidence_id: u64) {
-        if !self.evidence.contains(&evidence_id) {
-            self.evidence.push(evidence_id);
-        }
+        self.stamp = Stamp::merge(&self.stamp, &Stamp::input(evidence_id));
     }
     
-    /// Create a derived task from this task and another task
+    /// Create a derived task from this task and another task, drawing on
+    /// the evidence of both
     pub fn derive_from(&self, other: &Task, new_term: Term, new_truth: Option<Truth>,
                       new_punctuation: Punctuation, new_time: Time, new_budget: Budget) -> Task {
-        let mut evidence = self.evidence.clone();
-        // Add evidence from the other task
-        for &id in other.evidence.iter() {
-            if !evidence.contains(&id) {
-                evidence.push(id);
-            }
-        }
-        // Add the tasks themselves as evidence
-        if !evidence.contains(&self.id) {
-            evidence.push(self.id);
-        }
-        if !evidence.contains(&other.id) {
-            evidence.push(other.id);
-        }
-        
         Task::with_auto_id(
             new_term,
             new_truth,
             new_punctuation,
             new_time,
             new_budget,
-            evidence,
+            Stamp::merge(&self.stamp, &other.stamp),
             self.creation_time.max(other.creation_time),
         )
     }
     
+    /// Revise this belief or goal with another one about the same term at
+    /// the same time; nothing if their evidence overlaps, since it would be
+    /// counted twice
+    pub fn revise(&self, other: &Task, config: &TruthConfig) -> Option<Task> {
+        if self.term != other.term || self.punctuation != other.punctuation || self.time != other.time
+            || self.stamp.overlaps(&other.stamp) {
+            return None;
+        }
+        let truth = Truth::revision(self.truth.as_ref()?, other.truth.as_ref()?, config);
+        let budget = Budget::new(
+            self.budget.priority().max(other.budget.priority()),
+            self.budget.durability().max(other.budget.durability()),
+            self.budget.quality().max(other.budget.quality()),
+        );
+        Some(Task::with_auto_id(
+            self.term.clone(),
+            Some(truth),
+            self.punctuation,
+            self.time,
+            budget,
+            Stamp::merge(&self.stamp, &other.stamp),
+            self.creation_time.max(other.creation_time),
+        ))
+    }
+    
     /// Check if this is a belief task
     pub fn is_belief(&self) -> bool {
         matches!(self.punctuation, Punctuation::Belief)
@@ -403,6 +436,12 @@ impl TaskBuilder {
         self
     }
     
+    /// Set the evidence from a stamp
+    pub fn stamp(mut self, stamp: Stamp) -> Self {
+        self.evidence = stamp.evidence().to_vec();
+        self
+    }
+    
     /// Set the creation time
     pub fn creation_time(mut self, creation_time: i64) -> Self {
         self.creation_time = creation_time;
@@ -432,7 +471,14 @@ impl TaskBuilder {
             self.truth
         };
         
-        let id = self.id.unwrap_or_else(|| NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed));
+        // An explicit ID is never handed out again
+        let id = match self.id {
+            Some(id) => {
+                NEXT_TASK_ID.fetch_max(id + 1, Ordering::Relaxed);
+                id
+            },
+            None => NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed),
+        };
         
         Ok(Task::new(term, truth, punctuation, self.time, self.budget, id, self.evidence, self.creation_time))
     }
@@ -532,6 +578,61 @@ mod tests {
         assert_eq!(task.creation_time(), 50);
     }
 
+    #[test]
+    fn test_explicit_id_not_reused() {
+        let cat = || TaskBuilder::new().term(Term::Atomic(Atomic::new_atom("cat"))).punctuation(Punctuation::Question);
+        let explicit = cat().id(1_000_000).build().unwrap();
+        assert!(cat().build().unwrap().id() > explicit.id());
+    }
+
+    #[test]
+    fn test_derived_evidence() {
+        let belief = |name: &str, truth: Truth| TaskBuilder::new()
+            .term(Term::Atomic(Atomic::new_atom(name)))
+            .truth(truth)
+            .punctuation(Punctuation::Belief)
+            .build()
+            .unwrap();
+        let a = belief("swimmer", Truth::new(1.0, 0.9));
+        let b = belief("swimmer", Truth::new(0.1, 0.6));
+        assert_eq!(a.evidence(), &[a.id()]);
+        
+        let config = TruthConfig::opennars();
+        let revised = a.revise(&b, &config).unwrap();
+        assert_eq!(revised.truth(), Some(&Truth::new(0.87, 0.91)));
+        assert_eq!(revised.evidence(), &[a.id(), b.id()]);
+        // Evidence is never counted twice
+        assert!(revised.revise(&a, &config).is_none());
+        assert!(a.revise(&a, &config).is_none());
+        assert!(a.revise(&belief("flyer", Truth::new(1.0, 0.9)), &config).is_none());
+        
+        let derived = a.derive_from(&b, Term::Atomic(Atomic::new_atom("c")), None, Punctuation::Question, Time::Eternal, Budget::default());
+        assert!(derived.stamp().overlaps(a.stamp()) && derived.stamp().overlaps(b.stamp()));
+    }
+    
+    #[test]
+    fn test_truth_at() {
+        let config = TruthConfig::default();
+        let event = TaskBuilder::new()
+            .term(Term::Atomic(Atomic::new_atom("rain")))
+            .truth(Truth::new(0.9, 0.8))
+            .punctuation(Punctuation::Belief)
+            .time(Time::Tense(100))
+            .build()
+            .unwrap();
+        assert_eq!(event.truth_at(Time::Tense(100), 100, &config), Some(Truth::new(0.9, 0.8)));
+        assert_eq!(event.truth_at(Time::Tense(101), 100, &config), Some(Truth::new(0.9, 0.4)));
+        assert_eq!(event.truth_at(Time::Eternal, 100, &config), Some(Truth::new(0.9, 0.8 / 1.8)));
+        
+        let eternal = TaskBuilder::new()
+            .term(Term::Atomic(Atomic::new_atom("rain")))
+            .truth(Truth::new(0.9, 0.8))
+            .punctuation(Punctuation::Belief)
+            .build()
+            .unwrap();
+        assert_eq!(eternal.truth_at(Time::Tense(150), 100, &config), Some(Truth::new(0.9, 0.8)));
+    }
+    
     #[test]
     fn test_task_display() {
         let term = Term::Atomic(Atomic::new_atom("cat"));
@@ -0,0 +1,150 @@
+//! Evidential bases of tasks
+//!
+//! A stamp lists the input tasks a task's evidence comes from. Two tasks
+//! whose stamps share an entry draw on the same evidence, so revising them
+//! or deriving from them together would count it twice. Stamps are bounded:
+//! merging keeps the newest entries of both parents, taken alternately, as
+//! OpenNARS does, so neither parent's evidence crowds out the other's.
+
+use std::fmt;
+
+/// Sorted, bounded set of the ids of the input tasks behind a task
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Stamp {
+    evidence: Vec<u64>,
+}
+
+impl Stamp {
+    /// Most entries a stamp keeps
+    pub const CAPACITY: usize = 16;
+
+    /// Create a stamp from evidence ids; duplicates are dropped and only
+    /// the newest `CAPACITY` ids kept
+    pub fn new(evidence: impl IntoIterator<Item = u64>) -> Self {
+        let mut evidence: Vec<u64> = evidence.into_iter().collect();
+        evidence.sort_unstable();
+        evidence.dedup();
+        if evidence.len() > Stamp::CAPACITY {
+            evidence.drain(..evidence.len() - Stamp::CAPACITY);
+        }
+        Stamp { evidence }
+    }
+
+    /// The stamp of an input task: its own id
+    pub fn input(id: u64) -> Self {
+        Stamp { evidence: vec![id] }
+    }
+
+    /// Get the evidence ids in ascending order
+    pub fn evidence(&self) -> &[u64] {
+        &self.evidence
+    }
+
+    /// Get the number of evidence ids
+    pub fn len(&self) -> usize {
+        self.evidence.len()
+    }
+
+    /// Check if the stamp has no evidence
+    pub fn is_empty(&self) -> bool {
+        self.evidence.is_empty()
+    }
+
+    /// Check if an id is part of the evidence
+    pub fn contains(&self, id: u64) -> bool {
+        self.evidence.binary_search(&id).is_ok()
+    }
+
+    /// Check if two stamps share any evidence
+    pub fn overlaps(&self, other: &Stamp) -> bool {
+        let (mut a, mut b) = (self.evidence.iter().peekable(), other.evidence.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            match x.cmp(y) {
+                std::cmp::Ordering::Less => {
+                    a.next();
+                },
+                std::cmp::Ordering::Greater => {
+                    b.next();
+                },
+                std::cmp::Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    /// Combine the evidence of two stamps, newest first and alternating
+    /// between them until the capacity is reached
+    pub fn merge(a: &Stamp, b: &Stamp) -> Stamp {
+        let mut merged = Vec::with_capacity(Stamp::CAPACITY.min(a.len() + b.len()));
+        let (mut a, mut b) = (a.evidence.iter().rev(), b.evidence.iter().rev());
+        while merged.len() < Stamp::CAPACITY {
+            let (x, y) = (a.next(), b.next());
+            if x.is_none() && y.is_none() {
+                break;
+            }
+            for id in x.into_iter().chain(y) {
+                if merged.len() < Stamp::CAPACITY && !merged.contains(id) {
+                    merged.push(*id);
+                }
+            }
+        }
+        merged.sort_unstable();
+        Stamp { evidence: merged }
+    }
+}
+
+impl From<Vec<u64>> for Stamp {
+    fn from(evidence: Vec<u64>) -> Self {
+        Stamp::new(evidence)
+    }
+}
+
+/// Printed as `{1,2,3}`
+impl fmt::Display for Stamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids: Vec<String> = self.evidence.iter().map(u64::to_string).collect();
+        write!(f, "{{{}}}", ids.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let stamp = Stamp::new(vec![5, 1, 3, 1]);
+        assert_eq!(stamp.evidence(), &[1, 3, 5]);
+        assert!(stamp.contains(3) && !stamp.contains(2));
+        assert_eq!(stamp.to_string(), "{1,3,5}");
+
+        // Only the newest evidence is kept
+        let long = Stamp::new(0..100);
+        assert_eq!(long.len(), Stamp::CAPACITY);
+        assert_eq!(long.evidence()[0], 100 - Stamp::CAPACITY as u64);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = Stamp::new(vec![1, 4, 9]);
+        assert!(a.overlaps(&Stamp::new(vec![2, 9])));
+        assert!(!a.overlaps(&Stamp::new(vec![2, 3, 10])));
+        assert!(!a.overlaps(&Stamp::default()));
+        assert!(Stamp::input(4).overlaps(&a));
+    }
+
+    #[test]
+    fn test_merge() {
+        let merged = Stamp::merge(&Stamp::new(vec![1, 2]), &Stamp::new(vec![2, 3]));
+        assert_eq!(merged.evidence(), &[1, 2, 3]);
+
+        // At capacity both parents keep their newest half
+        let a = Stamp::new(0..Stamp::CAPACITY as u64);
+        let b = Stamp::new(1000..1000 + Stamp::CAPACITY as u64);
+        let merged = Stamp::merge(&a, &b);
+        assert_eq!(merged.len(), Stamp::CAPACITY);
+        let from_a = merged.evidence().iter().filter(|id| **id < 1000).count();
+        assert_eq!(from_a, Stamp::CAPACITY / 2);
+        assert!(merged.contains(Stamp::CAPACITY as u64 - 1) && !merged.contains(0));
+    }
+}
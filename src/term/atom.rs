@@ -3,7 +3,8 @@
 //! Atomic terms are the simplest terms in NARS. They include:
 //! - Atoms (strings like "cat", "dog")
 //! - Integers
-//! - Boolean values
+//! - Boolean values and the null term
+//! - The image placeholder `_`
 
 use super::{TermTrait, Op};
 use crate::Term;
@@ -22,6 +23,12 @@ pub enum Atomic {
     
     /// Boolean atomic term
     Bool(bool),
+    
+    /// The meaningless term that invalid constructions reduce to
+    Null,
+    
+    /// The image placeholder `_`, standing for the argument an image moved out
+    Placeholder,
 }
 
 impl Atomic {
@@ -40,6 +47,15 @@ impl Atomic {
         Atomic::Bool(value)
     }
     
+    /// Create the null term
+    pub fn new_null() -> Self {
+        Atomic::Null
+    }
+    
+    /// Create the image placeholder
+    pub fn new_placeholder() -> Self {
+        Atomic::Placeholder
+    }
 
 }
 
@@ -50,9 +66,9 @@ impl TermTrait for Atomic {
     
     fn op_id(&self) -> Op {
         match self {
-            Atomic::Atom(_) => Op::Atom,
+            Atomic::Atom(_) | Atomic::Placeholder => Op::Atom,
             Atomic::Int(_) => Op::Int,
-            Atomic::Bool(_) => Op::Bool,
+            Atomic::Bool(_) | Atomic::Null => Op::Bool,
         }
     }
     
@@ -79,6 +95,8 @@ impl fmt::Display for Atomic {
             Atomic::Atom(s) => write!(f, "{}", s),
             Atomic::Int(i) => write!(f, "{}", i),
             Atomic::Bool(b) => write!(f, "{}", b),
+            Atomic::Null => write!(f, "null"),
+            Atomic::Placeholder => write!(f, "_"),
         }
     }
 }
@@ -98,6 +116,8 @@ impl Hash for Atomic {
                 Op::Bool.hash(state);
                 b.hash(state);
             },
+            Atomic::Null => Op::Bool.hash(state),
+            Atomic::Placeholder => Op::Atom.hash(state),
         }
     }
 }
@@ -130,6 +150,15 @@ mod tests {
         assert_eq!(format!("{}", false_term), "false");
         assert_eq!(true_term.complexity(), 1);
         assert_eq!(true_term.op_id(), Op::Bool);
+        assert_eq!(format!("{}", Atomic::new_null()), "null");
+        assert_ne!(Atomic::new_null(), false_term);
+    }
+
+    #[test]
+    fn test_placeholder() {
+        assert_eq!(format!("{}", Atomic::new_placeholder()), "_");
+        assert_eq!(Atomic::new_placeholder().op_id(), Op::Atom);
+        assert_ne!(Atomic::new_placeholder(), Atomic::new_atom("_"));
     }
 
 
@@ -0,0 +1,275 @@
+//! Validated compound construction
+//!
+//! `Compound::new` accepts anything; `TermBuilder` only builds terms that
+//! make sense in NAL. Subterm counts are checked per operator and temporal
+//! offsets are only allowed on `==>`, `<=>` and `&&`, and an image needs
+//! exactly one placeholder among its arguments; violations are errors.
+//! Degenerate but well-formed constructions are reduced instead:
+//! - a statement whose subject and predicate are equal is `true`
+//! - statements about `true` or `false` are `null`, except that
+//!   `(true ==> x)` is `x`
+//! - double negation cancels, and negating `true` or `false` flips it
+//! - `false` absorbs a conjunction and `true` a disjunction, while the
+//!   identity element is dropped; `(a && --a)` is `false`, `(a || --a)` is
+//!   `true`, repeated members are dropped and a single member stands alone
+//! - repeated members of intersections and sets are dropped, `(a - a)` is
+//!   `null`
+//! - `null` anywhere makes the whole term `null`
+
+use super::atom::Atomic;
+use super::compound::Compound;
+use super::image;
+use super::normalize;
+use super::temporal::Dt;
+use super::{Op, Term, TermTrait};
+use std::fmt;
+
+/// Why a compound could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermError {
+    /// Wrong number of subterms for the operator
+    Arity {
+        /// The operator
+        op: Op,
+        /// Subterms given
+        found: usize,
+        /// Fewest subterms allowed
+        min: usize,
+        /// Most subterms allowed
+        max: usize,
+    },
+
+    /// Temporal offset on an operator that has no time
+    NotTemporal(Op),
+
+    /// Operator of an atomic term or variable used for a compound
+    NotCompound(Op),
+
+    /// Image without exactly one placeholder among its arguments
+    Placeholder(Op),
+}
+
+impl fmt::Display for TermError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TermError::Arity { op, found, min, max } if min == max =>
+                write!(f, "'{}' takes {} subterms, found {}", op, min, found),
+            TermError::Arity { op, found, min, max } if *max == usize::MAX =>
+                write!(f, "'{}' takes at least {} subterms, found {}", op, min, found),
+            TermError::Arity { op, found, min, max } =>
+                write!(f, "'{}' takes {} to {} subterms, found {}", op, min, max, found),
+            TermError::NotTemporal(op) => write!(f, "'{}' cannot have a temporal offset", op),
+            TermError::NotCompound(op) => write!(f, "'{}' is not a compound operator", op),
+            TermError::Placeholder(op) => write!(f, "'{}' needs exactly one placeholder after the relation", op),
+        }
+    }
+}
+
+impl std::error::Error for TermError {}
+
+/// Builds compounds through the NAL validity checks and reductions
+pub struct TermBuilder;
+
+impl TermBuilder {
+    /// Build an eternal compound
+    pub fn compound(op: Op, subterms: Vec<Term>) -> Result<Term, TermError> {
+        TermBuilder::build(op, subterms, Dt::Eternal)
+    }
+
+    /// Build a compound with a temporal offset
+    pub fn temporal(op: Op, subterms: Vec<Term>, dt: i32) -> Result<Term, TermError> {
+        TermBuilder::build(op, subterms, Dt::Offset(dt))
+    }
+
+    /// Build a statement from its subject and predicate
+    pub fn statement(op: Op, subject: Term, predicate: Term) -> Result<Term, TermError> {
+        TermBuilder::build(op, vec![subject, predicate], Dt::Eternal)
+    }
+
+    /// Negate a term
+    pub fn neg(term: Term) -> Result<Term, TermError> {
+        TermBuilder::build(Op::Neg, vec![term], Dt::Eternal)
+    }
+
+    /// Rebuild a whole term bottom up, applying every check and reduction
+    pub fn rebuild(term: &Term) -> Result<Term, TermError> {
+        match term {
+            Term::Compound(compound) => {
+                let subterms = compound.subterms().iter().map(TermBuilder::rebuild).collect::<Result<_, _>>()?;
+                TermBuilder::build(compound.op_id(), subterms, compound.dt())
+            },
+            _ => Ok(term.clone()),
+        }
+    }
+
+    /// Build a compound with an optional temporal offset
+    pub fn build(op: Op, mut subterms: Vec<Term>, dt: Dt) -> Result<Term, TermError> {
+        let info = op.info();
+        if !info.compound {
+            return Err(TermError::NotCompound(op));
+        }
+        let (min, max) = (info.min_arity, info.max_arity);
+        if subterms.len() < min || subterms.len() > max {
+            return Err(TermError::Arity { op, found: subterms.len(), min, max });
+        }
+        if !dt.is_eternal() && !info.temporal {
+            return Err(TermError::NotTemporal(op));
+        }
+        if info.image && (image::is_placeholder(&subterms[0])
+            || subterms[1..].iter().filter(|t| image::is_placeholder(t)).count() != 1)
+        {
+            return Err(TermError::Placeholder(op));
+        }
+        if subterms.iter().any(is_null) {
+            return Ok(null());
+        }
+
+        match op {
+            Op::Neg => return Ok(negate(subterms.remove(0))),
+            _ if info.statement => {
+                let (subject, predicate) = (&subterms[0], &subterms[1]);
+                if op == Op::Implication && *subject == boolean(true) {
+                    return Ok(subterms.remove(1));
+                }
+                if is_bool(subject) || is_bool(predicate) {
+                    return Ok(null());
+                }
+                if subject == predicate {
+                    return Ok(boolean(true));
+                }
+            },
+            Op::Conjunction | Op::Disjunction if matches!(dt, Dt::Eternal | Dt::Offset(0)) => {
+                // For a conjunction `false` absorbs and `true` is the identity;
+                // for a disjunction it is the other way round
+                let absorbing = boolean(op == Op::Disjunction);
+                let identity = boolean(op == Op::Conjunction);
+                if subterms.contains(&absorbing) {
+                    return Ok(absorbing);
+                }
+                subterms.retain(|t| *t != identity);
+                dedup(&mut subterms);
+                if subterms.iter().any(|t| subterms.contains(&negate(t.clone()))) {
+                    return Ok(absorbing);
+                }
+                match subterms.len() {
+                    0 => return Ok(identity),
+                    1 => return Ok(subterms.remove(0)),
+                    _ => {},
+                }
+            },
+            // A sequence of `true` and `false` events is meaningless
+            Op::Conjunction if subterms.iter().any(is_bool) => return Ok(null()),
+            Op::Intersection | Op::IntersectionExt | Op::SetExt | Op::SetInt => {
+                if subterms.iter().any(is_bool) {
+                    return Ok(null());
+                }
+                dedup(&mut subterms);
+                if subterms.len() == 1 && !matches!(op, Op::SetExt | Op::SetInt) {
+                    return Ok(subterms.remove(0));
+                }
+            },
+            Op::Difference | Op::DifferenceExt if subterms.iter().any(is_bool) || subterms[0] == subterms[1] =>
+                return Ok(null()),
+            _ => {},
+        }
+
+        Ok(Term::Compound(Compound::new_dt(op, subterms, dt)))
+    }
+}
+
+/// Negation with double negation cancelled and booleans flipped
+fn negate(term: Term) -> Term {
+    match term {
+        Term::Atomic(Atomic::Bool(value)) => boolean(!value),
+        Term::Compound(compound) if compound.op_id() == Op::Neg && compound.len() == 1 =>
+            compound.subterms()[0].clone(),
+        _ if is_null(&term) => term,
+        _ => Term::Compound(Compound::new(Op::Neg, vec![term])),
+    }
+}
+
+/// Drop repeated members, keeping the canonical order
+fn dedup(subterms: &mut Vec<Term>) {
+    subterms.sort_by(|a, b| normalize::compare(a, b, true));
+    subterms.dedup();
+}
+
+fn boolean(value: bool) -> Term {
+    Term::Atomic(Atomic::new_bool(value))
+}
+
+fn null() -> Term {
+    Term::Atomic(Atomic::new_null())
+}
+
+fn is_bool(term: &Term) -> bool {
+    matches!(term, Term::Atomic(Atomic::Bool(_)))
+}
+
+fn is_null(term: &Term) -> bool {
+    matches!(term, Term::Atomic(Atomic::Null))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    fn rebuilt(narsese: &str) -> Result<Term, TermError> {
+        TermBuilder::rebuild(&term(narsese))
+    }
+
+    #[test]
+    fn test_arity() {
+        assert_eq!(
+            TermBuilder::compound(Op::Inheritance, vec![term("a"), term("b"), term("c")]),
+            Err(TermError::Arity { op: Op::Inheritance, found: 3, min: 2, max: 2 }),
+        );
+        assert!(matches!(TermBuilder::compound(Op::Neg, vec![]), Err(TermError::Arity { .. })));
+        assert!(matches!(rebuilt("{}"), Err(TermError::Arity { .. })));
+        assert_eq!(TermBuilder::compound(Op::Atom, vec![]), Err(TermError::NotCompound(Op::Atom)));
+        assert_eq!(
+            TermBuilder::temporal(Op::Inheritance, vec![term("a"), term("b")], 1),
+            Err(TermError::NotTemporal(Op::Inheritance)),
+        );
+        assert_eq!(TermBuilder::compound(Op::Product, vec![]).unwrap(), term("(*)"));
+        assert_eq!(rebuilt("(/, r, a, b)"), Err(TermError::Placeholder(Op::ImageExt)));
+        assert_eq!(rebuilt("(\\, _, a, _)"), Err(TermError::Placeholder(Op::ImageInt)));
+        assert_eq!(rebuilt("(/, r, _, b)").unwrap(), term("(/, r, _, b)"));
+        assert_eq!(
+            TermError::Arity { op: Op::Neg, found: 0, min: 1, max: 1 }.to_string(),
+            "'--' takes 1 subterms, found 0",
+        );
+    }
+
+    #[test]
+    fn test_statement_reductions() {
+        assert_eq!(rebuilt("<a --> a>").unwrap(), term("true"));
+        assert_eq!(rebuilt("<(&&, a, a) <-> a>").unwrap(), term("true"));
+        assert_eq!(rebuilt("<true --> a>").unwrap(), term("null"));
+        assert_eq!(rebuilt("<true ==> <a --> b>>").unwrap(), term("<a --> b>"));
+        assert_eq!(rebuilt("<false ==> a>").unwrap(), term("null"));
+        assert_eq!(rebuilt("<(a - a) --> b>").unwrap(), term("null"));
+        assert_eq!(rebuilt("<a --> b>").unwrap(), term("<a --> b>"));
+    }
+
+    #[test]
+    fn test_connective_reductions() {
+        assert_eq!(rebuilt("(--, (--, a))").unwrap(), term("a"));
+        assert_eq!(rebuilt("(--, true)").unwrap(), term("false"));
+        assert_eq!(rebuilt("(&&, a, true)").unwrap(), term("a"));
+        assert_eq!(rebuilt("(&&, a, false)").unwrap(), term("false"));
+        assert_eq!(rebuilt("(&&, a, (--, a))").unwrap(), term("false"));
+        assert_eq!(rebuilt("(||, a, (--, a))").unwrap(), term("true"));
+        assert_eq!(rebuilt("(||, b, false, a)").unwrap(), term("(||, a, b)"));
+        assert_eq!(rebuilt("(&&, b, a, b)").unwrap(), term("(&&, a, b)"));
+        assert_eq!(rebuilt("(&/, a, a)").unwrap(), term("(&/, a, a)"));
+        assert_eq!(rebuilt("{b, a, b}").unwrap(), term("{a, b}"));
+        assert_eq!(rebuilt("{a}").unwrap(), term("{a}"));
+        assert_eq!(rebuilt("(|, a, a)").unwrap(), term("a"));
+    }
+}
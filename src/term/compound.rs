@@ -3,6 +3,7 @@
 //! Compound terms are constructed from other terms using operators.
 //! Examples include conjunctions, implications, inheritances, etc.
 
+use super::temporal::Dt;
 use super::{TermTrait, Op, Term};
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -18,7 +19,7 @@ pub struct Compound {
     subterms: Arc<[Term]>,
     
     /// Temporal relation value (for temporal operators)
-    dt: Option<i32>,
+    dt: Dt,
 }
 
 impl Compound {
@@ -27,16 +28,31 @@ impl Compound {
         Compound {
             operator,
             subterms: subterms.into(),
-            dt: None,
+            dt: Dt::Eternal,
         }
     }
     
-    /// Create a new temporal compound term
+    /// Create a new temporal compound term with a concrete offset
     pub fn new_temporal(operator: Op, subterms: Vec<Term>, dt: i32) -> Self {
+        Compound::new_dt(operator, subterms, Dt::Offset(dt))
+    }
+    
+    /// Create a new compound term with any temporal relation
+    pub fn new_dt(operator: Op, subterms: Vec<Term>, dt: Dt) -> Self {
         Compound {
             operator,
             subterms: subterms.into(),
-            dt: Some(dt),
+            dt,
+        }
+    }
+    
+    /// Create a compound with the same operator and temporal offset over
+    /// other subterms
+    pub fn with_subterms(&self, subterms: Vec<Term>) -> Self {
+        Compound {
+            operator: self.operator,
+            subterms: subterms.into(),
+            dt: self.dt,
         }
     }
     
@@ -46,18 +62,26 @@ impl Compound {
     }
     
     /// Get the temporal relation value
-    pub fn dt(&self) -> Option<i32> {
+    pub fn dt(&self) -> Dt {
         self.dt
     }
     
     /// Check if this is a temporal compound
     pub fn is_temporal(&self) -> bool {
-        self.dt.is_some()
+        !self.dt.is_eternal()
     }
     
-    /// Check if this is a sequence (temporal conjunction)
+    /// Check if this is a sequence (conjunction with a concrete offset)
     pub fn is_sequence(&self) -> bool {
-        self.operator == Op::Conjunction && self.is_temporal()
+        self.operator == Op::Conjunction && self.dt.offset().is_some()
+    }
+    
+    /// Check if the order of the subterms carries no meaning: sets,
+    /// intersections, disjunctions, similarity, and conjunctions and
+    /// equivalences that are eternal, of unknown timing or simultaneous
+    pub fn is_commutative(&self) -> bool {
+        let info = self.operator.info();
+        info.commutative && (!info.temporal || self.dt.is_unordered())
     }
     
     /// Get the number of subterms
@@ -100,9 +124,7 @@ impl TermTrait for Compound {
     }
     
     fn root(&self) -> Term {
-        // Root is the same as the term for now (simplified)
-        // In a full implementation, this would normalize the term
-        Term::Compound(self.clone())
+        super::normalize::normalize(&Term::Compound(self.clone()))
     }
 }
 
@@ -117,7 +139,7 @@ impl fmt::Display for Compound {
                     write!(f, "--")
                 }
             },
-            Op::Inheritance | Op::Implication | Op::Similarity | Op::Equivalence => {
+            op if op.info().statement => {
                 // Binary operators with infix notation
                 if self.subterms.len() == 2 {
                     write!(f, "({} {} {})", self.subterms[0], self.operator, self.subterms[1])
@@ -126,13 +148,15 @@ impl fmt::Display for Compound {
                            self.subterms.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(" "))
                 }
             },
-            Op::Conjunction | Op::Disjunction => {
+            Op::Conjunction | Op::Disjunction | Op::IntersectionExt => {
                 // N-ary operators with infix notation
-                if let Some(dt) = self.dt {
+                if self.is_temporal() {
                     write!(f, "({})",
                            self.subterms.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(&format!(" {} ", self.operator)))?;
-                    if dt != 0 {
-                        write!(f, "_{}", dt)?;
+                    match self.dt {
+                        Dt::Offset(0) => {},
+                        Dt::Offset(dt) => write!(f, "_{}", dt)?,
+                        _ => write!(f, "_{}", self.dt)?,
                     }
                     Ok(())
                 } else {
@@ -192,9 +216,12 @@ mod tests {
         let subterms = vec![atom1, atom2];
         
         let temporal_compound = Compound::new_temporal(Op::Conjunction, subterms, 5);
-        assert_eq!(temporal_compound.dt(), Some(5));
+        assert_eq!(temporal_compound.dt(), Dt::Offset(5));
         assert!(temporal_compound.is_temporal());
         assert!(temporal_compound.is_sequence());
+        assert!(!temporal_compound.is_commutative());
+        assert!(Compound::new_temporal(Op::Conjunction, vec![], 0).is_commutative());
+        assert!(!Compound::new(Op::Inheritance, vec![]).is_commutative());
     }
 
     #[test]
@@ -205,7 +232,14 @@ mod tests {
         // Test conjunction
         let conj_subterms = vec![atom1.clone(), atom2.clone()];
         let conjunction = Compound::new(Op::Conjunction, conj_subterms);
-        assert_eq!(format!("{}", conjunction), "(cat & dog)");
+        assert_eq!(format!("{}", conjunction), "(cat && dog)");
+        
+        // Extensional intersection and the instance copula print their own
+        // symbols
+        let intersection = Compound::new(Op::IntersectionExt, vec![atom1.clone(), atom2.clone()]);
+        assert_eq!(format!("{}", intersection), "(cat & dog)");
+        let instance = Compound::new(Op::Instance, vec![atom1.clone(), atom2.clone()]);
+        assert_eq!(format!("{}", instance), "(cat {-- dog)");
         
         // Test inheritance
         let inh_subterms = vec![atom1.clone(), atom2.clone()];
@@ -220,7 +254,7 @@ mod tests {
         // Test temporal conjunction
         let temp_subterms = vec![atom1, atom2];
         let temporal = Compound::new_temporal(Op::Conjunction, temp_subterms, 3);
-        assert_eq!(format!("{}", temporal), "(cat & dog)_3");
+        assert_eq!(format!("{}", temporal), "(cat && dog)_3");
     }
     
     #[test]
@@ -232,7 +266,7 @@ mod tests {
         let seq_compound = Compound::new_temporal(Op::Conjunction, vec![a.clone(), b.clone()], 1);
         // Note: The display format depends on how we map operators
         // For sequential conjunction, we might want to use a different operator
-        assert_eq!(format!("{}", seq_compound), "(a & b)_1");
+        assert_eq!(format!("{}", seq_compound), "(a && b)_1");
         
         // Test parallel conjunction (&|)
         let par_compound = Compound::new_temporal(Op::Intersection, vec![a.clone(), b.clone()], 0);
@@ -248,6 +282,6 @@ mod tests {
         // Test nested compounds
         let conjunction = Term::Compound(Compound::new(Op::Conjunction, vec![cat.clone(), dog.clone()]));
         let inheritance = Term::Compound(Compound::new(Op::Inheritance, vec![conjunction, animal]));
-        assert_eq!(format!("{}", inheritance), "((cat & dog) --> animal)");
+        assert_eq!(format!("{}", inheritance), "((cat && dog) --> animal)");
     }
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+//! NAL-6 images
+//!
+//! A relation between several terms is a statement about their product,
+//! `<(*, a, b) --> r>`. An extensional image singles out one argument by
+//! making it the subject and leaving the placeholder `_` in its place, so
+//! `<a --> (/, r, _, b)>` and `<b --> (/, r, a, _)>` say the same as the
+//! product statement. An intensional image does the same for a product in
+//! the predicate: `<r --> (*, a, b)>` is `<(\, r, _, b) --> a>`.
+//!
+//! The first subterm of an image is the relation, the rest are the product
+//! members with the placeholder standing for the one moved out. The normal
+//! form of a term uses products only, so every image of a relation shares
+//! the relation's concept.
+
+use super::atom::Atomic;
+use super::compound::Compound;
+use super::visit::Visit;
+use super::{Op, Term, TermTrait};
+
+/// The image placeholder `_`
+pub fn placeholder() -> Term {
+    Term::Atomic(Atomic::new_placeholder())
+}
+
+/// Check if a term is the image placeholder
+pub fn is_placeholder(term: &Term) -> bool {
+    matches!(term, Term::Atomic(Atomic::Placeholder))
+}
+
+/// Turn `<(*, a, b) --> r>` into the extensional image statement for the
+/// product member at `index`, e.g. `<a --> (/, r, _, b)>` for index 0
+pub fn image_ext(statement: &Term, index: usize) -> Option<Term> {
+    let (product, relation) = parts(statement)?;
+    let (argument, members) = take_argument(product, index)?;
+    let mut subterms = vec![relation.clone()];
+    subterms.extend(members);
+    Some(inheritance(argument, Term::Compound(Compound::new(Op::ImageExt, subterms))))
+}
+
+/// Turn `<r --> (*, a, b)>` into the intensional image statement for the
+/// product member at `index`, e.g. `<(\, r, _, b) --> a>` for index 0
+pub fn image_int(statement: &Term, index: usize) -> Option<Term> {
+    let (relation, product) = parts(statement)?;
+    let (argument, members) = take_argument(product, index)?;
+    let mut subterms = vec![relation.clone()];
+    subterms.extend(members);
+    Some(inheritance(Term::Compound(Compound::new(Op::ImageInt, subterms)), argument))
+}
+
+/// Turn an image statement back into its product statement:
+/// `<a --> (/, r, _, b)>` becomes `<(*, a, b) --> r>` and
+/// `<(\, r, _, b) --> a>` becomes `<r --> (*, a, b)>`
+pub fn to_product(statement: &Term) -> Option<Term> {
+    let (subject, predicate) = parts(statement)?;
+    match (subject, predicate) {
+        (_, Term::Compound(image)) if image.op_id() == Op::ImageExt => {
+            let (relation, product) = fill(image, subject)?;
+            Some(inheritance(product, relation))
+        },
+        (Term::Compound(image), _) if image.op_id() == Op::ImageInt => {
+            let (relation, product) = fill(image, predicate)?;
+            Some(inheritance(relation, product))
+        },
+        _ => None,
+    }
+}
+
+/// Replace every image statement in a term by its product statement
+pub fn normalize_images(term: &Term) -> Term {
+    term.transform(&mut |_: &[usize], sub: &Term| match to_product(sub) {
+        Some(product) => Visit::Replace(normalize_images(&product)),
+        None => Visit::Descend,
+    })
+}
+
+/// Subject and predicate of an eternal inheritance
+fn parts(statement: &Term) -> Option<(&Term, &Term)> {
+    match statement {
+        Term::Compound(compound) if compound.op_id() == Op::Inheritance && !compound.is_temporal()
+            && compound.len() == 2 => Some((&compound.subterms()[0], &compound.subterms()[1])),
+        _ => None,
+    }
+}
+
+/// Product member at an index, and the members with the placeholder in its
+/// place
+fn take_argument(product: &Term, index: usize) -> Option<(Term, Vec<Term>)> {
+    let Term::Compound(product) = product else {
+        return None;
+    };
+    if product.op_id() != Op::Product || product.is_temporal() || product.subterms().iter().any(is_placeholder) {
+        return None;
+    }
+    let argument = product.get(index)?.clone();
+    let mut members = product.subterms().to_vec();
+    members[index] = placeholder();
+    Some((argument, members))
+}
+
+/// Relation of an image and its product with the placeholder filled in
+fn fill(image: &Compound, argument: &Term) -> Option<(Term, Term)> {
+    let (relation, members) = image.subterms().split_first()?;
+    if image.is_temporal() || members.iter().filter(|t| is_placeholder(t)).count() != 1 {
+        return None;
+    }
+    let members = members.iter().map(|t| if is_placeholder(t) { argument.clone() } else { t.clone() }).collect();
+    Some((relation.clone(), Term::Compound(Compound::new(Op::Product, members))))
+}
+
+fn inheritance(subject: Term, predicate: Term) -> Term {
+    Term::Compound(Compound::new(Op::Inheritance, vec![subject, predicate]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    #[test]
+    fn test_extensional_image() {
+        let relation = term("<(*, a, b) --> r>");
+        assert_eq!(image_ext(&relation, 0), Some(term("<a --> (/, r, _, b)>")));
+        assert_eq!(image_ext(&relation, 1), Some(term("<b --> (/, r, a, _)>")));
+        assert_eq!(image_ext(&relation, 2), None);
+        assert_eq!(image_ext(&term("<r --> (*, a, b)>"), 0), None);
+        for index in 0..2 {
+            assert_eq!(to_product(&image_ext(&relation, index).unwrap()), Some(relation.clone()));
+        }
+    }
+
+    #[test]
+    fn test_intensional_image() {
+        let relation = term("<r --> (*, a, b)>");
+        assert_eq!(image_int(&relation, 0), Some(term("<(\\, r, _, b) --> a>")));
+        assert_eq!(to_product(&term("<(\\, r, a, _) --> b>")), Some(relation));
+        assert_eq!(to_product(&term("<(\\, r, a, b) --> c>")), None);
+        assert_eq!(to_product(&term("<a --> (/, r, _, _)>")), None);
+    }
+
+    #[test]
+    fn test_normalize_images() {
+        assert_eq!(
+            normalize_images(&term("(&&, <a --> (/, r, _, b)>, <c --> d>)")),
+            term("(&&, <(*, a, b) --> r>, <c --> d>)"),
+        );
+        // An image whose argument is itself an image statement
+        assert_eq!(
+            normalize_images(&term("<<x --> (/, s, _, y)> --> (/, r, _, b)>")),
+            term("<(*, <(*, x, y) --> s>, b) --> r>"),
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Hash-consed terms
+//!
+//! The interner keeps one shared copy of every distinct term. Interning
+//! works bottom up, so equal subterms of different terms share storage too.
+//! Each copy is handed out as a `TermRef` carrying metadata computed once:
+//! its hash, complexity, volume, variable counts and the set of operators
+//! it contains. Two handles from the same interner are equal exactly when
+//! they point at the same copy, so comparing and hashing them never walks
+//! the term.
+
+use super::compound::Compound;
+use super::normalize::normalize;
+use super::{Op, Term, TermTrait};
+use parking_lot::RwLock;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Occurrences of each variable kind in a term
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct VarCounts {
+    /// Dependent variables, `#x`
+    pub dep: u16,
+
+    /// Independent variables, `$x`
+    pub indep: u16,
+
+    /// Query variables, `?x`
+    pub query: u16,
+
+    /// Pattern variables, `@x`
+    pub pattern: u16,
+}
+
+impl VarCounts {
+    /// Total number of variable occurrences
+    pub fn total(&self) -> u32 {
+        self.dep as u32 + self.indep as u32 + self.query as u32 + self.pattern as u32
+    }
+
+    fn add(self, other: VarCounts) -> VarCounts {
+        VarCounts {
+            dep: self.dep.saturating_add(other.dep),
+            indep: self.indep.saturating_add(other.indep),
+            query: self.query.saturating_add(other.query),
+            pattern: self.pattern.saturating_add(other.pattern),
+        }
+    }
+}
+
+/// Facts about a term computed when it is interned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermMeta {
+    /// Hash of the term
+    pub hash: u64,
+
+    /// Number of non-variable nodes in the term
+    pub complexity: u32,
+
+    /// Number of nodes in the term, variables included
+    pub volume: u32,
+
+    /// Variable occurrences
+    pub vars: VarCounts,
+
+    /// Bit `op.code()` is set for every operator occurring in the term
+    pub structure: u32,
+}
+
+impl TermMeta {
+    /// Check if an operator occurs anywhere in the term
+    pub fn has_op(&self, op: Op) -> bool {
+        self.structure & Self::bit(op) != 0
+    }
+
+    /// Check if every operator of another structure mask occurs in the term,
+    /// a cheap necessary condition for containing that term
+    pub fn covers(&self, structure: u32) -> bool {
+        self.structure & structure == structure
+    }
+
+    fn bit(op: Op) -> u32 {
+        1 << op.code()
+    }
+}
+
+struct Node {
+    id: u64,
+    term: Term,
+    meta: TermMeta,
+}
+
+/// Handle to an interned term
+#[derive(Clone)]
+pub struct TermRef(Arc<Node>);
+
+impl TermRef {
+    /// Get the term
+    pub fn term(&self) -> &Term {
+        &self.0.term
+    }
+
+    /// Get the precomputed metadata
+    pub fn meta(&self) -> &TermMeta {
+        &self.0.meta
+    }
+
+    /// Get the number assigned by the interner, unique per distinct term
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    /// Get the number of non-variable nodes
+    pub fn complexity(&self) -> u32 {
+        self.0.meta.complexity
+    }
+
+    /// Get the number of nodes
+    pub fn volume(&self) -> u32 {
+        self.0.meta.volume
+    }
+}
+
+impl PartialEq for TermRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TermRef {}
+
+impl Hash for TermRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.meta.hash);
+    }
+}
+
+impl fmt::Display for TermRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.term)
+    }
+}
+
+impl fmt::Debug for TermRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TermRef({}, {})", self.0.id, self.0.term)
+    }
+}
+
+#[derive(Default)]
+struct Tables {
+    terms: HashMap<Term, TermRef>,
+    normal: HashMap<Term, TermRef>,
+
+    /// Id of the next term interned; ids are never reused
+    next_id: u64,
+}
+
+/// Shared store of distinct terms; safe to use through `&self` from
+/// several threads
+#[derive(Default)]
+pub struct TermInterner {
+    tables: RwLock<Tables>,
+}
+
+impl TermInterner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        TermInterner::default()
+    }
+
+    /// Get the handle for a term, interning it and its subterms if needed
+    pub fn intern(&self, term: &Term) -> TermRef {
+        if let Some(found) = self.get(term) {
+            return found;
+        }
+        let mut tables = self.tables.write();
+        Self::insert(&mut tables, term)
+    }
+
+    /// Get the handle for a term that is already interned
+    pub fn get(&self, term: &Term) -> Option<TermRef> {
+        self.tables.read().terms.get(term).cloned()
+    }
+
+    /// Get the handle for the normal form of a term; the normal form is
+    /// remembered, so looking the same term up again does not allocate
+    pub fn intern_normal(&self, term: &Term) -> TermRef {
+        if let Some(found) = self.tables.read().normal.get(term) {
+            return found.clone();
+        }
+        let normal = normalize(term);
+        let mut tables = self.tables.write();
+        let found = Self::insert(&mut tables, &normal);
+        tables.normal.insert(term.clone(), found.clone());
+        found
+    }
+
+    /// Get the handle for the normal form of a term if it is interned,
+    /// without interning anything
+    pub fn get_normal(&self, term: &Term) -> Option<TermRef> {
+        if let Some(found) = self.tables.read().normal.get(term) {
+            return Some(found.clone());
+        }
+        self.get(&normalize(term))
+    }
+
+    /// Forget a term and the terms remembered as having it for their normal
+    /// form; its subterms stay, as other terms may share them
+    pub fn remove(&self, term: &TermRef) {
+        let mut tables = self.tables.write();
+        tables.terms.remove(term.term());
+        tables.normal.retain(|_, normal| normal != term);
+    }
+
+    /// Forget every term; ids keep counting up, so handles from before
+    /// never equal new ones
+    pub fn clear(&self) {
+        let mut tables = self.tables.write();
+        tables.terms.clear();
+        tables.normal.clear();
+    }
+
+    /// Get the number of distinct terms
+    pub fn len(&self) -> usize {
+        self.tables.read().terms.len()
+    }
+
+    /// Check if nothing has been interned
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(tables: &mut Tables, term: &Term) -> TermRef {
+        if let Some(found) = tables.terms.get(term) {
+            return found.clone();
+        }
+        let (term, meta) = match term {
+            Term::Compound(compound) => {
+                let subterms: Vec<TermRef> = compound.subterms().iter().map(|t| Self::insert(tables, t)).collect();
+                let shared: Vec<Term> = subterms.iter().map(|t| t.term().clone()).collect();
+                let term = Term::Compound(compound.with_subterms(shared));
+                (term, Self::compound_meta(compound, &subterms))
+            },
+            _ => (term.clone(), Self::atomic_meta(term)),
+        };
+        let found = TermRef(Arc::new(Node { id: tables.next_id, term: term.clone(), meta }));
+        tables.next_id += 1;
+        tables.terms.insert(term, found.clone());
+        found
+    }
+
+    fn atomic_meta(term: &Term) -> TermMeta {
+        let mut hasher = DefaultHasher::new();
+        term.hash(&mut hasher);
+        let vars = match term.op_id() {
+            Op::VarDep => VarCounts { dep: 1, ..VarCounts::default() },
+            Op::VarIndep => VarCounts { indep: 1, ..VarCounts::default() },
+            Op::VarQuery => VarCounts { query: 1, ..VarCounts::default() },
+            Op::VarPattern => VarCounts { pattern: 1, ..VarCounts::default() },
+            _ => VarCounts::default(),
+        };
+        TermMeta {
+            hash: hasher.finish(),
+            complexity: if matches!(term, Term::Atomic(_)) { 1 } else { 0 },
+            volume: 1,
+            vars,
+            structure: TermMeta::bit(term.op_id()),
+        }
+    }
+
+    fn compound_meta(compound: &Compound, subterms: &[TermRef]) -> TermMeta {
+        let mut hasher = DefaultHasher::new();
+        compound.op_id().hash(&mut hasher);
+        compound.dt().hash(&mut hasher);
+        let mut meta = TermMeta {
+            hash: 0,
+            complexity: 1,
+            volume: 1,
+            vars: VarCounts::default(),
+            structure: TermMeta::bit(compound.op_id()),
+        };
+        for subterm in subterms {
+            let sub = subterm.meta();
+            hasher.write_u64(sub.hash);
+            meta.complexity += sub.complexity;
+            meta.volume += sub.volume;
+            meta.vars = meta.vars.add(sub.vars);
+            meta.structure |= sub.structure;
+        }
+        meta.hash = hasher.finish();
+        meta
+    }
+}
+
+impl fmt::Debug for TermInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TermInterner({} terms)", self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    #[test]
+    fn test_intern_shares_equal_terms() {
+        let interner = TermInterner::new();
+        let a = interner.intern(&term("<(*, a, b) --> c>"));
+        let b = interner.intern(&term("<(*, a, b) --> c>"));
+        let c = interner.intern(&term("<(*, a, b) --> d>"));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.id(), b.id());
+
+        // The product is stored once and shared by both statements
+        let (Term::Compound(x), Term::Compound(y)) = (a.term(), c.term()) else { panic!("Expected compounds") };
+        let (Term::Compound(px), Term::Compound(py)) = (&x.subterms()[0], &y.subterms()[0]) else { panic!() };
+        assert!(std::ptr::eq(px.subterms().as_ptr(), py.subterms().as_ptr()));
+        assert_eq!(interner.len(), 7);
+    }
+
+    #[test]
+    fn test_meta() {
+        let interner = TermInterner::new();
+        let handle = interner.intern(&term("<(&&, $x, #y, a) --> $x>"));
+        let meta = handle.meta();
+        assert_eq!(meta.volume, 6);
+        assert_eq!(meta.complexity, 3);
+        assert_eq!(meta.vars, VarCounts { dep: 1, indep: 2, query: 0, pattern: 0 });
+        assert!(meta.has_op(Op::Conjunction));
+        assert!(meta.has_op(Op::VarDep));
+        assert!(!meta.has_op(Op::Product));
+        let inner = interner.intern(&term("(&&, $x, #y, a)"));
+        assert!(meta.covers(inner.meta().structure));
+        assert_eq!(handle.term(), &term("<(&&, $x, #y, a) --> $x>"));
+    }
+
+    #[test]
+    fn test_intern_normal() {
+        let interner = TermInterner::new();
+        let a = interner.intern_normal(&term("(a && b)"));
+        let b = interner.intern_normal(&term("(b && a)"));
+        assert_eq!(a, b);
+        assert_eq!(interner.intern_normal(&term("<$x --> a>")), interner.intern_normal(&term("<$y --> a>")));
+        assert!(interner.get(&term("(b && a)")).is_none());
+    }
+
+    #[test]
+    fn test_lookup_and_remove() {
+        let interner = TermInterner::new();
+        assert!(interner.get_normal(&term("(b && a)")).is_none());
+        assert!(interner.is_empty());
+
+        let a = interner.intern_normal(&term("(a && b)"));
+        assert_eq!(interner.get_normal(&term("(b && a)")), Some(a.clone()));
+        interner.remove(&a);
+        assert!(interner.get_normal(&term("(a && b)")).is_none());
+        assert_eq!(interner.len(), 2);
+
+        // Ids are not handed out twice
+        interner.clear();
+        assert!(interner.intern(&term("c")).id() > a.id());
+    }
+}
@@ -5,8 +5,16 @@
 //! Terms are the basic building blocks of NARS's logical language.
 
 pub mod atom;
+pub mod builder;
 pub mod compound;
+pub mod image;
+pub mod intern;
+pub mod normalize;
+pub mod op;
+pub mod temporal;
+pub mod unify;
 pub mod var;
+pub mod visit;
 
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -32,73 +40,53 @@ pub trait TermTrait: fmt::Display + fmt::Debug + Hash + Eq {
     fn root(&self) -> Term;
 }
 
-/// Operator types for terms
+/// Operator types for terms; the discriminant is the operator's stable byte
+/// code and its index in the operator table
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
 pub enum Op {
     // Atomic operators
-    Atom,
-    Int,
-    Bool,
+    Atom = 0,
+    Int = 1,
+    Bool = 2,
     
     // Variable operators
-    VarDep,
-    VarIndep,
-    VarQuery,
-    VarPattern,
+    VarDep = 3,
+    VarIndep = 4,
+    VarQuery = 5,
+    VarPattern = 6,
     
     // Compound operators
-    Neg,
-    Conjunction,
-    Disjunction,
-    Intersection,
-    Difference,
-    Inheritance,
-    Similarity,
-    Implication,
-    Equivalence,
-    Instance,
-    Property,
-    InstanceProperty,
-    ImageExt,
-    ImageInt,
+    Neg = 7,
+    Conjunction = 8,
+    Disjunction = 9,
+    Intersection = 10,
+    IntersectionExt = 11,
+    Difference = 12,
+    DifferenceExt = 13,
+    Inheritance = 14,
+    Similarity = 15,
+    Implication = 16,
+    Equivalence = 17,
+    Instance = 18,
+    Property = 19,
+    InstanceProperty = 20,
+    ImageExt = 21,
+    ImageInt = 22,
     
     // Special operators
-    SetExt,
-    SetInt,
-    Product,
-    ExtensionalImage,
-    IntensionalImage,
+    SetExt = 23,
+    SetInt = 24,
+    Product = 25,
 }
 
+/// Operators print as their canonical Narsese symbol from the operator
+/// table; atomic operators without one print their name
 impl fmt::Display for Op {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Op::Atom => write!(f, "Atom"),
-            Op::Int => write!(f, "Int"),
-            Op::Bool => write!(f, "Bool"),
-            Op::VarDep => write!(f, "#"),
-            Op::VarIndep => write!(f, "$"),
-            Op::VarQuery => write!(f, "?"),
-            Op::VarPattern => write!(f, "@"),
-            Op::Neg => write!(f, "--"),
-            Op::Conjunction => write!(f, "&"),
-            Op::Disjunction => write!(f, "||"),
-            Op::Intersection => write!(f, "|"),
-            Op::Difference => write!(f, "~"),
-            Op::Inheritance => write!(f, "-->"),
-            Op::Similarity => write!(f, "<->"),
-            Op::Implication => write!(f, "==>"),
-            Op::Equivalence => write!(f, "<=>"),
-            Op::Instance => write!(f, "{{"),
-            Op::Property => write!(f, "}}"),
-            Op::InstanceProperty => write!(f, "}}]"),
-            Op::ImageExt => write!(f, "\\"),
-            Op::ImageInt => write!(f, "/"),
-            Op::SetExt => write!(f, "{{}}"),
-            Op::SetInt => write!(f, "[]"),
-            Op::Product => write!(f, "*"),
-            Op::ExtensionalImage => write!(f, "\\"),
-            Op::IntensionalImage => write!(f, "/"),
+        match self.info().symbol {
+            "" => write!(f, "{:?}", self),
+            symbol => f.write_str(symbol),
         }
     }
 }
@@ -161,6 +149,13 @@ impl TermTrait for Term {
     }
 }
 
+impl Term {
+    /// Replace the variables bound in a substitution
+    pub fn apply(&self, subst: &unify::Subst) -> Term {
+        unify::apply(self, subst)
+    }
+}
+
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -201,7 +196,7 @@ mod tests {
     #[test]
     fn test_op_display() {
         assert_eq!(format!("{}", Op::Atom), "Atom");
-        assert_eq!(format!("{}", Op::Conjunction), "&");
+        assert_eq!(format!("{}", Op::Conjunction), "&&");
         assert_eq!(format!("{}", Op::Inheritance), "-->");
     }
     
@@ -215,24 +210,24 @@ mod tests {
         assert_eq!(format!("{}", Op::VarQuery), "?");
         assert_eq!(format!("{}", Op::VarPattern), "@");
         assert_eq!(format!("{}", Op::Neg), "--");
-        assert_eq!(format!("{}", Op::Conjunction), "&");
+        assert_eq!(format!("{}", Op::Conjunction), "&&");
         assert_eq!(format!("{}", Op::Disjunction), "||");
         assert_eq!(format!("{}", Op::Intersection), "|");
+        assert_eq!(format!("{}", Op::IntersectionExt), "&");
         assert_eq!(format!("{}", Op::Difference), "~");
+        assert_eq!(format!("{}", Op::DifferenceExt), "-");
         assert_eq!(format!("{}", Op::Inheritance), "-->");
         assert_eq!(format!("{}", Op::Similarity), "<->");
         assert_eq!(format!("{}", Op::Implication), "==>");
         assert_eq!(format!("{}", Op::Equivalence), "<=>");
-        assert_eq!(format!("{}", Op::Instance), "{");
-        assert_eq!(format!("{}", Op::Property), "}");
-        assert_eq!(format!("{}", Op::InstanceProperty), "}]");
-        assert_eq!(format!("{}", Op::ImageExt), "\\");
-        assert_eq!(format!("{}", Op::ImageInt), "/");
+        assert_eq!(format!("{}", Op::Instance), "{--");
+        assert_eq!(format!("{}", Op::Property), "--]");
+        assert_eq!(format!("{}", Op::InstanceProperty), "{-]");
+        assert_eq!(format!("{}", Op::ImageExt), "/");
+        assert_eq!(format!("{}", Op::ImageInt), "\\");
         assert_eq!(format!("{}", Op::SetExt), "{}");
         assert_eq!(format!("{}", Op::SetInt), "[]");
         assert_eq!(format!("{}", Op::Product), "*");
-        assert_eq!(format!("{}", Op::ExtensionalImage), "\\");
-        assert_eq!(format!("{}", Op::IntensionalImage), "/");
     }
     
     #[test]
@@ -250,12 +245,12 @@ mod tests {
         let walk = Term::Atomic(Atomic::new_atom("walk"));
         let run = Term::Atomic(Atomic::new_atom("run"));
         let conjunction = Term::Compound(Compound::new(Op::Conjunction, vec![walk.clone(), run]));
-        assert_eq!(format!("{}", conjunction), "(walk & run)");
+        assert_eq!(format!("{}", conjunction), "(walk && run)");
         
         // Test a nested compound term
         let conjunction2 = Term::Compound(Compound::new(Op::Conjunction, vec![cat.clone(), walk.clone()]));
         let nested = Term::Compound(Compound::new(Op::Inheritance, vec![conjunction2, animal.clone()]));
-        assert_eq!(format!("{}", nested), "((cat & walk) --> animal)");
+        assert_eq!(format!("{}", nested), "((cat && walk) --> animal)");
     }
     
     #[test]
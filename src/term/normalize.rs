@@ -0,0 +1,199 @@
+//! Canonical term form
+//!
+//! Terms that mean the same thing should land in the same concept. The
+//! normal form:
+//! - replaces images by products, `<a --> (/, r, _, b)>` becomes
+//!   `<(*, a, b) --> r>`
+//! - cancels double negation, `(--, (--, a))` becomes `a`
+//! - flattens conjunctions, disjunctions and intersections nested in one of
+//!   the same operator and offset, `(&&, a, (&&, b, c))` becomes `(&&, a, b, c)`
+//! - writes sequences in one way, so `(a &&+2 b &&+3 c)` and
+//!   `((a &&+2 b) &&+3 c)` agree and `(a &&-1 b)` becomes `(b &/ a)`
+//! - sorts the subterms of commutative compounds and drops duplicates from
+//!   the ones that are not statements
+//! - renumbers dependent, independent and query variables in order of
+//!   appearance, so `<$x --> a>` and `<$y --> a>` both become `<$1 --> a>`
+//!
+//! Pattern variables keep their names, since rules refer to them by name.
+
+use super::atom::Atomic;
+use super::compound::Compound;
+use super::image;
+use super::temporal::{Dt, Sequence};
+use super::var::Variable;
+use super::{Op, Term, TermTrait};
+use std::cmp::Ordering;
+
+/// Bring a term into canonical form
+pub fn normalize(term: &Term) -> Term {
+    // Sort without looking at variable names, number the variables, then
+    // sort again with the new names until the numbering stops changing
+    let mut current = renumber(&structure(&image::normalize_images(term), false));
+    for _ in 0..3 {
+        let next = renumber(&structure(&current, true));
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+/// Total order on terms used for sorting commutative subterms: operator,
+/// complexity, then contents; with `names` off all variables of one kind
+/// compare equal
+pub fn compare(a: &Term, b: &Term, names: bool) -> Ordering {
+    a.op_id()
+        .code()
+        .cmp(&b.op_id().code())
+        .then_with(|| a.complexity().cmp(&b.complexity()))
+        .then_with(|| match (a, b) {
+            (Term::Atomic(x), Term::Atomic(y)) => match (x, y) {
+                (Atomic::Atom(x), Atomic::Atom(y)) => x.cmp(y),
+                (Atomic::Int(x), Atomic::Int(y)) => x.cmp(y),
+                (Atomic::Bool(x), Atomic::Bool(y)) => x.cmp(y),
+                (Atomic::Placeholder, Atomic::Atom(_)) => Ordering::Less,
+                (Atomic::Atom(_), Atomic::Placeholder) => Ordering::Greater,
+                _ => Ordering::Equal,
+            },
+            (Term::Variable(x), Term::Variable(y)) if names => x.name().cmp(y.name()),
+            (Term::Compound(x), Term::Compound(y)) => x
+                .dt()
+                .cmp(&y.dt())
+                .then_with(|| x.len().cmp(&y.len()))
+                .then_with(|| {
+                    x.subterms()
+                        .iter()
+                        .zip(y.subterms())
+                        .map(|(p, q)| compare(p, q, names))
+                        .find(|order| order.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                }),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Apply the structural rewrites bottom up
+fn structure(term: &Term, names: bool) -> Term {
+    let Term::Compound(compound) = term else {
+        return term.clone();
+    };
+    let op = compound.op_id();
+    let dt = compound.dt();
+    let mut subterms: Vec<Term> = Vec::with_capacity(compound.len());
+    for subterm in compound.subterms() {
+        let subterm = structure(subterm, names);
+        match &subterm {
+            Term::Compound(inner) if is_associative(op) && inner.op_id() == op && inner.dt() == dt
+                && compound.is_commutative() =>
+            {
+                subterms.extend_from_slice(inner.subterms())
+            },
+            _ => subterms.push(subterm),
+        }
+    }
+
+    if op == Op::Neg && subterms.len() == 1 {
+        if let Term::Compound(inner) = &subterms[0] {
+            if inner.op_id() == Op::Neg && inner.len() == 1 {
+                return inner.subterms()[0].clone();
+            }
+        }
+    }
+
+    if compound.is_commutative() {
+        subterms.sort_by(|a, b| compare(a, b, names));
+        // Repeated events at unknown times are different events
+        if !op.info().statement && !dt.is_xternal() {
+            subterms.dedup();
+        }
+    }
+    let normal = Term::Compound(Compound::new_dt(op, subterms, dt));
+    match dt {
+        Dt::Offset(offset) if op == Op::Conjunction && offset != 0 =>
+            Sequence::of(&normal).to_term().unwrap_or(normal),
+        _ => normal,
+    }
+}
+
+fn is_associative(op: Op) -> bool {
+    matches!(op, Op::Conjunction | Op::Disjunction | Op::Intersection | Op::IntersectionExt)
+}
+
+/// Rename variables to `1`, `2`, ... in order of first appearance
+fn renumber(term: &Term) -> Term {
+    let mut names: Vec<(Variable, Variable)> = Vec::new();
+    rename(term, &mut names)
+}
+
+fn rename(term: &Term, names: &mut Vec<(Variable, Variable)>) -> Term {
+    match term {
+        Term::Variable(variable) if variable.op_id() != Op::VarPattern => {
+            if let Some((_, renamed)) = names.iter().find(|(original, _)| original == variable) {
+                return Term::Variable(renamed.clone());
+            }
+            let number = (names.len() + 1).to_string();
+            let renamed = match variable.op_id() {
+                Op::VarDep => Variable::new_dep(&number),
+                Op::VarIndep => Variable::new_indep(&number),
+                _ => Variable::new_query(&number),
+            };
+            names.push((variable.clone(), renamed.clone()));
+            Term::Variable(renamed)
+        },
+        Term::Compound(compound) => {
+            let subterms = compound.subterms().iter().map(|t| rename(t, names)).collect();
+            Term::Compound(compound.with_subterms(subterms))
+        },
+        _ => term.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn normal(narsese: &str) -> Term {
+        normalize(&Parser::parse_term(narsese).unwrap())
+    }
+
+    #[test]
+    fn test_commutative_order() {
+        assert_eq!(normal("(a && b)"), normal("(b && a)"));
+        assert_eq!(normal("{c, a, b}"), Parser::parse_term("{a, b, c}").unwrap());
+        assert_eq!(normal("<b <-> a>"), normal("<a <-> b>"));
+        assert_ne!(normal("<b --> a>"), normal("<a --> b>"));
+        assert_ne!(normal("(a &/ b)"), normal("(b &/ a)"));
+        assert_eq!(normal("[a, b, a]"), Parser::parse_term("[a, b]").unwrap());
+        assert_eq!(normal("<a <-> a>"), Parser::parse_term("<a <-> a>").unwrap());
+    }
+
+    #[test]
+    fn test_flatten_and_negation() {
+        assert_eq!(normal("(&&, a, (&&, c, b))"), Parser::parse_term("(&&, a, b, c)").unwrap());
+        assert_eq!(normal("(&&, a, (&/, c, b))"), normal("(&&, (&/, c, b), a)"));
+        assert_eq!(normal("(&&, a, (&/, c, b))").complexity(), 5);
+        assert_eq!(normal("(--, (--, <a --> b>))"), Parser::parse_term("<a --> b>").unwrap());
+        assert_eq!(normal("(--, (--, (--, a)))"), Parser::parse_term("(--, a)").unwrap());
+        assert_eq!(normal("(&&+5, (&&+2, a, b), c)"), normal("(a &&+2 b &&+5 c)"));
+        assert_eq!(normal("(a &&-1 b)"), Parser::parse_term("(b &/ a)").unwrap());
+        assert_eq!(normal("(&&+1, (&&+1, a, b), c)"), Parser::parse_term("(&&+1, a, b, c)").unwrap());
+        assert_eq!(normal("(a &&+- a)"), Parser::parse_term("(a &&+- a)").unwrap());
+    }
+
+    #[test]
+    fn test_variable_renumbering() {
+        assert_eq!(normal("<$x --> a>"), normal("<$y --> a>"));
+        assert_eq!(normal("<$x --> a>"), Parser::parse_term("<$1 --> a>").unwrap());
+        assert_eq!(normal("<(*, #a, $b) --> ?c>"), Parser::parse_term("<(*, #1, $2) --> ?3>").unwrap());
+        assert_ne!(normal("<$x --> $x>"), normal("<$x --> $y>"));
+        assert_eq!(normal("<@S --> @P>"), Parser::parse_term("<@S --> @P>").unwrap());
+
+        // Alpha-equivalent terms written in a different order agree
+        assert_eq!(
+            normal("(&&, <$x --> a>, <$y --> b>)"),
+            normal("(&&, <$y --> b>, <$x --> a>)"),
+        );
+    }
+}
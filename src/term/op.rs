@@ -0,0 +1,190 @@
+//! Operator metadata
+//!
+//! Every fact about an operator that the parser, printer, normalizer and
+//! term builder rely on lives in one table, indexed by the operator's byte
+//! code, which is the discriminant of `Op`. The byte codes are part of the
+//! binary term format and must never be renumbered; new operators get new
+//! codes.
+
+use super::Op;
+
+/// Static properties of an operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpInfo {
+    /// The operator described
+    pub op: Op,
+
+    /// Canonical Narsese symbol: the copula or connector of a compound, the
+    /// prefix of a variable, empty for the other atomic operators
+    pub symbol: &'static str,
+
+    /// Stable byte code, also the index into the table
+    pub code: u8,
+
+    /// Fewest subterms of a compound, 0 for atomic operators
+    pub min_arity: usize,
+
+    /// Most subterms of a compound, `usize::MAX` when unbounded
+    pub max_arity: usize,
+
+    /// Builds compounds rather than atoms or variables
+    pub compound: bool,
+
+    /// Names a variable kind
+    pub variable: bool,
+
+    /// Subterm order carries no meaning; for temporal-capable operators only
+    /// when eternal or simultaneous
+    pub commutative: bool,
+
+    /// Relates a subject to a predicate, printed `<s op p>`
+    pub statement: bool,
+
+    /// May carry a temporal offset
+    pub temporal: bool,
+
+    /// Extensional or intensional set
+    pub set: bool,
+
+    /// Extensional or intensional image
+    pub image: bool,
+}
+
+const UNBOUNDED: usize = usize::MAX;
+
+const fn atomic(op: Op, code: u8, symbol: &'static str, variable: bool) -> OpInfo {
+    OpInfo {
+        op,
+        symbol,
+        code,
+        min_arity: 0,
+        max_arity: 0,
+        compound: false,
+        variable,
+        commutative: false,
+        statement: false,
+        temporal: false,
+        set: false,
+        image: false,
+    }
+}
+
+const fn compound(op: Op, code: u8, symbol: &'static str, min_arity: usize, max_arity: usize) -> OpInfo {
+    OpInfo {
+        op,
+        symbol,
+        code,
+        min_arity,
+        max_arity,
+        compound: true,
+        variable: false,
+        commutative: false,
+        statement: false,
+        temporal: false,
+        set: false,
+        image: false,
+    }
+}
+
+const fn statement(op: Op, code: u8, symbol: &'static str) -> OpInfo {
+    OpInfo { statement: true, ..compound(op, code, symbol, 2, 2) }
+}
+
+static OPS: [OpInfo; 26] = [
+    atomic(Op::Atom, 0, "", false),
+    atomic(Op::Int, 1, "", false),
+    atomic(Op::Bool, 2, "", false),
+    atomic(Op::VarDep, 3, "#", true),
+    atomic(Op::VarIndep, 4, "$", true),
+    atomic(Op::VarQuery, 5, "?", true),
+    atomic(Op::VarPattern, 6, "@", true),
+    compound(Op::Neg, 7, "--", 1, 1),
+    OpInfo { commutative: true, temporal: true, ..compound(Op::Conjunction, 8, "&&", 1, UNBOUNDED) },
+    OpInfo { commutative: true, ..compound(Op::Disjunction, 9, "||", 1, UNBOUNDED) },
+    OpInfo { commutative: true, ..compound(Op::Intersection, 10, "|", 1, UNBOUNDED) },
+    OpInfo { commutative: true, ..compound(Op::IntersectionExt, 11, "&", 1, UNBOUNDED) },
+    compound(Op::Difference, 12, "~", 2, 2),
+    compound(Op::DifferenceExt, 13, "-", 2, 2),
+    statement(Op::Inheritance, 14, "-->"),
+    OpInfo { commutative: true, ..statement(Op::Similarity, 15, "<->") },
+    OpInfo { temporal: true, ..statement(Op::Implication, 16, "==>") },
+    OpInfo { commutative: true, temporal: true, ..statement(Op::Equivalence, 17, "<=>") },
+    statement(Op::Instance, 18, "{--"),
+    statement(Op::Property, 19, "--]"),
+    statement(Op::InstanceProperty, 20, "{-]"),
+    OpInfo { image: true, ..compound(Op::ImageExt, 21, "/", 2, UNBOUNDED) },
+    OpInfo { image: true, ..compound(Op::ImageInt, 22, "\\", 2, UNBOUNDED) },
+    OpInfo { commutative: true, set: true, ..compound(Op::SetExt, 23, "{}", 1, UNBOUNDED) },
+    OpInfo { commutative: true, set: true, ..compound(Op::SetInt, 24, "[]", 1, UNBOUNDED) },
+    compound(Op::Product, 25, "*", 0, UNBOUNDED),
+];
+
+impl Op {
+    /// Every operator, in byte code order
+    pub fn all() -> impl Iterator<Item = Op> {
+        OPS.iter().map(|info| info.op)
+    }
+
+    /// Get the static properties of the operator
+    pub fn info(self) -> &'static OpInfo {
+        &OPS[self.code() as usize]
+    }
+
+    /// Get the stable byte code of the operator
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Look an operator up by its byte code
+    pub fn from_code(code: u8) -> Option<Op> {
+        OPS.get(code as usize).map(|info| info.op)
+    }
+
+    /// Look an operator up by its canonical Narsese symbol; temporal
+    /// spellings such as `=/>` are not symbols of their own
+    pub fn from_symbol(symbol: &str) -> Option<Op> {
+        if symbol.is_empty() {
+            return None;
+        }
+        OPS.iter().find(|info| info.symbol == symbol).map(|info| info.op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_order() {
+        for (index, info) in OPS.iter().enumerate() {
+            assert_eq!(info.code as usize, index);
+            assert_eq!(info.op.code(), info.code);
+            assert_eq!(Op::from_code(info.code), Some(info.op));
+        }
+        assert_eq!(Op::from_code(OPS.len() as u8), None);
+    }
+
+    #[test]
+    fn test_symbols() {
+        for op in Op::all().filter(|op| !op.info().symbol.is_empty()) {
+            assert_eq!(Op::from_symbol(op.info().symbol), Some(op));
+        }
+        assert_eq!(Op::from_symbol("-->"), Some(Op::Inheritance));
+        assert_eq!(Op::from_symbol("&&"), Some(Op::Conjunction));
+        assert_eq!(Op::from_symbol("&"), Some(Op::IntersectionExt));
+        assert_eq!(Op::from_symbol("/"), Some(Op::ImageExt));
+        assert_eq!(Op::from_symbol("=/>"), None);
+        assert_eq!(Op::from_symbol(""), None);
+    }
+
+    #[test]
+    fn test_properties() {
+        assert!(Op::Similarity.info().statement && Op::Similarity.info().commutative);
+        assert!(!Op::Inheritance.info().commutative);
+        assert!(Op::Conjunction.info().temporal && !Op::Disjunction.info().temporal);
+        assert!(Op::SetInt.info().set && Op::ImageInt.info().image);
+        assert!(Op::VarQuery.info().variable && !Op::VarQuery.info().compound);
+        assert_eq!((Op::Neg.info().min_arity, Op::Neg.info().max_arity), (1, 1));
+        assert_eq!(Op::Product.info().max_arity, usize::MAX);
+    }
+}
@@ -0,0 +1,261 @@
+//! Temporal relations between terms
+//!
+//! `Dt` is the temporal offset of a compound: eternal (no timing at all,
+//! `==>`), unknown (`==>+-`, the events happen at some unspecified distance)
+//! or a concrete offset (`==>+5`). Only `==>`, `<=>` and `&&` carry one.
+//!
+//! A conjunction with an offset is a sequence of events. `(&&+2, a, b, c)`
+//! spaces its members evenly, each starting 2 after the previous one ends;
+//! sequences with uneven gaps nest, `(&&+5, (&&+2, a, b), c)` puts `a` at 0,
+//! `b` at 2 and `c` at 7. `Sequence` lists the events of such a term with
+//! their offsets from its first event and builds the canonical term back:
+//! events at the same time joined by `&|`, one flat conjunction when the
+//! gaps are even, nested conjunctions otherwise.
+
+use super::compound::Compound;
+use super::{Op, Term, TermTrait};
+use std::fmt;
+
+/// Temporal offset of a compound
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dt {
+    /// No timing, the relation holds regardless of when
+    #[default]
+    Eternal,
+
+    /// Timed, but the offset is unknown
+    Xternal,
+
+    /// Time from the end of one subterm to the start of the next
+    Offset(i32),
+}
+
+impl Dt {
+    /// Get the concrete offset, if any
+    pub fn offset(self) -> Option<i32> {
+        match self {
+            Dt::Offset(dt) => Some(dt),
+            _ => None,
+        }
+    }
+
+    /// Check if there is no timing
+    pub fn is_eternal(self) -> bool {
+        self == Dt::Eternal
+    }
+
+    /// Check if the offset is unknown
+    pub fn is_xternal(self) -> bool {
+        self == Dt::Xternal
+    }
+
+    /// Check if the order of the subterms carries no meaning: eternal,
+    /// unknown or simultaneous
+    pub fn is_unordered(self) -> bool {
+        matches!(self, Dt::Eternal | Dt::Xternal | Dt::Offset(0))
+    }
+
+    /// Check if a pattern offset admits another; an unknown offset admits
+    /// any timed one
+    pub fn matches(self, other: Dt) -> bool {
+        self == other || (self.is_xternal() && !other.is_eternal()) || (other.is_xternal() && !self.is_eternal())
+    }
+
+    /// Offset clamped from a time difference
+    pub fn from_time(dt: i64) -> Dt {
+        Dt::Offset(dt.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+}
+
+/// Printed as the suffix of a temporal operator: nothing, `+-`, `+5` or `-3`
+impl fmt::Display for Dt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dt::Eternal => Ok(()),
+            Dt::Xternal => write!(f, "+-"),
+            Dt::Offset(dt) => write!(f, "{:+}", dt),
+        }
+    }
+}
+
+/// Events with their start offsets, ordered by time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sequence {
+    events: Vec<(i64, Term)>,
+}
+
+impl Sequence {
+    /// Create an empty sequence
+    pub fn new() -> Self {
+        Sequence::default()
+    }
+
+    /// Get the events of a term; anything but a conjunction with an offset
+    /// is a single event
+    pub fn of(term: &Term) -> Self {
+        let mut sequence = Sequence::new();
+        sequence.add(0, term);
+        sequence
+    }
+
+    /// Add the events of a term, its first event at an offset
+    pub fn add(&mut self, at: i64, term: &Term) {
+        match term {
+            Term::Compound(compound) if compound.op_id() == Op::Conjunction => match compound.dt() {
+                Dt::Offset(dt) => {
+                    let mut parts = Vec::with_capacity(compound.len());
+                    let mut cursor = 0i64;
+                    for subterm in compound.subterms() {
+                        let part = Sequence::of(subterm);
+                        let range = part.event_range();
+                        parts.push((cursor, part));
+                        cursor += range + dt as i64;
+                    }
+                    // A negative offset puts later members first
+                    let first = parts.iter().map(|(start, _)| *start).min().unwrap_or(0);
+                    for (start, part) in parts {
+                        for (offset, event) in part.events {
+                            self.insert(at + start - first + offset, event);
+                        }
+                    }
+                },
+                _ => self.insert(at, term.clone()),
+            },
+            _ => self.insert(at, term.clone()),
+        }
+    }
+
+    /// Add the events of a term after the last event, with a gap
+    pub fn then(&mut self, gap: i64, term: &Term) {
+        let at = self.events.last().map_or(0, |(offset, _)| offset + gap);
+        self.add(at, term);
+    }
+
+    /// Get the events in time order
+    pub fn events(&self) -> &[(i64, Term)] {
+        &self.events
+    }
+
+    /// Time from the first event to the last
+    pub fn event_range(&self) -> i64 {
+        match (self.events.first(), self.events.last()) {
+            (Some((first, _)), Some((last, _))) => last - first,
+            _ => 0,
+        }
+    }
+
+    /// The same events, all moved by a time difference
+    pub fn shift(&self, delta: i64) -> Self {
+        Sequence { events: self.events.iter().map(|(offset, event)| (offset + delta, event.clone())).collect() }
+    }
+
+    /// Build the canonical term for the events, or nothing if there are none
+    pub fn to_term(&self) -> Option<Term> {
+        let mut groups: Vec<(i64, Vec<Term>)> = Vec::new();
+        for (offset, event) in &self.events {
+            match groups.last_mut() {
+                Some((at, members)) if at == offset => members.push(event.clone()),
+                _ => groups.push((*offset, vec![event.clone()])),
+            }
+        }
+        let mut groups = groups.into_iter().map(|(offset, mut members)| {
+            let event = match members.len() {
+                1 => members.remove(0),
+                _ => Term::Compound(Compound::new_temporal(Op::Conjunction, members, 0)),
+            };
+            (offset, event)
+        });
+
+        let (mut last, first) = groups.next()?;
+        let mut members = vec![first];
+        let mut gaps = Vec::new();
+        for (offset, event) in groups {
+            gaps.push(offset - last);
+            members.push(event);
+            last = offset;
+        }
+        if gaps.is_empty() {
+            return members.pop();
+        }
+        if gaps.iter().all(|gap| *gap == gaps[0]) {
+            return Some(Term::Compound(Compound::new_dt(Op::Conjunction, members, Dt::from_time(gaps[0]))));
+        }
+        let mut members = members.into_iter();
+        let first = members.next()?;
+        Some(members.zip(gaps).fold(first, |sequence, (event, gap)| {
+            Term::Compound(Compound::new_dt(Op::Conjunction, vec![sequence, event], Dt::from_time(gap)))
+        }))
+    }
+
+    fn insert(&mut self, offset: i64, event: Term) {
+        let position = self.events.partition_point(|(other, _)| *other <= offset);
+        self.events.insert(position, (offset, event));
+    }
+}
+
+impl Term {
+    /// Get the events of the term with their offsets from the first one
+    pub fn events(&self) -> Vec<(i64, Term)> {
+        Sequence::of(self).events
+    }
+
+    /// Time from the first event of the term to its last
+    pub fn event_range(&self) -> i64 {
+        Sequence::of(self).event_range()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    fn offsets(narsese: &str) -> Vec<(i64, String)> {
+        term(narsese).events().into_iter().map(|(offset, event)| (offset, event.to_string())).collect()
+    }
+
+    #[test]
+    fn test_dt() {
+        assert!(Dt::Eternal.is_unordered() && Dt::Offset(0).is_unordered());
+        assert!(!Dt::Offset(3).is_unordered());
+        assert!(Dt::Xternal.matches(Dt::Offset(3)));
+        assert!(!Dt::Xternal.matches(Dt::Eternal));
+        assert!(!Dt::Offset(2).matches(Dt::Offset(3)));
+        assert_eq!(format!("==>{}", Dt::Offset(-3)), "==>-3");
+        assert_eq!(format!("==>{}", Dt::Xternal), "==>+-");
+        assert_eq!(Dt::from_time(i64::MAX), Dt::Offset(i32::MAX));
+    }
+
+    #[test]
+    fn test_events() {
+        assert_eq!(offsets("a"), vec![(0, "a".to_string())]);
+        assert_eq!(offsets("(&&+2, a, b, c)"), vec![(0, "a".into()), (2, "b".into()), (4, "c".into())]);
+        assert_eq!(offsets("(&&+5, (&&+2, a, b), c)"), vec![(0, "a".into()), (2, "b".into()), (7, "c".into())]);
+        assert_eq!(offsets("(a &&-3 b)"), vec![(0, "b".into()), (3, "a".into())]);
+        assert_eq!(offsets("(a &| b)").len(), 2);
+        assert_eq!(offsets("(a &&+- b)").len(), 1);
+        assert_eq!(term("(&&+5, (&&+2, a, b), c)").event_range(), 7);
+        assert_eq!(term("(a && b)").event_range(), 0);
+    }
+
+    #[test]
+    fn test_to_term() {
+        let mut sequence = Sequence::new();
+        sequence.add(0, &term("a"));
+        sequence.then(2, &term("b"));
+        sequence.then(5, &term("c"));
+        assert_eq!(sequence.to_term(), Some(term("(&&+5, (&&+2, a, b), c)")));
+        assert_eq!(Sequence::of(&term("(&&+2, a, b, c)")).to_term(), Some(term("(&&+2, a, b, c)")));
+        assert_eq!(Sequence::of(&term("(a &&-3 b)")).to_term(), Some(term("(b &&+3 a)")));
+        assert_eq!(Sequence::of(&term("((a &| b) &/ c)")).to_term(), Some(term("((a &| b) &/ c)")));
+        assert_eq!(Sequence::new().to_term(), None);
+
+        let shifted = Sequence::of(&term("(a &/ b)")).shift(10);
+        assert_eq!(shifted.events()[0].0, 10);
+        assert_eq!(shifted.event_range(), 1);
+    }
+}
@@ -0,0 +1,346 @@
+//! Unification of terms containing variables
+//!
+//! Two terms unify when some substitution of their variables makes them
+//! equal. Only the variable kinds named in a `VarTypes` set may be bound; any
+//! other variable behaves like a constant. Variables on either side can be
+//! bound, subterms of commutative compounds may pair up in any order, and a
+//! variable is never bound to a term containing itself (occurs check).
+//! When a choice made for one subterm of a commutative compound fails later
+//! on, the search backtracks and tries the next pairing.
+
+use super::var::Variable;
+use super::{Op, Term, TermTrait};
+use bitflags::bitflags;
+use std::fmt;
+
+bitflags! {
+    /// Variable kinds that unification may bind
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct VarTypes: u8 {
+        /// Dependent variables, `#x`
+        const DEP = 1;
+        /// Independent variables, `$x`
+        const INDEP = 1 << 1;
+        /// Query variables, `?x`
+        const QUERY = 1 << 2;
+        /// Pattern variables, `@x`
+        const PATTERN = 1 << 3;
+    }
+}
+
+impl VarTypes {
+    /// The kind of a variable operator, or no kinds for other operators
+    pub fn of(op: Op) -> Self {
+        match op {
+            Op::VarDep => VarTypes::DEP,
+            Op::VarIndep => VarTypes::INDEP,
+            Op::VarQuery => VarTypes::QUERY,
+            Op::VarPattern => VarTypes::PATTERN,
+            _ => VarTypes::empty(),
+        }
+    }
+
+    /// Check if a variable may be bound
+    pub fn binds(self, variable: &Variable) -> bool {
+        self.intersects(VarTypes::of(variable.op_id()))
+    }
+}
+
+/// Variable bindings produced by unification
+///
+/// A variable may be bound to a term that itself contains bound variables;
+/// `Term::apply` resolves the whole chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Subst {
+    bindings: Vec<(Variable, Term)>,
+}
+
+impl Subst {
+    /// Create an empty substitution
+    pub fn new() -> Self {
+        Subst::default()
+    }
+
+    /// Get the term a variable is bound to
+    pub fn get(&self, variable: &Variable) -> Option<&Term> {
+        self.bindings.iter().find(|(v, _)| v == variable).map(|(_, term)| term)
+    }
+
+    /// Bind a variable, replacing any earlier binding
+    pub fn bind(&mut self, variable: Variable, term: Term) {
+        match self.bindings.iter_mut().find(|(v, _)| *v == variable) {
+            Some(binding) => binding.1 = term,
+            None => self.bindings.push((variable, term)),
+        }
+    }
+
+    /// Get the bindings in the order they were made
+    pub fn iter(&self) -> impl Iterator<Item = (&Variable, &Term)> {
+        self.bindings.iter().map(|(v, term)| (v, term))
+    }
+
+    /// Get the number of bound variables
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    /// Check if no variable is bound
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// Undo the bindings made after the substitution had `len` entries
+    fn truncate(&mut self, len: usize) {
+        self.bindings.truncate(len);
+    }
+
+    /// Follow variable bindings until reaching an unbound variable or a
+    /// non-variable term
+    fn resolve<'a>(&'a self, mut term: &'a Term) -> &'a Term {
+        while let Term::Variable(variable) = term {
+            match self.get(variable) {
+                Some(bound) => term = bound,
+                None => break,
+            }
+        }
+        term
+    }
+}
+
+impl fmt::Display for Subst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (variable, term)) in self.bindings.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", variable, term)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Unify two terms, binding variables of the given kinds; returns the first
+/// substitution found
+pub fn unify(a: &Term, b: &Term, var_types: VarTypes) -> Option<Subst> {
+    let mut found = None;
+    Unifier { var_types }.solve(vec![Goal::Pair(a.clone(), b.clone())], &mut Subst::new(), &mut |subst| {
+        found = Some(subst.clone());
+        true
+    });
+    found
+}
+
+/// Every distinct substitution unifying two terms, in search order
+pub fn unify_all(a: &Term, b: &Term, var_types: VarTypes) -> Vec<Subst> {
+    let mut found: Vec<Subst> = Vec::new();
+    Unifier { var_types }.solve(vec![Goal::Pair(a.clone(), b.clone())], &mut Subst::new(), &mut |subst| {
+        if !found.contains(subst) {
+            found.push(subst.clone());
+        }
+        false
+    });
+    found
+}
+
+/// Unify several pairs of terms under one substitution, calling `found`
+/// with every substitution in search order until it returns true; returns
+/// whether it did
+pub fn unify_pairs(pairs: &[(&Term, &Term)], var_types: VarTypes, found: &mut dyn FnMut(&Subst) -> bool) -> bool {
+    // Goals are taken from the back, so the first pair is solved first
+    let goals = pairs.iter().rev().map(|(a, b)| Goal::Pair((*a).clone(), (*b).clone())).collect();
+    Unifier { var_types }.solve(goals, &mut Subst::new(), found)
+}
+
+/// Replace bound variables throughout a term
+pub(crate) fn apply(term: &Term, subst: &Subst) -> Term {
+    match term {
+        Term::Variable(variable) => match subst.get(variable) {
+            // The occurs check keeps binding chains acyclic
+            Some(bound) => apply(bound, subst),
+            None => term.clone(),
+        },
+        Term::Compound(compound) => {
+            let subterms = compound.subterms().iter().map(|t| apply(t, subst)).collect();
+            Term::Compound(compound.with_subterms(subterms))
+        },
+        Term::Atomic(_) => term.clone(),
+    }
+}
+
+/// Outstanding unification work
+#[derive(Clone)]
+enum Goal {
+    /// Two terms that must unify
+    Pair(Term, Term),
+
+    /// Subterms of two commutative compounds that must unify in some pairing
+    Unordered(Vec<Term>, Vec<Term>),
+}
+
+struct Unifier {
+    var_types: VarTypes,
+}
+
+impl Unifier {
+    /// Work through the goals depth first, calling `found` with every
+    /// complete substitution until it returns true; returns whether it did
+    fn solve(&self, mut goals: Vec<Goal>, subst: &mut Subst, found: &mut dyn FnMut(&Subst) -> bool) -> bool {
+        let Some(goal) = goals.pop() else {
+            return found(subst);
+        };
+        match goal {
+            Goal::Pair(a, b) => {
+                let a = subst.resolve(&a).clone();
+                let b = subst.resolve(&b).clone();
+                if a == b {
+                    return self.solve(goals, subst, found);
+                }
+                match (&a, &b) {
+                    (Term::Variable(variable), other) | (other, Term::Variable(variable))
+                        if self.var_types.binds(variable) =>
+                    {
+                        if occurs(variable, other, subst) {
+                            return false;
+                        }
+                        let mark = subst.len();
+                        subst.bind(variable.clone(), other.clone());
+                        let stop = self.solve(goals, subst, found);
+                        subst.truncate(mark);
+                        stop
+                    },
+                    (Term::Compound(x), Term::Compound(y)) => {
+                        if x.op_id() != y.op_id() || !x.dt().matches(y.dt()) || x.len() != y.len() {
+                            return false;
+                        }
+                        if x.is_commutative() {
+                            goals.push(Goal::Unordered(x.subterms().to_vec(), y.subterms().to_vec()));
+                        } else {
+                            goals.extend(
+                                x.subterms().iter().zip(y.subterms()).rev().map(|(p, q)| Goal::Pair(p.clone(), q.clone())),
+                            );
+                        }
+                        self.solve(goals, subst, found)
+                    },
+                    _ => false,
+                }
+            },
+            Goal::Unordered(mut xs, ys) => {
+                let Some(x) = xs.pop() else {
+                    return self.solve(goals, subst, found);
+                };
+                for j in 0..ys.len() {
+                    // Identical partners leave the same alternatives behind
+                    if ys[..j].contains(&ys[j]) {
+                        continue;
+                    }
+                    let mut rest = ys.clone();
+                    let y = rest.remove(j);
+                    let mut branch: Vec<Goal> = goals.clone();
+                    branch.push(Goal::Unordered(xs.clone(), rest));
+                    branch.push(Goal::Pair(x.clone(), y));
+                    let mark = subst.len();
+                    let stop = self.solve(branch, subst, found);
+                    subst.truncate(mark);
+                    if stop {
+                        return true;
+                    }
+                }
+                false
+            },
+        }
+    }
+}
+
+/// Check if a variable appears in a term once bindings are followed
+fn occurs(variable: &Variable, term: &Term, subst: &Subst) -> bool {
+    match subst.resolve(term) {
+        Term::Variable(other) => other == variable,
+        Term::Compound(compound) => compound.subterms().iter().any(|t| occurs(variable, t, subst)),
+        Term::Atomic(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    #[test]
+    fn test_unify_query() {
+        let subst = unify(&term("<?x --> animal>"), &term("<bird --> animal>"), VarTypes::QUERY).unwrap();
+        assert_eq!(subst.get(&Variable::new_query("x")), Some(&term("bird")));
+        assert_eq!(term("<?x --> animal>").apply(&subst), term("<bird --> animal>"));
+
+        // Variables of other kinds are constants
+        assert!(unify(&term("<?x --> animal>"), &term("<bird --> animal>"), VarTypes::DEP).is_none());
+        assert!(unify(&term("<?x --> animal>"), &term("<bird <-> animal>"), VarTypes::all()).is_none());
+    }
+
+    #[test]
+    fn test_unify_both_sides() {
+        let a = term("<$x --> (*, b, #y)>");
+        let b = term("<a --> (*, #z, c)>");
+        let subst = unify(&a, &b, VarTypes::INDEP | VarTypes::DEP).unwrap();
+        assert_eq!(a.apply(&subst), b.apply(&subst));
+        assert_eq!(a.apply(&subst), term("<a --> (*, b, c)>"));
+
+        // The same variable must be bound consistently
+        assert!(unify(&term("(*, $x, $x)"), &term("(*, a, b)"), VarTypes::INDEP).is_none());
+        let subst = unify(&term("(*, $x, $x)"), &term("(*, $y, a)"), VarTypes::INDEP).unwrap();
+        assert_eq!(term("$y").apply(&subst), term("a"));
+    }
+
+    #[test]
+    fn test_occurs_check() {
+        assert!(unify(&term("#x"), &term("(*, #x, a)"), VarTypes::DEP).is_none());
+        assert!(unify(&term("(*, #x, #y)"), &term("(*, #y, (&&, #x, a))"), VarTypes::DEP).is_none());
+    }
+
+    #[test]
+    fn test_commutative_backtracking() {
+        // Binding $y to flyer first leaves nothing for <$x --> flyer> and
+        // must be undone
+        let a = term("(&&, <$x --> flyer>, <$x --> $y>)");
+        let b = term("(&&, <tweety --> flyer>, <tweety --> bird>)");
+        let subst = unify(&a, &b, VarTypes::INDEP).unwrap();
+        assert_eq!(subst.get(&Variable::new_indep("y")), Some(&term("bird")));
+        assert_eq!(a.apply(&subst), term("(&&, <tweety --> flyer>, <tweety --> bird>)"));
+
+        let a = term("{#x, #y}");
+        let b = term("{a, b}");
+        let all = unify_all(&a, &b, VarTypes::DEP);
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|subst| a.apply(subst) == b || a.apply(subst) == term("{b, a}")));
+
+        // Order matters in a sequence
+        assert!(unify(&term("(&/, #x, b)"), &term("(&/, b, a)"), VarTypes::DEP).is_none());
+    }
+
+    #[test]
+    fn test_unify_pairs() {
+        // Bindings are shared across the pairs, and a failure in the second
+        // pair sends the search back into the first
+        let (a, b) = (term("{@x, @y}"), term("<@y --> c>"));
+        let (x, y) = (term("{p, q}"), term("<p --> c>"));
+        let mut found = Vec::new();
+        unify_pairs(&[(&a, &x), (&b, &y)], VarTypes::PATTERN, &mut |subst| {
+            found.push(subst.clone());
+            false
+        });
+        assert_eq!(found.len(), 1);
+        assert_eq!(term("@x").apply(&found[0]), term("q"));
+    }
+
+    #[test]
+    fn test_subst_display() {
+        let mut subst = Subst::new();
+        subst.bind(Variable::new_query("x"), term("bird"));
+        assert_eq!(subst.to_string(), "{?x: bird}");
+        assert_eq!(subst.len(), 1);
+    }
+}
@@ -32,7 +32,7 @@ impl Variable {
             SmartString::from(format!("#{}", name))
         };
         Variable {
-            name: full_name.into(),
+            name: full_name,
             var_type: Op::VarDep,
         }
     }
@@ -45,7 +45,7 @@ impl Variable {
             SmartString::from(format!("${}", name))
         };
         Variable {
-            name: full_name.into(),
+            name: full_name,
             var_type: Op::VarIndep,
         }
     }
@@ -58,7 +58,7 @@ impl Variable {
             SmartString::from(format!("?{}", name))
         };
         Variable {
-            name: full_name.into(),
+            name: full_name,
             var_type: Op::VarQuery,
         }
     }
@@ -71,7 +71,7 @@ impl Variable {
             SmartString::from(format!("@{}", name))
         };
         Variable {
-            name: full_name.into(),
+            name: full_name,
             var_type: Op::VarPattern,
         }
     }
@@ -0,0 +1,285 @@
+//! Walking, searching and rewriting terms
+//!
+//! A subterm is addressed by its path, the subterm indices leading to it
+//! from the root; the root itself has the empty path. `dfs` and `bfs` list
+//! every subterm with its path. `walk` and `transform` hand each subterm to
+//! a `TermVisitor`, which decides whether to look inside it, replace it or
+//! stop altogether. Rewritten compounds keep their operator and offset and
+//! are not reduced; pass the result through `TermBuilder::rebuild` for that.
+
+use super::Term;
+use std::collections::VecDeque;
+
+/// What to do after visiting a subterm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visit {
+    /// Go on into the subterms
+    Descend,
+
+    /// Leave the subterms alone and go on with the next sibling
+    Skip,
+
+    /// Put this term in place of the subterm, without visiting it; a walk
+    /// treats it like `Skip`
+    Replace(Term),
+
+    /// Visit nothing more and keep everything not yet visited unchanged
+    Stop,
+}
+
+/// Decides, subterm by subterm, how a walk or transform proceeds
+pub trait TermVisitor {
+    /// Visit a subterm, parents before their subterms
+    fn visit(&mut self, path: &[usize], term: &Term) -> Visit;
+}
+
+impl<F: FnMut(&[usize], &Term) -> Visit> TermVisitor for F {
+    fn visit(&mut self, path: &[usize], term: &Term) -> Visit {
+        self(path, term)
+    }
+}
+
+/// Subterms in depth-first order, parents before their subterms
+pub struct Dfs<'a> {
+    pending: Vec<(Vec<usize>, &'a Term)>,
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = (Vec<usize>, &'a Term);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, term) = self.pending.pop()?;
+        if let Term::Compound(compound) = term {
+            for (index, subterm) in compound.subterms().iter().enumerate().rev() {
+                self.pending.push((child(&path, index), subterm));
+            }
+        }
+        Some((path, term))
+    }
+}
+
+/// Subterms in breadth-first order, level by level
+pub struct Bfs<'a> {
+    pending: VecDeque<(Vec<usize>, &'a Term)>,
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = (Vec<usize>, &'a Term);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, term) = self.pending.pop_front()?;
+        if let Term::Compound(compound) = term {
+            for (index, subterm) in compound.subterms().iter().enumerate() {
+                self.pending.push_back((child(&path, index), subterm));
+            }
+        }
+        Some((path, term))
+    }
+}
+
+fn child(path: &[usize], index: usize) -> Vec<usize> {
+    let mut path = path.to_vec();
+    path.push(index);
+    path
+}
+
+impl Term {
+    /// Every subterm with its path, the term itself first, depth first
+    pub fn dfs(&self) -> Dfs<'_> {
+        Dfs { pending: vec![(Vec::new(), self)] }
+    }
+
+    /// Every subterm with its path, the term itself first, breadth first
+    pub fn bfs(&self) -> Bfs<'_> {
+        Bfs { pending: VecDeque::from([(Vec::new(), self)]) }
+    }
+
+    /// Get the subterm at a path
+    pub fn sub_at_path(&self, path: &[usize]) -> Option<&Term> {
+        path.iter().try_fold(self, |term, &index| match term {
+            Term::Compound(compound) => compound.get(index),
+            _ => None,
+        })
+    }
+
+    /// Get the path of the first occurrence of a term, depth first; the
+    /// term itself is found at the empty path
+    pub fn path_to(&self, target: &Term) -> Option<Vec<usize>> {
+        let mut found = None;
+        self.walk(&mut |path: &[usize], term: &Term| {
+            if term == target {
+                found = Some(path.to_vec());
+                Visit::Stop
+            } else {
+                Visit::Descend
+            }
+        });
+        found
+    }
+
+    /// Check if a term occurs anywhere below this one
+    pub fn contains_recursively(&self, target: &Term) -> bool {
+        self.dfs().skip(1).any(|(_, term)| term == target)
+    }
+
+    /// Visit the subterms depth first; returns false if the visitor stopped
+    /// the walk
+    pub fn walk(&self, visitor: &mut impl TermVisitor) -> bool {
+        walk(self, &mut Vec::new(), visitor)
+    }
+
+    /// Rebuild the term with the replacements a visitor asks for
+    pub fn transform(&self, visitor: &mut impl TermVisitor) -> Term {
+        transform(self, &mut Vec::new(), visitor, &mut false)
+    }
+
+    /// Replace every occurrence of one term by another; occurrences inside
+    /// the replacement are not replaced again
+    pub fn replace(&self, from: &Term, to: &Term) -> Term {
+        self.transform(&mut |_: &[usize], term: &Term| {
+            if term == from {
+                Visit::Replace(to.clone())
+            } else {
+                Visit::Descend
+            }
+        })
+    }
+
+    /// Replace every atom and variable by the result of a function
+    pub fn map_atoms(&self, mut f: impl FnMut(&Term) -> Term) -> Term {
+        self.transform(&mut |_: &[usize], term: &Term| match term {
+            Term::Compound(_) => Visit::Descend,
+            _ => Visit::Replace(f(term)),
+        })
+    }
+}
+
+fn walk(term: &Term, path: &mut Vec<usize>, visitor: &mut impl TermVisitor) -> bool {
+    match visitor.visit(path, term) {
+        Visit::Stop => false,
+        Visit::Skip | Visit::Replace(_) => true,
+        Visit::Descend => {
+            let Term::Compound(compound) = term else {
+                return true;
+            };
+            for (index, subterm) in compound.subterms().iter().enumerate() {
+                path.push(index);
+                let go_on = walk(subterm, path, visitor);
+                path.pop();
+                if !go_on {
+                    return false;
+                }
+            }
+            true
+        },
+    }
+}
+
+fn transform(term: &Term, path: &mut Vec<usize>, visitor: &mut impl TermVisitor, stopped: &mut bool) -> Term {
+    if *stopped {
+        return term.clone();
+    }
+    match visitor.visit(path, term) {
+        Visit::Replace(replacement) => replacement,
+        Visit::Skip => term.clone(),
+        Visit::Stop => {
+            *stopped = true;
+            term.clone()
+        },
+        Visit::Descend => {
+            let Term::Compound(compound) = term else {
+                return term.clone();
+            };
+            let mut changed = false;
+            let mut subterms = Vec::with_capacity(compound.len());
+            for (index, subterm) in compound.subterms().iter().enumerate() {
+                path.push(index);
+                let rewritten = transform(subterm, path, visitor, stopped);
+                path.pop();
+                changed |= rewritten != *subterm;
+                subterms.push(rewritten);
+            }
+            if changed {
+                Term::Compound(compound.with_subterms(subterms))
+            } else {
+                term.clone()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::term::atom::Atomic;
+
+    fn term(narsese: &str) -> Term {
+        Parser::parse_term(narsese).unwrap()
+    }
+
+    #[test]
+    fn test_traversal_order() {
+        let t = term("<(*, a, b) --> c>");
+        let dfs: Vec<String> = t.dfs().map(|(_, t)| t.to_string()).collect();
+        let bfs: Vec<Vec<usize>> = t.bfs().map(|(path, _)| path).collect();
+        assert_eq!(dfs.len(), 5);
+        assert_eq!(dfs[1], term("(*, a, b)").to_string());
+        assert_eq!(dfs[2], "a");
+        assert_eq!(bfs, vec![vec![], vec![0], vec![1], vec![0, 0], vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_search() {
+        let t = term("<(*, a, b) --> (&&, c, b)>");
+        assert_eq!(t.path_to(&term("b")), Some(vec![0, 1]));
+        assert_eq!(t.path_to(&t), Some(vec![]));
+        assert_eq!(t.path_to(&term("d")), None);
+        assert_eq!(t.sub_at_path(&[1, 0]), Some(&term("c")));
+        assert_eq!(t.sub_at_path(&[0, 0, 0]), None);
+        assert!(t.contains_recursively(&term("c")));
+        assert!(!t.contains_recursively(&t));
+    }
+
+    #[test]
+    fn test_walk_stops() {
+        // The inner product is skipped, so the walk sees the root, a, the
+        // inner product and d, then stops before e
+        let t = term("(*, a, (*, b, c), d, e)");
+        let mut seen = Vec::new();
+        let finished = t.walk(&mut |path: &[usize], sub: &Term| {
+            seen.push(sub.clone());
+            match sub {
+                Term::Compound(_) if !path.is_empty() => Visit::Skip,
+                _ if *sub == term("d") => Visit::Stop,
+                _ => Visit::Descend,
+            }
+        });
+        assert!(!finished);
+        assert_eq!(seen.len(), 4);
+        assert!(t.walk(&mut |_: &[usize], _: &Term| Visit::Descend));
+    }
+
+    #[test]
+    fn test_replace_and_map() {
+        let t = term("<(*, a, b) --> (&&, a, c)>");
+        assert_eq!(t.replace(&term("a"), &term("x")), term("<(*, x, b) --> (&&, x, c)>"));
+        assert_eq!(t.replace(&term("(*, a, b)"), &term("a")), term("<a --> (&&, a, c)>"));
+        assert_eq!(term("(&/, a, b)").replace(&term("b"), &term("c")), term("(&/, a, c)"));
+
+        let upper = t.map_atoms(|atom| Term::Atomic(Atomic::new_atom(&atom.to_string().to_uppercase())));
+        assert_eq!(upper, term("<(*, A, B) --> (&&, A, C)>"));
+
+        // Stopping keeps the rest as it was
+        let mut count = 0;
+        let first = t.transform(&mut |_: &[usize], sub: &Term| match sub {
+            Term::Compound(_) => Visit::Descend,
+            _ if count == 1 => Visit::Stop,
+            _ => {
+                count += 1;
+                Visit::Replace(term("z"))
+            },
+        });
+        assert_eq!(first, term("<(*, z, b) --> (&&, a, c)>"));
+    }
+}
@@ -0,0 +1,12 @@
+//! Test support for reasoner behaviour
+//!
+//! This module runs the NAL conformance scripts shipped in `njava/docs/nal`
+//! against the Rust reasoner, so that parity with the Java implementation can
+//! be tracked from `cargo test`, and provides `TestNAR` for writing
+//! regression tests of individual inference rules.
+
+pub mod script;
+pub mod test_nar;
+
+pub use script::{Expectation, ExpectationResult, Outcome, Script, ScriptReport, ScriptRunner, Step};
+pub use test_nar::{TestNAR, TestReport};
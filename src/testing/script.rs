@@ -0,0 +1,409 @@
+//! `.nal` test scripts
+//!
+//! The OpenNARS conformance scripts under `njava/docs/nal` are plain text:
+//!
+//! - `'` starts a comment
+//! - a bare integer runs that many cycles
+//! - `''outputMustContain('...')` and `''outputMustNotContain('...')` state
+//!   expectations on the derived output
+//! - every other non-blank line is a Narsese sentence to input
+//!
+//! As in OpenNARS, expectations are checked against everything the reasoner
+//! derived while the whole script ran, not only the output before them.
+
+use crate::nal::NAR;
+use crate::parser::{ParseError, Parser, Sentence};
+use crate::task::Task;
+use parking_lot::Mutex;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// One instruction of a script
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Narsese sentence to input
+    Input(String),
+
+    /// Number of reasoning cycles to run
+    Cycles(usize),
+
+    /// Expectation on the derived output
+    Expect(Expectation),
+}
+
+/// An `outputMustContain` or `outputMustNotContain` check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expectation {
+    /// The sentence as written in the script
+    pub text: String,
+
+    /// Whether the sentence must be absent rather than present
+    pub negated: bool,
+}
+
+impl Expectation {
+    /// Whether a derived task satisfies the expected sentence; the truth value
+    /// is compared within `tolerance` and only when the expectation has one
+    fn matches(sentence: &Sentence, task: &Task, tolerance: f32) -> bool {
+        let (term, truth, punctuation, _) = sentence;
+        if task.term() != term || task.punctuation() != *punctuation {
+            return false;
+        }
+        match (truth, task.truth()) {
+            (None, _) => true,
+            (Some(expected), Some(actual)) =>
+                (expected.frequency() - actual.frequency()).abs() <= tolerance
+                    && (expected.confidence() - actual.confidence()).abs() <= tolerance,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// A parsed `.nal` script
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    name: String,
+    steps: Vec<(usize, Step)>,
+}
+
+impl Script {
+    /// Parse a script from its source text
+    pub fn parse(name: &str, source: &str) -> Self {
+        let steps = source
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| Script::parse_line(line.trim()).map(|step| (i + 1, step)))
+            .collect();
+        Script { name: name.to_string(), steps }
+    }
+
+    /// Load a script from a file, named after the file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(Script::parse(&name, &source))
+    }
+
+    fn parse_line(line: &str) -> Option<Step> {
+        if let Some(directive) = line.strip_prefix("''") {
+            let (negated, rest) = if let Some(rest) = directive.strip_prefix("outputMustContain(") {
+                (false, rest)
+            } else if let Some(rest) = directive.strip_prefix("outputMustNotContain(") {
+                (true, rest)
+            } else {
+                // Other embedded script code is not supported
+                return None;
+            };
+            let text = rest.strip_suffix(')')?.trim();
+            let text = text.strip_prefix('\'')?.strip_suffix('\'')?;
+            return Some(Step::Expect(Expectation { text: text.to_string(), negated }));
+        }
+        if line.is_empty() || line.starts_with('\'') || line.starts_with("//") || line.starts_with('*') {
+            return None;
+        }
+        // Transcript lines of older scripts: `IN:` input and `OUT:` expected output
+        if let Some(input) = line.strip_prefix("IN:") {
+            return Some(Step::Input(input.trim().to_string()));
+        }
+        if let Some(output) = line.strip_prefix("OUT:") {
+            return Some(Step::Expect(Expectation { text: output.trim().to_string(), negated: false }));
+        }
+        match line.parse::<usize>() {
+            Ok(cycles) => Some(Step::Cycles(cycles)),
+            Err(_) => Some(Step::Input(line.to_string())),
+        }
+    }
+
+    /// Get the script name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the steps with their one-based line numbers
+    pub fn steps(&self) -> &[(usize, Step)] {
+        &self.steps
+    }
+
+    /// Number of expectations in the script
+    pub fn expectation_count(&self) -> usize {
+        self.steps.iter().filter(|(_, step)| matches!(step, Step::Expect(_))).count()
+    }
+}
+
+/// Runs scripts against fresh reasoners
+#[derive(Debug, Clone)]
+pub struct ScriptRunner {
+    /// Allowed difference in frequency and confidence
+    truth_tolerance: f32,
+
+    /// Upper bound on the cycles run for any single cycle step
+    max_cycles: Option<usize>,
+}
+
+impl ScriptRunner {
+    /// Create a runner with the default truth tolerance of 0.01
+    pub fn new() -> Self {
+        ScriptRunner {
+            truth_tolerance: 0.01,
+            max_cycles: None,
+        }
+    }
+
+    /// Set the allowed difference in frequency and confidence
+    pub fn truth_tolerance(mut self, tolerance: f32) -> Self {
+        self.truth_tolerance = tolerance;
+        self
+    }
+
+    /// Cap the cycles run for any single cycle step
+    pub fn max_cycles(mut self, cycles: usize) -> Self {
+        self.max_cycles = Some(cycles);
+        self
+    }
+
+    /// Run a script on a new `NAR`
+    pub fn run(&self, script: &Script) -> ScriptReport {
+        self.run_with(script, NAR::new())
+    }
+
+    /// Run a script on the given `NAR`
+    pub fn run_with(&self, script: &Script, mut nar: NAR) -> ScriptReport {
+        let derived = Arc::new(Mutex::new(Vec::new()));
+        let sink = derived.clone();
+        nar.on_derived(move |task| sink.lock().push(task.clone()));
+
+        let mut input_errors = Vec::new();
+        let mut expectations = Vec::new();
+        for (line, step) in script.steps() {
+            match step {
+                Step::Input(sentence) => {
+                    if let Err(error) = nar.input_sentence(sentence) {
+                        input_errors.push((*line, error.to_string()));
+                    }
+                },
+                Step::Cycles(cycles) => {
+                    for _ in 0..self.max_cycles.map_or(*cycles, |max| max.min(*cycles)) {
+                        nar.cycle();
+                    }
+                },
+                Step::Expect(expectation) => expectations.push((*line, expectation)),
+            }
+        }
+
+        let derived = derived.lock();
+        let results = expectations
+            .into_iter()
+            .map(|(line, expectation)| {
+                let outcome = match Parser::parse_sentence(&expectation.text) {
+                    Ok(sentence) => {
+                        let found = derived.iter().any(|task| Expectation::matches(&sentence, task, self.truth_tolerance));
+                        if found != expectation.negated { Outcome::Passed } else { Outcome::Failed }
+                    },
+                    Err(error) => Outcome::Unparsed(error),
+                };
+                ExpectationResult { line, expectation: expectation.clone(), outcome }
+            })
+            .collect();
+
+        ScriptReport {
+            name: script.name().to_string(),
+            results,
+            input_errors,
+            derived: derived.len(),
+        }
+    }
+}
+
+impl Default for ScriptRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of checking one expectation
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The expectation held
+    Passed,
+
+    /// The expectation did not hold
+    Failed,
+
+    /// The expected sentence could not be parsed
+    Unparsed(ParseError),
+}
+
+/// An expectation together with its outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectationResult {
+    /// One-based line of the expectation in the script
+    pub line: usize,
+
+    /// The expectation
+    pub expectation: Expectation,
+
+    /// Whether it held
+    pub outcome: Outcome,
+}
+
+/// Pass/fail report of one script run
+#[derive(Debug, Clone)]
+pub struct ScriptReport {
+    /// Script name
+    pub name: String,
+
+    /// Outcome of every expectation, in script order
+    pub results: Vec<ExpectationResult>,
+
+    /// Input lines that could not be parsed or input, with their line numbers
+    pub input_errors: Vec<(usize, String)>,
+
+    /// Number of tasks derived during the run
+    pub derived: usize,
+}
+
+impl ScriptReport {
+    /// Number of expectations that held
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.outcome == Outcome::Passed).count()
+    }
+
+    /// Whether every expectation held
+    pub fn is_success(&self) -> bool {
+        self.passed() == self.results.len()
+    }
+}
+
+impl fmt::Display for ScriptReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_success() { "ok" } else { "FAILED" };
+        writeln!(f, "{}: {} ({}/{} expectations, {} derived)",
+                 self.name, status, self.passed(), self.results.len(), self.derived)?;
+        for result in &self.results {
+            let check = if result.expectation.negated { "must not contain" } else { "must contain" };
+            match &result.outcome {
+                Outcome::Passed => writeln!(f, "  line {}: passed: {} {}", result.line, check, result.expectation.text)?,
+                Outcome::Failed => writeln!(f, "  line {}: failed: {} {}", result.line, check, result.expectation.text)?,
+                Outcome::Unparsed(error) => writeln!(f, "  line {}: unparsed: {}", result.line, error)?,
+            }
+        }
+        for (line, error) in &self.input_errors {
+            writeln!(f, "  line {}: input error: {}", line, error)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const SCRIPT: &str = "\
+'Simultaneous events
+a. :|:
+
+b. :|:
+3
+''outputMustContain('(&|,a,b). %1.00;0.81%')
+''outputMustNotContain('(&|,a,c).')
+''outputMustContain('(&|,a,b). %0.50;0.90%')
+''print(out)
+";
+
+    #[test]
+    fn test_parse_script() {
+        let script = Script::parse("events", SCRIPT);
+        assert_eq!(script.name(), "events");
+        assert_eq!(script.steps()[0], (2, Step::Input("a. :|:".to_string())));
+        assert_eq!(script.steps()[2], (5, Step::Cycles(3)));
+        assert_eq!(script.steps()[4], (7, Step::Expect(Expectation {
+            text: "(&|,a,c).".to_string(),
+            negated: true,
+        })));
+        assert_eq!(script.steps().len(), 6);
+        assert_eq!(script.expectation_count(), 3);
+    }
+
+    #[test]
+    fn test_parse_transcript() {
+        let script = Script::parse("transcript", "  IN: <a --> b>.\n5\nOUT: <b --> a>. %1.00;0.45%\n' OUT: <a --> a>.\n");
+        assert_eq!(script.steps(), &[
+            (1, Step::Input("<a --> b>.".to_string())),
+            (2, Step::Cycles(5)),
+            (3, Step::Expect(Expectation { text: "<b --> a>. %1.00;0.45%".to_string(), negated: false })),
+        ]);
+    }
+
+    #[test]
+    fn test_run_script() {
+        // The conjunction of simultaneous beliefs keeps the time of its premises
+        let script = Script::parse("events", &SCRIPT.replace("(&|,a,b)", "(&&,a,b)").replace("(&|,a,c)", "(&&,a,c)"));
+        let report = ScriptRunner::new().run(&script);
+        let outcomes: Vec<&Outcome> = report.results.iter().map(|r| &r.outcome).collect();
+        assert_eq!(outcomes, vec![&Outcome::Passed, &Outcome::Passed, &Outcome::Failed]);
+        assert!(!report.is_success());
+        assert!(report.to_string().starts_with("events: FAILED (2/3 expectations"));
+    }
+
+    #[test]
+    fn test_report_errors() {
+        let script = Script::parse("broken", "<a -->.\n''outputMustContain('<a --> >.')\n");
+        let report = ScriptRunner::new().run(&script);
+        assert_eq!(report.input_errors.len(), 1);
+        assert_eq!(report.input_errors[0].0, 1);
+        assert!(matches!(report.results[0].outcome, Outcome::Unparsed(_)));
+    }
+
+    #[test]
+    fn test_revision_script() {
+        // Conflicting input beliefs are revised as they arrive
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("njava/docs/nal/test1/nal1.0.nal");
+        let report = ScriptRunner::new().run(&Script::load(&path).unwrap());
+        assert!(report.is_success(), "{}", report);
+    }
+
+    /// Run the conformance scripts of some NAL levels, printing each report;
+    /// returns the number of scripts, passed expectations and expectations
+    fn run_levels(levels: impl Iterator<Item = usize>, runner: &ScriptRunner) -> (usize, usize, usize) {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("njava/docs/nal");
+        let (mut scripts, mut passed, mut total) = (0, 0, 0);
+        for level in levels {
+            let mut paths: Vec<PathBuf> = fs::read_dir(root.join(format!("test{}", level)))
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|e| e == "nal"))
+                .collect();
+            paths.sort();
+            for path in paths {
+                let report = runner.run(&Script::load(&path).unwrap());
+                print!("{}", report);
+                scripts += 1;
+                passed += report.passed();
+                total += report.results.len();
+            }
+        }
+        println!("NAL parity: {}/{} expectations in {} scripts", passed, total, scripts);
+        (scripts, passed, total)
+    }
+
+    #[test]
+    fn test_nal1_nal2_scripts() {
+        let (scripts, passed, total) = run_levels(1..=2, &ScriptRunner::new());
+        assert_eq!((scripts, total), (29, 31));
+        // Floor of the current parity; raise it as the reasoner catches up
+        assert!(passed >= 8, "{}/{} expectations passed", passed, total);
+    }
+
+    /// Full parity report; run with `cargo test nal_corpus -- --nocapture`
+    #[test]
+    fn test_nal_corpus() {
+        let (scripts, passed, total) = run_levels(1..=8, &ScriptRunner::new().max_cycles(100));
+        assert_eq!((scripts, total), (189, 235));
+        assert!(passed >= 16, "{}/{} expectations passed", passed, total);
+    }
+}
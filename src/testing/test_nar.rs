@@ -0,0 +1,301 @@
+//! Fluent assertions on what a reasoner derives
+//!
+//! A port of the Java `nars.test.TestNAR`. Inputs and expectations are
+//! collected first, then `run` cycles a seeded `NAR` until the last deadline
+//! and panics with a report of everything derived if an expectation failed:
+//!
+//! ```no_run
+//! use nar::testing::TestNAR;
+//!
+//! TestNAR::new()
+//!     .believe("<a --> b>")
+//!     .believe("<b --> c>")
+//!     .must_believe(10, "<a --> c>", 0.9..=1.0, 0.7..=0.9)
+//!     .must_not_output(10, "<c --> a>", '.')
+//!     .run();
+//! ```
+
+use crate::nal::NAR;
+use crate::narsese;
+use crate::parser::Parser;
+use crate::task::{Punctuation, Task};
+use crate::term::Term;
+use parking_lot::Mutex;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Seed used by `TestNAR::new`
+pub const DEFAULT_SEED: u64 = 1;
+
+/// An expectation on the derived tasks
+#[derive(Debug, Clone)]
+struct Condition {
+    /// Whether a matching task must appear rather than must not
+    must: bool,
+    term: Term,
+    punctuation: Punctuation,
+    frequency: RangeInclusive<f32>,
+    confidence: RangeInclusive<f32>,
+    /// Last cycle at which a matching derivation counts
+    deadline: i64,
+}
+
+impl Condition {
+    fn matches(&self, task: &Task) -> bool {
+        if task.term() != &self.term || task.punctuation() != self.punctuation || task.creation_time() > self.deadline {
+            return false;
+        }
+        match task.truth() {
+            Some(truth) => self.frequency.contains(&truth.frequency()) && self.confidence.contains(&truth.confidence()),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", narsese::format(&self.term), self.punctuation)?;
+        if matches!(self.punctuation, Punctuation::Belief | Punctuation::Goal) {
+            write!(f, " %{:?}..{:?};{:?}..{:?}%",
+                   self.frequency.start(), self.frequency.end(),
+                   self.confidence.start(), self.confidence.end())?;
+        }
+        write!(f, " by cycle {}", self.deadline)
+    }
+}
+
+/// Reasoning test harness with fluent inputs and expectations
+pub struct TestNAR {
+    nar: NAR,
+    seed: u64,
+    derived: Arc<Mutex<Vec<Task>>>,
+    conditions: Vec<Condition>,
+}
+
+impl TestNAR {
+    /// Create a harness around a `NAR` seeded with `DEFAULT_SEED`
+    pub fn new() -> Self {
+        TestNAR::with_seed(DEFAULT_SEED)
+    }
+
+    /// Create a harness around a `NAR` with a specific seed
+    pub fn with_seed(seed: u64) -> Self {
+        let mut nar = NAR::with_seed(seed);
+        let derived = Arc::new(Mutex::new(Vec::new()));
+        let sink = derived.clone();
+        nar.on_derived(move |task| sink.lock().push(task.clone()));
+        TestNAR { nar, seed, derived, conditions: Vec::new() }
+    }
+
+    /// Input a Narsese sentence; panics if it does not parse
+    pub fn input(mut self, narsese: &str) -> Self {
+        if let Err(error) = self.nar.input_sentence(narsese) {
+            panic!("invalid test input\n{}", error.render(narsese));
+        }
+        self
+    }
+
+    /// Input a belief with the default truth value
+    pub fn believe(self, term: &str) -> Self {
+        self.input(&format!("{}.", term))
+    }
+
+    /// Input a belief with the given frequency and confidence
+    pub fn believe_truth(self, term: &str, frequency: f32, confidence: f32) -> Self {
+        self.input(&format!("{}. %{};{}%", term, frequency, confidence))
+    }
+
+    /// Input a goal with the default truth value
+    pub fn goal(self, term: &str) -> Self {
+        self.input(&format!("{}!", term))
+    }
+
+    /// Input a question
+    pub fn ask(self, term: &str) -> Self {
+        self.input(&format!("{}?", term))
+    }
+
+    /// Expect a belief in `term` with truth in the given ranges to be derived
+    /// within `cycles`
+    pub fn must_believe(self, cycles: usize, term: &str, frequency: RangeInclusive<f32>, confidence: RangeInclusive<f32>) -> Self {
+        self.must_output(cycles, term, '.', frequency, confidence)
+    }
+
+    /// Expect a goal for `term` with truth in the given ranges to be derived
+    /// within `cycles`
+    pub fn must_goal(self, cycles: usize, term: &str, frequency: RangeInclusive<f32>, confidence: RangeInclusive<f32>) -> Self {
+        self.must_output(cycles, term, '!', frequency, confidence)
+    }
+
+    /// Expect a task with the given punctuation character and truth ranges to
+    /// be derived within `cycles`; the ranges are ignored for questions
+    pub fn must_output(
+        self,
+        cycles: usize,
+        term: &str,
+        punctuation: char,
+        frequency: RangeInclusive<f32>,
+        confidence: RangeInclusive<f32>,
+    ) -> Self {
+        self.condition(true, cycles, term, punctuation, frequency, confidence)
+    }
+
+    /// Expect no task for `term` with the given punctuation character to be
+    /// derived within `cycles`, whatever its truth value
+    pub fn must_not_output(self, cycles: usize, term: &str, punctuation: char) -> Self {
+        self.condition(false, cycles, term, punctuation, 0.0..=1.0, 0.0..=1.0)
+    }
+
+    fn condition(
+        mut self,
+        must: bool,
+        cycles: usize,
+        term: &str,
+        punctuation: char,
+        frequency: RangeInclusive<f32>,
+        confidence: RangeInclusive<f32>,
+    ) -> Self {
+        let expected = format!("{}{}", term, punctuation);
+        let (term, _, punctuation, _) = Parser::parse_sentence(&expected)
+            .unwrap_or_else(|error| panic!("invalid expectation\n{}", error.render(&expected)));
+        let deadline = self.nar.time() + cycles as i64;
+        self.conditions.push(Condition { must, term, punctuation, frequency, confidence, deadline });
+        self
+    }
+
+    /// Run until the last deadline and check every expectation
+    pub fn report(mut self) -> TestReport {
+        let end = self.conditions.iter().map(|c| c.deadline).max().unwrap_or(self.nar.time());
+        while self.nar.time() < end {
+            self.nar.cycle();
+        }
+
+        let derived = std::mem::take(&mut *self.derived.lock());
+        let failures = self.conditions
+            .iter()
+            .filter_map(|condition| {
+                let found = derived.iter().find(|task| condition.matches(task));
+                match (condition.must, found) {
+                    (true, None) => Some(format!("missing: {}", condition)),
+                    (false, Some(task)) => Some(format!("unwanted: {} (derived at cycle {})", condition, task.creation_time())),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        TestReport { seed: self.seed, cycles: end, failures, derived }
+    }
+
+    /// Run until the last deadline; panics with a report if any expectation
+    /// failed
+    pub fn run(self) {
+        let report = self.report();
+        if !report.is_success() {
+            panic!("{}", report);
+        }
+    }
+}
+
+impl Default for TestNAR {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Outcome of a `TestNAR` run
+#[derive(Debug, Clone)]
+pub struct TestReport {
+    /// Seed of the reasoner
+    pub seed: u64,
+
+    /// Number of cycles run
+    pub cycles: i64,
+
+    /// Description of every failed expectation
+    pub failures: Vec<String>,
+
+    /// Every task derived during the run, in derivation order
+    pub derived: Vec<Task>,
+}
+
+impl TestReport {
+    /// Whether every expectation held
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_success() { "passed" } else { "failed" };
+        writeln!(f, "TestNAR {} (seed {}, {} cycles)", status, self.seed, self.cycles)?;
+        for failure in &self.failures {
+            writeln!(f, "  {}", failure)?;
+        }
+        writeln!(f, "derived {} tasks:", self.derived.len())?;
+        // Occurrences are relative to the cycle the task was derived in
+        for task in &self.derived {
+            writeln!(f, "  [{}] {}", task.creation_time(), narsese::format_task(task, task.creation_time()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_must_believe_conjunction() {
+        TestNAR::new()
+            .input("cat. :|:")
+            .input("dog. :|:")
+            .must_believe(3, "(&&,cat,dog)", 1.0..=1.0, 0.8..=0.82)
+            .must_not_output(3, "(&&,cat,bird)", '.')
+            .run();
+    }
+
+    #[test]
+    fn test_failure_report() {
+        let report = TestNAR::new()
+            .input("cat. :|:")
+            .input("dog. :|:")
+            .must_believe(2, "<cat --> dog>", 0.0..=1.0, 0.0..=1.0)
+            .must_not_output(2, "(&&,cat,dog)", '.')
+            .report();
+
+        assert!(!report.is_success());
+        assert_eq!(report.cycles, 2);
+        assert_eq!(report.failures.len(), 2);
+        assert!(report.failures[0].starts_with("missing: <cat --> dog>. %0.0..1.0;0.0..1.0% by cycle 2"));
+        assert!(report.failures[1].starts_with("unwanted: (&&,cat,dog)."));
+
+        let text = report.to_string();
+        assert!(text.starts_with("TestNAR failed (seed 1, 2 cycles)"));
+        assert!(text.contains("[1] $0.25;0.5;0.5$ (&&,cat,dog). :-1: %1.0;0.8"));
+    }
+
+    #[test]
+    fn test_seeded_runs_are_reproducible() {
+        let run = || {
+            let report = TestNAR::with_seed(42)
+                .input("a. :|:")
+                .input("b. :|:")
+                .input("c. :|:")
+                .must_not_output(5, "d", '.')
+                .report();
+            report.derived.iter().map(|task| narsese::format_task(task, task.creation_time())).collect::<Vec<_>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    #[should_panic(expected = "missing: <a --> c>")]
+    fn test_run_panics_on_failure() {
+        TestNAR::new()
+            .believe("<a --> b>")
+            .must_believe(1, "<a --> c>", 0.0..=1.0, 0.0..=1.0)
+            .run();
+    }
+}
@@ -3,7 +3,26 @@
 //! This module handles temporal aspects of NARS including time tracking,
 //! temporal reasoning, and time-related operations.
 
+use parking_lot::Mutex;
 use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Source of the current time of a reasoner
+///
+/// `Time` counts reasoning cycles; `RealTime` follows the wall clock.
+pub trait Clock: Send + Sync {
+    /// Get the current time
+    fn now(&self) -> i64;
+    
+    /// Note that a reasoning cycle passed; returns the new time
+    fn next(&self) -> i64;
+    
+    /// Get the duration of an event, in units of time
+    fn dur(&self) -> f32;
+    
+    /// Start counting from 0 again
+    fn reset(&self);
+}
 
 /// Time management system for NARS
 pub struct Time {
@@ -69,6 +88,78 @@ impl Default for Time {
     }
 }
 
+/// Counts one unit of time per reasoning cycle
+impl Clock for Time {
+    fn now(&self) -> i64 {
+        Time::now(self)
+    }
+    
+    fn next(&self) -> i64 {
+        Time::next(self)
+    }
+    
+    fn dur(&self) -> f32 {
+        Time::dur(self)
+    }
+    
+    fn reset(&self) {
+        Time::reset(self)
+    }
+}
+
+/// Wall-clock time in whole units since the clock started, however many
+/// cycles ran meanwhile
+pub struct RealTime {
+    /// When time 0 was
+    start: Mutex<Instant>,
+    
+    /// Length of one unit of time
+    unit: Duration,
+    
+    /// Duration of an event, in units
+    duration: f32,
+}
+
+impl RealTime {
+    /// Create a clock counting units of the given length from now
+    pub fn new(unit: Duration) -> Self {
+        RealTime {
+            start: Mutex::new(Instant::now()),
+            unit: unit.max(Duration::from_nanos(1)),
+            duration: 1.0,
+        }
+    }
+    
+    /// Create a clock counting milliseconds
+    pub fn millis() -> Self {
+        RealTime::new(Duration::from_millis(1))
+    }
+    
+    /// Set the duration of an event, in units
+    pub fn with_dur(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+impl Clock for RealTime {
+    fn now(&self) -> i64 {
+        (self.start.lock().elapsed().as_nanos() / self.unit.as_nanos()) as i64
+    }
+    
+    fn next(&self) -> i64 {
+        self.now()
+    }
+    
+    fn dur(&self) -> f32 {
+        self.duration
+    }
+    
+    fn reset(&self) {
+        *self.start.lock() = Instant::now();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,6 +200,20 @@ mod tests {
         assert_eq!(stamp2, 2);
     }
     
+    #[test]
+    fn test_real_time() {
+        let clock = RealTime::new(Duration::from_secs(3600)).with_dur(5.0);
+        assert_eq!(Clock::now(&clock), 0);
+        assert_eq!(Clock::next(&clock), 0); // cycles do not move it
+        assert_eq!(Clock::dur(&clock), 5.0);
+        
+        let clock = RealTime::millis();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(Clock::now(&clock) >= 20);
+        clock.reset();
+        assert!(Clock::now(&clock) < 20);
+    }
+    
     #[test]
     fn test_relative_occurrence() {
         let time = Time::new();
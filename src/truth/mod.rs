@@ -4,13 +4,16 @@
 //! They consist of two components:
 //! - Frequency (f): The estimated probability of the statement being true
 //! - Confidence (c): The weight of evidence supporting the estimation
+//!
+//! Both are stored rounded to a precision: `Truth::EPSILON` for values
+//! made with `Truth::new`, the configured epsilons for values made by a
+//! `TruthConfig`. Equality and hashing compare the rounded values.
 
 use std::fmt;
-use std::hash::{Hash, Hasher};
 use ordered_float::OrderedFloat;
 
 /// Truth value representation
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Truth {
     /// Frequency: probability estimate [0.0, 1.0]
     frequency: OrderedFloat<f32>,
@@ -20,15 +23,20 @@ pub struct Truth {
 }
 
 impl Truth {
-    /// Create a new truth value
+    /// Resolution of frequency and confidence of truth values made with
+    /// `Truth::new`
+    pub const EPSILON: f32 = 0.0001;
+
+    /// Create a new truth value, rounded to `Truth::EPSILON`
     pub fn new(frequency: f32, confidence: f32) -> Self {
-        // Clamp values to valid ranges
-        let f = frequency.clamp(0.0, 1.0);
-        let c = confidence.clamp(0.0, 1.0);
-        
+        Truth::rounded(frequency, confidence, Truth::EPSILON, Truth::EPSILON)
+    }
+    
+    /// Clamp to the valid ranges and round to the given resolutions
+    fn rounded(frequency: f32, confidence: f32, frequency_epsilon: f32, confidence_epsilon: f32) -> Self {
         Truth {
-            frequency: OrderedFloat(f),
-            confidence: OrderedFloat(c),
+            frequency: OrderedFloat(quantize(frequency.clamp(0.0, 1.0), frequency_epsilon)),
+            confidence: OrderedFloat(quantize(confidence.clamp(0.0, 1.0), confidence_epsilon)),
         }
     }
     
@@ -42,19 +50,16 @@ impl Truth {
         self.confidence.0
     }
     
-    /// Calculate evidence amount from confidence
-    pub fn evidence(&self) -> f64 {
-        // Convert confidence to evidence using c = e / (e + k)
-        // where k is a parameter (typically 1.0 in NARS)
-        let c = self.confidence.0 as f64;
-        c / (1.0 - c)
+    /// Calculate evidence amount from confidence under a horizon; see
+    /// `TruthConfig::evidence`
+    pub fn evidence(&self, config: &TruthConfig) -> f64 {
+        config.evidence(self)
     }
     
-    /// Create a truth value from evidence amount
-    pub fn from_evidence(freq: f32, evidence: f64) -> Self {
-        // Convert evidence to confidence using c = e / (e + k)
-        let conf = (evidence / (evidence + 1.0)) as f32;
-        Truth::new(freq, conf)
+    /// Create a truth value from evidence amount under a horizon; see
+    /// `TruthConfig::from_evidence`
+    pub fn from_evidence(freq: f32, evidence: f64, config: &TruthConfig) -> Self {
+        config.from_evidence(freq, evidence)
     }
     
     /// Get the expected value (frequency * confidence)
@@ -79,100 +84,355 @@ impl Truth {
         Truth::new(1.0 - self.frequency.0, self.confidence.0)
     }
     
+    /// What the truth value observed at `from` says about `to`, as seen at
+    /// `now`: the frequency stays, the confidence decays with the distance
+    /// along the configured curve
+    pub fn project(&self, from: i64, to: i64, now: i64, dur: f32, config: &TruthConfig) -> Self {
+        let factor = config.decay.factor(from, to, now, dur);
+        config.truth(self.frequency(), self.confidence() * factor)
+    }
+    
+    /// Generalize an observation to a timeless truth value: the confidence
+    /// of the event counts as a single piece of evidence of that weight
+    pub fn eternalize(&self, config: &TruthConfig) -> Self {
+        config.truth(self.frequency(), config.confidence(self.confidence() as f64))
+    }
+    
     /// Deduction: C1 and (C1 ==> C2) |- C2
-    pub fn deduction(a: &Truth, b: &Truth) -> Self {
+    pub fn deduction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
         let f = a.frequency() * b.frequency();
         let c = a.confidence() * b.confidence() * f;
-        Truth::new(f, c)
+        config.truth(f, c)
     }
     
-    /// Induction: C1 and C2 |- C1 ==> C2
-    pub fn induction(a: &Truth, b: &Truth) -> Self {
-        let f = a.frequency() * b.frequency();
-        let c = a.confidence() * b.confidence() * f / (a.frequency() * b.frequency() + 1.0);
-        Truth::new(f, c)
+    /// Induction: C1 and C2 |- C1 ==> C2; the frequency of the first
+    /// premise, backed by as much evidence as the second lends it
+    pub fn induction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let w = b.frequency() * a.confidence() * b.confidence();
+        config.from_evidence(a.frequency(), w as f64)
     }
     
     /// Comparison: C1 and C2 |- C1 <-> C2
-    pub fn comparison(a: &Truth, b: &Truth) -> Self {
-        let f = a.frequency() * b.frequency() / (a.frequency() * b.frequency() + (1.0 - a.frequency()) * (1.0 - b.frequency()));
-        let c = a.confidence() * b.confidence() * f;
-        Truth::new(f, c)
+    pub fn comparison(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let either = or(a.frequency(), b.frequency());
+        let f = if either > 0.0 { a.frequency() * b.frequency() / either } else { 0.0 };
+        let w = either * a.confidence() * b.confidence();
+        config.from_evidence(f, w as f64)
     }
     
     /// Conjunction: C1 and C2 |- C1 && C2
-    pub fn conjunction(a: &Truth, b: &Truth) -> Self {
+    pub fn conjunction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
         let f = a.frequency() * b.frequency();
         let c = a.confidence() * b.confidence();
-        Truth::new(f, c)
+        config.truth(f, c)
     }
     
     /// Disjunction: C1 and C2 |- C1 || C2
-    pub fn disjunction(a: &Truth, b: &Truth) -> Self {
+    pub fn disjunction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
         let f = a.frequency() + b.frequency() - a.frequency() * b.frequency();
         let c = a.confidence() * b.confidence();
-        Truth::new(f, c)
+        config.truth(f, c)
     }
     
     /// Revision: C1 and C2 |- C3 (revised belief)
-    pub fn revision(a: &Truth, b: &Truth) -> Self {
-        let w1 = a.evidence();
-        let w2 = b.evidence();
+    pub fn revision(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let w1 = config.evidence(a);
+        let w2 = config.evidence(b);
         let w = w1 + w2;
         let f = (w1 * a.frequency() as f64 + w2 * b.frequency() as f64) / w;
-        let _c = w / (w + 1.0);
-        Truth::from_evidence(f as f32, w)
+        config.from_evidence(f as f32, w)
     }
     
-    /// Abduction: C2 and (C1 ==> C2) |- C1
-    pub fn abduction(a: &Truth, b: &Truth) -> Self {
-        let f = a.frequency() * b.frequency();
-        let c = a.confidence() * b.confidence() * f / (b.frequency() * b.frequency() + 1.0);
-        Truth::new(f, c)
+    /// Abduction: C2 and (C1 ==> C2) |- C1; induction with the premises
+    /// swapped
+    pub fn abduction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::induction(b, a, config)
     }
     
     /// Exemplification: C1 and C2 |- C2 ==> C1
-    pub fn exemplification(a: &Truth, b: &Truth) -> Self {
-        let f = 1.0 - a.frequency() * b.frequency();
-        let c = a.confidence() * b.confidence() * f / (a.frequency() * b.frequency() + 1.0);
-        Truth::new(f, c)
+    pub fn exemplification(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let w = a.frequency() * b.frequency() * a.confidence() * b.confidence();
+        config.from_evidence(1.0, w as f64)
     }
     
     /// Analogy: (C1 ==> C2) and (C2 ==> C3) |- (C1 ==> C3)
-    pub fn analogy(a: &Truth, b: &Truth) -> Self {
+    pub fn analogy(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
         let f = a.frequency() * b.frequency();
         let c = a.confidence() * b.confidence() * f;
-        Truth::new(f, c)
+        config.truth(f, c)
+    }
+
+    /// Resemblance: (C1 <=> C2) and (C2 <=> C3) |- (C1 <=> C3)
+    pub fn resemblance(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let f = a.frequency() * b.frequency();
+        let c = a.confidence() * b.confidence() * or(a.frequency(), b.frequency());
+        config.truth(f, c)
+    }
+
+    /// Intersection: (M --> S) and (M --> P) |- (M --> (S & P))
+    pub fn intersection(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        config.truth(a.frequency() * b.frequency(), a.confidence() * b.confidence())
+    }
+
+    /// Union: (M --> S) and (M --> P) |- (M --> (S | P))
+    pub fn union(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        config.truth(or(a.frequency(), b.frequency()), a.confidence() * b.confidence())
+    }
+
+    /// Difference: (M --> S) and (M --> P) |- (M --> (S - P))
+    pub fn difference(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        config.truth(a.frequency() * (1.0 - b.frequency()), a.confidence() * b.confidence())
+    }
+
+    /// Reduce conjunction: --(C1 && C2) and C1 |- --C2
+    pub fn reduce_conjunction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let f = (1.0 - a.frequency()) * b.frequency();
+        config.truth(1.0 - f, f * a.confidence() * b.confidence())
+    }
+
+    /// Reduce disjunction: (C1 || C2) and --C2 |- C1
+    pub fn reduce_disjunction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let f = a.frequency() * (1.0 - b.frequency());
+        config.truth(f, f * a.confidence() * b.confidence())
+    }
+
+    /// Decomposition with the polarity of each premise and of the
+    /// conclusion; `true` is positive, `false` negative
+    pub fn decompose(a: &Truth, b: &Truth, a_positive: bool, b_positive: bool, positive: bool, config: &TruthConfig) -> Self {
+        let fa = if a_positive { a.frequency() } else { 1.0 - a.frequency() };
+        let fb = if b_positive { b.frequency() } else { 1.0 - b.frequency() };
+        let f = fa * fb;
+        let c = f * a.confidence() * b.confidence();
+        config.truth(if positive { f } else { 1.0 - f }, c)
+    }
+
+    /// Decomposition of two positive premises into a positive conclusion
+    pub fn decompose_pp(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::decompose(a, b, true, true, true, config)
+    }
+
+    /// Decomposition of a positive and a negative premise into a negative
+    /// conclusion
+    pub fn decompose_pn(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::decompose(a, b, true, false, false, config)
+    }
+
+    /// Decomposition of a negative and a positive premise into a positive
+    /// conclusion
+    pub fn decompose_np(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::decompose(a, b, false, true, true, config)
+    }
+
+    /// Decomposition of two negative premises into a negative conclusion
+    pub fn decompose_nn(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::decompose(a, b, false, false, false, config)
+    }
+
+    /// Contraposition: (S ==> P) |- (--P ==> --S), with frequency 0 and the
+    /// evidence `f * c` of the premise
+    pub fn contraposition(a: &Truth, config: &TruthConfig) -> Self {
+        let w = a.frequency() * a.confidence();
+        config.from_evidence(0.0, w as f64)
+    }
+
+    /// Conversion: (S --> P) |- (P --> S)
+    pub fn conversion(a: &Truth, config: &TruthConfig) -> Self {
+        let w = a.frequency() * a.confidence();
+        config.from_evidence(1.0, w as f64)
+    }
+
+    /// Deduction with a negated belief: the belief is about the negation of
+    /// what the conclusion uses
+    pub fn deduction_pn(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::deduction(a, &b.neg(), config)
+    }
+
+    /// Deduction with a negated task
+    pub fn deduction_np(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::deduction(&a.neg(), b, config)
+    }
+
+    /// Abduction with a negated belief
+    pub fn abduction_pn(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::abduction(a, &b.neg(), config)
+    }
+
+    /// Abduction with a negated task
+    pub fn abduction_np(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        Truth::abduction(&a.neg(), b, config)
+    }
+
+    /// Strong desire: goal C2 and belief (C1 ==> C2) |- goal C1
+    pub fn desire_strong(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let f = a.frequency() * b.frequency();
+        let c = a.confidence() * b.confidence() * b.frequency();
+        config.truth(f, c)
+    }
+
+    /// Weak desire: as strong desire, with the confidence of a single piece
+    /// of evidence
+    pub fn desire_weak(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let f = a.frequency() * b.frequency();
+        let c = a.confidence() * b.confidence() * b.frequency() * config.confidence(1.0);
+        config.truth(f, c)
+    }
+
+    /// Desire deduction: goal C1 and belief (C1 ==> C2) |- goal C2
+    pub fn desire_deduction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        config.truth(a.frequency() * b.frequency(), a.confidence() * b.confidence())
+    }
+
+    /// Desire induction: goal C1 and belief C2 |- goal (C2 ==> C1)
+    pub fn desire_induction(a: &Truth, b: &Truth, config: &TruthConfig) -> Self {
+        let w = b.frequency() * a.confidence() * b.confidence();
+        config.from_evidence(a.frequency(), w as f64)
     }
 }
 
-impl fmt::Display for Truth {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", 
-               (self.frequency.0 * 100.0).round() / 100.0, 
-               (self.confidence.0 * 100.0).round() / 100.0)
+/// Evidential horizon and precision shared by every truth function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TruthConfig {
+    /// Evidential horizon k: confidence is w / (w + k) for evidence w
+    pub horizon: f32,
+
+    /// Resolution of frequencies
+    pub frequency_epsilon: f32,
+
+    /// Resolution of confidences
+    pub confidence_epsilon: f32,
+
+    /// Highest confidence any computed truth value may have
+    pub max_confidence: f32,
+
+    /// How confidence fades when a truth value is projected in time
+    pub decay: Decay,
+
+    /// Perceptual duration: the time span the decay is measured in
+    pub duration: f32,
+}
+
+impl TruthConfig {
+    /// Settings of OpenNARS: horizon 1, two decimal places, confidence at
+    /// most 0.99, projection relative to the present
+    pub fn opennars() -> Self {
+        TruthConfig {
+            frequency_epsilon: 0.01,
+            confidence_epsilon: 0.01,
+            max_confidence: 0.99,
+            decay: Decay::Relative,
+            ..TruthConfig::default()
+        }
+    }
+
+    /// Build a truth value at this precision, confidence capped
+    pub fn truth(&self, frequency: f32, confidence: f32) -> Truth {
+        let mut truth = Truth::rounded(frequency, confidence.min(self.max_confidence), self.frequency_epsilon, self.confidence_epsilon);
+        truth.confidence = truth.confidence.min(OrderedFloat(self.max_confidence));
+        truth
+    }
+
+    /// Bring a truth value, e.g. an input one, to this precision
+    pub fn quantize(&self, truth: &Truth) -> Truth {
+        self.truth(truth.frequency(), truth.confidence())
+    }
+
+    /// Check if two truth values fall on the same step of this precision
+    pub fn same(&self, a: &Truth, b: &Truth) -> bool {
+        self.quantize(a) == self.quantize(b)
+    }
+
+    /// Amount of evidence behind a truth value
+    pub fn evidence(&self, truth: &Truth) -> f64 {
+        let c = truth.confidence() as f64;
+        self.horizon as f64 * c / (1.0 - c)
+    }
+
+    /// Confidence of an amount of evidence
+    pub fn confidence(&self, evidence: f64) -> f32 {
+        (evidence / (evidence + self.horizon as f64)) as f32
+    }
+
+    /// Truth value with a frequency and an amount of evidence
+    pub fn from_evidence(&self, frequency: f32, evidence: f64) -> Truth {
+        self.truth(frequency, self.confidence(evidence))
     }
 }
 
-impl Hash for Truth {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        // Hash with reduced precision to account for floating point errors
-        let freq_hash = (self.frequency.0 * 10000.0) as i32;
-        let conf_hash = (self.confidence.0 * 10000.0) as i32;
-        freq_hash.hash(state);
-        conf_hash.hash(state);
+/// Horizon 1, the precision of `Truth::new`, and inverse decay over a
+/// duration of one cycle
+impl Default for TruthConfig {
+    fn default() -> Self {
+        TruthConfig {
+            horizon: 1.0,
+            frequency_epsilon: Truth::EPSILON,
+            confidence_epsilon: Truth::EPSILON,
+            max_confidence: 1.0,
+            decay: Decay::Inverse,
+            duration: 1.0,
+        }
     }
 }
 
-impl PartialEq for Truth {
-    fn eq(&self, other: &Self) -> bool {
-        // Compare with reduced precision to account for floating point errors
-        (self.frequency.0 - other.frequency.0).abs() < 0.0001 &&
-        (self.confidence.0 - other.confidence.0).abs() < 0.0001
+/// Curve by which a truth value loses confidence as it is projected away
+/// from the time it was observed at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Decay {
+    /// Confidence divided by 1 + dt / dur
+    #[default]
+    Inverse,
+
+    /// Confidence scaled by e^(-dt / dur)
+    Exponential,
+
+    /// Confidence scaled by 1 - dt / (|from - now| + |to - now|), as in
+    /// OpenNARS: projecting between two times far from the present costs
+    /// little, reaching from the present into the past or future costs much
+    Relative,
+}
+
+impl Decay {
+    /// Factor confidence is scaled by when moving a truth value from one
+    /// time to another
+    pub fn factor(self, from: i64, to: i64, now: i64, dur: f32) -> f32 {
+        let dt = from.abs_diff(to) as f32;
+        if dt == 0.0 {
+            return 1.0;
+        }
+        let dur = dur.max(f32::EPSILON);
+        match self {
+            Decay::Inverse => 1.0 / (1.0 + dt / dur),
+            Decay::Exponential => (-dt / dur).exp(),
+            Decay::Relative => {
+                let span = (from.abs_diff(now) + to.abs_diff(now)) as f32;
+                1.0 - dt / span
+            },
+        }
     }
 }
 
-impl Eq for Truth {}
+/// Round to the nearest multiple of a step; steps such as 0.01 that
+/// divide 1 land exactly on the decimal values
+fn quantize(value: f32, epsilon: f32) -> f32 {
+    if epsilon <= 0.0 {
+        return value;
+    }
+    let steps = 1.0 / epsilon as f64;
+    let steps = if (steps - steps.round()).abs() < 0.001 { steps.round() } else { steps };
+    ((value as f64 * steps).round() / steps) as f32
+}
+
+/// Probabilistic sum, the fuzzy or
+fn or(a: f32, b: f32) -> f32 {
+    1.0 - (1.0 - a) * (1.0 - b)
+}
+
+impl fmt::Display for Truth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", 
+               (self.frequency.0 * 100.0).round() / 100.0, 
+               (self.confidence.0 * 100.0).round() / 100.0)
+    }
+}
 
 /// Common truth values
 impl Truth {
@@ -231,10 +491,10 @@ mod tests {
     #[test]
     fn test_evidence_conversion() {
         let truth = Truth::new(0.8, 0.5);
-        let evidence = truth.evidence();
+        let evidence = truth.evidence(&TruthConfig::default());
         assert!((evidence - 1.0).abs() < 0.0001);
         
-        let truth2 = Truth::from_evidence(0.7, 3.0);
+        let truth2 = Truth::from_evidence(0.7, 3.0, &TruthConfig::default());
         assert!((truth2.confidence() - 0.75).abs() < 0.0001);
     }
 
@@ -273,7 +533,7 @@ mod tests {
    fn test_deduction() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::deduction(&a, &b);
+       let result = Truth::deduction(&a, &b, &TruthConfig::default());
        assert!((result.frequency() - 0.72).abs() < 0.0001);
        assert!((result.confidence() - 0.5184).abs() < 0.0001);
    }
@@ -282,27 +542,25 @@ mod tests {
    fn test_induction() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::induction(&a, &b);
-       assert!((result.frequency() - 0.72).abs() < 0.0001);
-       // The exact value depends on the implementation, but it should be less than the product
-       assert!(result.confidence() < 0.65);
+       let result = Truth::induction(&a, &b, &TruthConfig::default());
+       assert!((result.frequency() - 0.9).abs() < 0.0001);
+       assert!((result.confidence() - 0.365482).abs() < 0.0001);
    }
    
    #[test]
    fn test_comparison() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::comparison(&a, &b);
-       // The exact value depends on the implementation
-       assert!(result.frequency() > 0.97 && result.frequency() < 0.98);
-       assert!(result.confidence() > 0.7 && result.confidence() < 0.71);
+       let result = Truth::comparison(&a, &b, &TruthConfig::default());
+       assert!((result.frequency() - 0.734694).abs() < 0.0001);
+       assert!((result.confidence() - 0.413696).abs() < 0.0001);
    }
    
    #[test]
    fn test_conjunction() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::conjunction(&a, &b);
+       let result = Truth::conjunction(&a, &b, &TruthConfig::default());
        assert!((result.frequency() - 0.72).abs() < 0.0001);
        assert!((result.confidence() - 0.72).abs() < 0.0001);
    }
@@ -311,7 +569,7 @@ mod tests {
    fn test_disjunction() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::disjunction(&a, &b);
+       let result = Truth::disjunction(&a, &b, &TruthConfig::default());
        assert!((result.frequency() - 0.98).abs() < 0.0001);
        assert!((result.confidence() - 0.72).abs() < 0.0001);
    }
@@ -320,7 +578,7 @@ mod tests {
    fn test_revision() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::revision(&a, &b);
+       let result = Truth::revision(&a, &b, &TruthConfig::default());
        // The exact value depends on the evidence calculation
        assert!(result.frequency() > 0.8 && result.frequency() < 0.9);
        assert!(result.confidence() > 0.8);
@@ -330,28 +588,168 @@ mod tests {
    fn test_abduction() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::abduction(&a, &b);
-       assert!((result.frequency() - 0.72).abs() < 0.0001);
-       // The exact value depends on the implementation, but it should be less than the product
-       assert!(result.confidence() < 0.65);
+       let result = Truth::abduction(&a, &b, &TruthConfig::default());
+       assert!((result.frequency() - 0.8).abs() < 0.0001);
+       assert!((result.confidence() - 0.393204).abs() < 0.0001);
    }
    
    #[test]
    fn test_exemplification() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::exemplification(&a, &b);
-       assert!((result.frequency() - 0.28).abs() < 0.0001);
-       // The exact value depends on the implementation
-       assert!(result.confidence() < 0.3);
+       let result = Truth::exemplification(&a, &b, &TruthConfig::default());
+       assert!((result.frequency() - 1.0).abs() < 0.0001);
+       assert!((result.confidence() - 0.341412).abs() < 0.0001);
+   }
+   
+   // The weak syllogisms of nal1.2, nal1.3 and nal1.4: evidence is the
+   // frequency lent by one premise times both confidences
+   #[test]
+   fn test_weak_syllogisms() {
+       let config = TruthConfig::opennars();
+       let (sport, chess) = (Truth::new(1.0, 0.9), Truth::new(0.9, 0.9));
+       assert_eq!(Truth::abduction(&chess, &sport, &config), Truth::new(1.0, 0.42));
+       assert_eq!(Truth::abduction(&sport, &chess, &config), Truth::new(0.9, 0.45));
+       assert_eq!(Truth::induction(&chess, &sport, &config), Truth::new(0.9, 0.45));
+       assert_eq!(Truth::exemplification(&sport, &sport, &config), Truth::new(1.0, 0.45));
    }
    
    #[test]
    fn test_analogy() {
        let a = Truth::new(0.9, 0.9);
        let b = Truth::new(0.8, 0.8);
-       let result = Truth::analogy(&a, &b);
+       let result = Truth::analogy(&a, &b, &TruthConfig::default());
        assert!((result.frequency() - 0.72).abs() < 0.0001);
        assert!((result.confidence() - 0.5184).abs() < 0.0001);
    }
-}
\ No newline at end of file
+   
+   fn assert_truth(result: Truth, f: f32, c: f32) {
+       assert!((result.frequency() - f).abs() < 0.0001, "frequency {} != {}", result.frequency(), f);
+       assert!((result.confidence() - c).abs() < 0.0001, "confidence {} != {}", result.confidence(), c);
+   }
+
+   // Values for (0.9, 0.9) and (0.8, 0.8) by the formulas of
+   // njava/nar/src/main/java/nars/TruthFunctions.java
+   #[test]
+   fn test_set_functions() {
+       let a = Truth::new(0.9, 0.9);
+       let b = Truth::new(0.8, 0.8);
+       assert_truth(Truth::resemblance(&a, &b, &TruthConfig::default()), 0.72, 0.7056);
+       assert_truth(Truth::intersection(&a, &b, &TruthConfig::default()), 0.72, 0.72);
+       assert_truth(Truth::union(&a, &b, &TruthConfig::default()), 0.98, 0.72);
+       assert_truth(Truth::difference(&a, &b, &TruthConfig::default()), 0.18, 0.72);
+   }
+
+   #[test]
+   fn test_reduce() {
+       let a = Truth::new(0.9, 0.9);
+       let b = Truth::new(0.8, 0.8);
+       assert_truth(Truth::reduce_conjunction(&a, &b, &TruthConfig::default()), 0.92, 0.0576);
+       assert_truth(Truth::reduce_disjunction(&a, &b, &TruthConfig::default()), 0.18, 0.1296);
+   }
+
+   #[test]
+   fn test_decompose() {
+       let a = Truth::new(0.9, 0.9);
+       let b = Truth::new(0.8, 0.8);
+       assert_truth(Truth::decompose_pp(&a, &b, &TruthConfig::default()), 0.72, 0.5184);
+       assert_truth(Truth::decompose_pn(&a, &b, &TruthConfig::default()), 0.82, 0.1296);
+       assert_truth(Truth::decompose_np(&a, &b, &TruthConfig::default()), 0.08, 0.0576);
+       assert_truth(Truth::decompose_nn(&a, &b, &TruthConfig::default()), 0.98, 0.0144);
+   }
+
+   #[test]
+   fn test_single_premise() {
+       let a = Truth::new(0.9, 0.9);
+       assert_truth(Truth::contraposition(&a, &TruthConfig::default()), 0.0, 0.447514);
+       assert_truth(Truth::conversion(&a, &TruthConfig::default()), 1.0, 0.447514);
+   }
+
+   #[test]
+   fn test_negated_premises() {
+       let a = Truth::new(0.9, 0.9);
+       let b = Truth::new(0.8, 0.8);
+       assert_truth(Truth::deduction_pn(&a, &b, &TruthConfig::default()), 0.18, 0.1296);
+       assert_truth(Truth::deduction_np(&a, &b, &TruthConfig::default()), 0.08, 0.0576);
+       assert_truth(Truth::abduction_pn(&a, &b, &TruthConfig::default()), 0.2, 0.393204);
+       assert_truth(Truth::abduction_np(&a, &b, &TruthConfig::default()), 0.8, 0.067164);
+   }
+
+   #[test]
+   fn test_desire() {
+       let a = Truth::new(0.9, 0.9);
+       let b = Truth::new(0.8, 0.8);
+       assert_truth(Truth::desire_strong(&a, &b, &TruthConfig::default()), 0.72, 0.576);
+       assert_truth(Truth::desire_weak(&a, &b, &TruthConfig::default()), 0.72, 0.288);
+       assert_truth(Truth::desire_deduction(&a, &b, &TruthConfig::default()), 0.72, 0.72);
+       assert_truth(Truth::desire_induction(&a, &b, &TruthConfig::default()), 0.9, 0.365482);
+   }
+   
+   #[test]
+   fn test_config_precision() {
+       // nal1.0.nal: %1.0;0.9% revised with %0.1;0.6%
+       let config = TruthConfig::opennars();
+       let revised = Truth::revision(&Truth::new(1.0, 0.9), &Truth::new(0.1, 0.6), &config);
+       assert_eq!(revised, Truth::new(0.87, 0.91));
+       assert_eq!(config.quantize(&Truth::new(1.0, 1.0)), Truth::new(1.0, 0.99));
+       assert!(config.same(&Truth::new(0.801, 0.9), &Truth::new(0.804, 0.9)));
+       assert!(!config.same(&Truth::new(0.80, 0.9), &Truth::new(0.81, 0.9)));
+       // Values made under a precision are equal, and hash alike, on the
+       // same step of it
+       let set: std::collections::HashSet<Truth> = [config.truth(0.801, 0.9), config.truth(0.804, 0.9)].into_iter().collect();
+       assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![Truth::new(0.8, 0.9)]);
+       assert_ne!(config.truth(0.804, 0.9), config.truth(0.806, 0.9));
+   }
+
+   #[test]
+   fn test_config_horizon() {
+       let config = TruthConfig { horizon: 2.0, ..TruthConfig::default() };
+       let truth = Truth::new(1.0, 0.5);
+       assert!((config.evidence(&truth) - 2.0).abs() < 0.0001);
+       assert!((config.confidence(2.0) - 0.5).abs() < 0.0001);
+       let revised = Truth::revision(&truth, &truth, &config);
+       assert!((revised.confidence() - 2.0 / 3.0).abs() < 0.0001);
+       // The same evidence is worth less confidence under a longer horizon
+       assert!((Truth::conversion(&truth, &config).confidence() - 0.2).abs() < 0.0001);
+       assert!((Truth::conversion(&truth, &TruthConfig::default()).confidence() - 1.0 / 3.0).abs() < 0.0001);
+       assert!((Truth::induction(&truth, &truth, &config).confidence() - 0.25 / 2.25).abs() < 0.0001);
+       assert!((truth.evidence(&config) - 2.0).abs() < 0.0001);
+   }
+
+   #[test]
+   fn test_hash_matches_equality() {
+       use std::collections::HashSet;
+       let set: HashSet<Truth> = [Truth::new(0.8, 0.9), Truth::new(0.80001, 0.89999)].into_iter().collect();
+       assert_eq!(set.len(), 1);
+   }
+   
+   #[test]
+   fn test_project() {
+       let truth = Truth::new(0.8, 0.9);
+       let config = TruthConfig::default();
+       assert_eq!(truth.project(100, 100, 120, 1.0, &config), truth);
+       let projected = truth.project(100, 150, 100, 10.0, &config);
+       assert_eq!(projected.frequency(), 0.8);
+       assert!((projected.confidence() - 0.15).abs() < 0.0001);
+       // Further is weaker, in either direction
+       assert!(truth.project(100, 200, 100, 10.0, &config).confidence() < projected.confidence());
+       assert_eq!(truth.project(100, 50, 100, 10.0, &config), projected);
+
+       let exponential = TruthConfig { decay: Decay::Exponential, ..config };
+       assert!((truth.project(0, 10, 0, 10.0, &exponential).confidence() - 0.9 * (-1.0f32).exp()).abs() < 0.0001);
+
+       // Relative to the present: between two past times far away it
+       // barely fades, from the present into the past it is gone
+       let relative = TruthConfig { decay: Decay::Relative, ..config };
+       assert!((truth.project(0, 10, 1000, 1.0, &relative).confidence() - 0.9 * (1.0 - 10.0 / 1990.0)).abs() < 0.0001);
+       assert_eq!(truth.project(1000, 990, 1000, 1.0, &relative).confidence(), 0.0);
+   }
+
+   #[test]
+   fn test_eternalize() {
+       let eternal = Truth::new(0.8, 0.9).eternalize(&TruthConfig::default());
+       assert_eq!(eternal.frequency(), 0.8);
+       assert!((eternal.confidence() - 0.9 / 1.9).abs() < 0.0001);
+       assert!(Truth::new(0.8, 0.9).eternalize(&TruthConfig { horizon: 2.0, ..TruthConfig::default() }).confidence() < eternal.confidence());
+   }
+}